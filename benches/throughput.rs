@@ -0,0 +1,37 @@
+// Headless emulation speed: runs Blargg's cpu_instrs for as many frames as it takes
+// to finish all of its tests, a few times, and reports frames per second.
+// Run with `cargo bench --no-default-features --features optimize`
+use std::time::{Duration, Instant};
+
+use rmg_001::config::EmulatorConfig;
+use rmg_001::emulator::Emulator;
+
+const ROM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/roms/cpu_instrs.gb");
+const FRAMES: usize = 3300;
+const RUNS: usize = 5;
+
+fn run(data: &[u8]) -> Duration {
+    let mut emulator = Emulator::from_rom_bytes(data.to_vec(), None, EmulatorConfig::new()).unwrap();
+    let mut frame = vec![0; 144 * 160 * 4];
+    let start = Instant::now();
+    for _ in 0..FRAMES {
+        emulator.run_frame(&mut frame);
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let data = std::fs::read(ROM).unwrap();
+    let mut times: Vec<Duration> = (0..RUNS).map(|_| run(&data)).collect();
+    times.sort();
+    let fps = |time: Duration| FRAMES as f64 / time.as_secs_f64();
+    println!(
+        "{} frames, {} runs: median {:.2?} ({:.0} fps), best {:.2?} ({:.0} fps)",
+        FRAMES,
+        RUNS,
+        times[RUNS / 2],
+        fps(times[RUNS / 2]),
+        times[0],
+        fps(times[0]),
+    );
+}
@@ -1,6 +1,52 @@
+use std::env;
+use rmg_001::config::{EmulatorConfig, HardwareModel, FramePacing};
+use rmg_001::emulator::Emulator;
+#[cfg(feature = "camera-png")]
+use rmg_001::camera;
 use rmg_001::render::start_eventloop;
 
+fn is_env_set(name: &str) -> bool {
+    env::var(name).is_ok()
+}
+
 fn main() -> std::io::Result<()> {
-    start_eventloop();
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 2 {
+        eprintln!("Please, specify a ROM file");
+        std::process::exit(1);
+    }
+    let mut config = EmulatorConfig::new()
+        .with_hardware_model(match is_env_set("FORCE_DMG") {
+            true => HardwareModel::DMG,
+            false => HardwareModel::CGB,
+        })
+        .with_audio(is_env_set("SOUND_ENABLE"))
+        .with_cpu_trace(is_env_set("CPU_LOG") || is_env_set("CPU_LOGS"))
+        .with_frame_pacing(match is_env_set("UNLOCK_FPS") {
+            true => FramePacing::Unlocked,
+            false => FramePacing::Limited,
+        })
+        // Snapshot every 4 frames, keeping around 40 seconds of history
+        .with_rewind(4, 600);
+    // An IPS, UPS or BPS patch can follow the ROM
+    if let Some(patch) = args.get(2) {
+        config = config.with_patch(patch);
+    }
+    let mut emulator = match Emulator::from_file(&args[1], config) {
+        Ok(emulator) => emulator,
+        Err(err) => {
+            eprintln!("Could not read ROM: {}", err);
+            std::process::exit(1);
+        },
+    };
+    // Picture for the Game Boy Camera, it sees a test pattern otherwise
+    #[cfg(feature = "camera-png")]
+    if let Ok(filename) = env::var("CAMERA_IMAGE") {
+        match camera::load_png(&filename) {
+            Ok(image) => emulator.set_camera_image(&image),
+            Err(err) => eprintln!("Could not load camera image: {}", err),
+        };
+    }
+    start_eventloop(emulator);
     Ok(())
 }
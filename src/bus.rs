@@ -1,7 +1,8 @@
-use std::env;
 use std::ops::RangeInclusive;
 use crate::utils::join_bytes;
-use crate::rom::{ROM, load_rom};
+use crate::rom::ROM;
+use crate::config::{EmulatorConfig, HardwareModel};
+use crate::savestate::{SaveState, SaveStateError, StateReader, StateWriter};
 use crate::ram::{RAM, DMGRAM, CGBRAM, WRAM_BANK_SELECT_ADDRESS};
 use crate::ppu::{
     PPU,
@@ -10,7 +11,9 @@ use crate::ppu::{
 };
 use crate::timer::Timer;
 use crate::joypad::{Joypad, JOYPAD_ADDRESS};
-use crate::sound::Sound;
+use crate::sound::{Sound, FRAME_SEQUENCER_PERIOD};
+use crate::serial::{Serial, SERIAL_CONTROL_ADDRESS};
+use crate::scheduler::{Scheduler, Event};
 use crate::interrupts::{
     Interrupts,
     INTERRUPT_ENABLE_ADDRESS,
@@ -53,29 +56,18 @@ pub struct Bus {
     pub joypad: Joypad,
     pub timer: Timer,
     pub sound: Sound,
+    pub serial: Serial,
     pub interrupts: Interrupts,
+    pub scheduler: Scheduler,
     pub cgb_mode: bool,
     pub double_speed_mode: bool,
     pub prepare_double_speed_mode: bool,
 }
 
 impl Bus {
-    pub fn new() -> Self {
-        let args: Vec<String> = std::env::args().collect();
-        #[cfg(not(test))]
-        if args.len() < 2 {
-            eprintln!("Please, specify a ROM file");
-            std::process::exit(1);
-        }
-        let rom = match load_rom(&args.get(1).unwrap_or(&"".to_string())) {
-            Ok(rom) => rom,
-            Err(err) => {
-                eprintln!("Could not read ROM: {}", err);
-                std::process::exit(1);
-            },
-        };
+    pub fn new(rom: Box<dyn ROM>, config: &EmulatorConfig) -> Self {
         let info = rom.info().clone();
-        let force_dmg_mode = !env::var("FORCE_DMG").is_err();
+        let force_dmg_mode = config.hardware_model() == HardwareModel::DMG;
         let cgb_mode = (info.cgb_features() || info.cgb_only()) && !force_dmg_mode;
         let mut bus = Self {
             data: [0x00; 0x10000],
@@ -87,8 +79,10 @@ impl Bus {
             ppu: PPU::new(cgb_mode),
             joypad: Joypad::new(),
             timer: Timer::new(),
-            sound: Sound::new(),
+            sound: Sound::new(config.audio()),
+            serial: Serial::new(),
             interrupts: Interrupts::new(),
+            scheduler: Scheduler::new(),
             cgb_mode,
             double_speed_mode: false,
             prepare_double_speed_mode: false,
@@ -117,6 +111,8 @@ impl Bus {
         bus.write(0xFF4B, 0x00);
         bus.write(0xFFFF, 0x00);
 
+        bus.scheduler.schedule(Event::FrameSequencer, FRAME_SEQUENCER_PERIOD);
+
         bus
     }
 
@@ -137,12 +133,22 @@ impl Bus {
         }
     }
 
-    pub fn read(&self, address: u16) -> u8 {
+    pub fn read(&mut self, address: u16) -> u8 {
         match Bus::map_address(address) {
-            MemoryMap::BankZero | MemoryMap::BankSwitchable | MemoryMap::ExternalRam => self.rom.read(address),
+            MemoryMap::BankZero | MemoryMap::BankSwitchable => self.rom.read(address),
+            MemoryMap::ExternalRam => {
+                self.rom.catch_up(self.scheduler.now());
+                self.rom.read(address)
+            },
             MemoryMap::WorkRam1 | MemoryMap::WorkRam2 | MemoryMap::EchoRam => self.ram.read(address),
-            MemoryMap::VideoRam => self.ppu.read_vram_external(address),
-            MemoryMap::SpriteAttributeTable => self.ppu.read_oam(address),
+            MemoryMap::VideoRam => {
+                self.sync_ppu();
+                self.ppu.read_vram_external(address)
+            },
+            MemoryMap::SpriteAttributeTable => {
+                self.sync_ppu();
+                self.ppu.read_oam(address)
+            },
             MemoryMap::IoRegisters => {
                 if self.cgb_mode && address == PREPARE_SPEED_SWITCH_ADDRESS {
                     let byte = self.data[address as usize];
@@ -154,11 +160,16 @@ impl Bus {
                 } else if address == INTERRUPT_FLAG_ADDRESS {
                     return self.interrupts.read(address);
                 } else if PPU::is_io_register(address) {
+                    self.sync_ppu();
                     return self.ppu.get_register(address);
                 } else if Sound::is_io_register(address) {
+                    self.sync_sound();
                     return self.sound.get_register(address);
                 } else if Timer::is_io_register(address) {
+                    self.sync_timer();
                     return self.timer.get_register(address);
+                } else if Serial::is_io_register(address) {
+                    return self.serial.get_register(address);
                 } else if address == JOYPAD_ADDRESS {
                     return self.joypad.read(self.data[address as usize]);
                 }
@@ -169,16 +180,26 @@ impl Bus {
         }
     }
 
-    pub fn read_16bit(&self, address: u16) -> u16 {
+    pub fn read_16bit(&mut self, address: u16) -> u16 {
         join_bytes(self.read(address.wrapping_add(1)), self.read(address))
     }
 
     pub fn write(&mut self, address: u16, data: u8) {
         match Bus::map_address(address) {
-            MemoryMap::BankZero | MemoryMap::BankSwitchable | MemoryMap::ExternalRam => self.rom.write(address, data),
+            MemoryMap::BankZero | MemoryMap::BankSwitchable => self.rom.write(address, data),
+            MemoryMap::ExternalRam => {
+                self.rom.catch_up(self.scheduler.now());
+                self.rom.write(address, data);
+            },
             MemoryMap::WorkRam1 | MemoryMap::WorkRam2 | MemoryMap::EchoRam => self.ram.write(address, data),
-            MemoryMap::VideoRam => self.ppu.write_vram_external(address, data),
-            MemoryMap::SpriteAttributeTable => self.ppu.write_oam(address, data),
+            MemoryMap::VideoRam => {
+                self.sync_ppu();
+                self.ppu.write_vram_external(address, data);
+            },
+            MemoryMap::SpriteAttributeTable => {
+                self.sync_ppu();
+                self.ppu.write_oam(address, data);
+            },
             MemoryMap::IoRegisters => {
                 if self.cgb_mode && address == PREPARE_SPEED_SWITCH_ADDRESS {
                     let current_byte = self.data[address as usize];
@@ -190,6 +211,7 @@ impl Bus {
                 } else if address == INTERRUPT_FLAG_ADDRESS {
                     self.interrupts.write(address, data);
                 } else if PPU::is_io_register(address) {
+                    self.sync_ppu();
                     self.ppu.set_register(address, data);
                     match address {
                         DMA_ADDRESS => {
@@ -202,10 +224,19 @@ impl Bus {
                         },
                         _ => {}
                     }
+                    self.schedule_ppu();
                 } else if Sound::is_io_register(address) {
+                    self.sync_sound();
                     self.sound.set_register(address, data);
                 } else if Timer::is_io_register(address) {
+                    self.sync_timer();
                     self.timer.set_register(address, data);
+                    self.schedule_timer();
+                } else if Serial::is_io_register(address) {
+                    self.serial.set_register(address, data);
+                    if address == SERIAL_CONTROL_ADDRESS {
+                        self.schedule_serial(self.scheduler.now());
+                    }
                 } else if address == JOYPAD_ADDRESS {
                     let byte = self.data[address as usize];
                     self.data[address as usize] = (data & 0b11110000) | (byte & 0b00001111);
@@ -233,7 +264,73 @@ impl Bus {
     }
 
     pub fn set_double_speed_mode(&mut self, val: bool) {
+        // The timer runs at a different rate on each speed
+        self.sync_timer();
         self.double_speed_mode = val;
+        self.schedule_timer();
+    }
+
+    fn sync_ppu(&mut self) {
+        self.ppu.catch_up(&mut self.interrupts, self.scheduler.now());
+    }
+
+    fn schedule_ppu(&mut self) {
+        match self.ppu.next_transition() {
+            Some(time) => self.scheduler.schedule(Event::PPUModeTransition, time),
+            None => self.scheduler.cancel(Event::PPUModeTransition),
+        };
+    }
+
+    fn sync_timer(&mut self) {
+        let double_speed = self.double_speed_mode();
+        self.timer.catch_up(&mut self.interrupts, self.scheduler.now(), double_speed);
+    }
+
+    fn schedule_timer(&mut self) {
+        match self.timer.next_overflow(self.double_speed_mode()) {
+            Some(time) => self.scheduler.schedule(Event::TimerOverflow, time),
+            None => self.scheduler.cancel(Event::TimerOverflow),
+        };
+    }
+
+    fn sync_sound(&mut self) {
+        self.sound.catch_up(self.scheduler.now());
+    }
+
+    // Queues the next bit shift counting from `time`
+    fn schedule_serial(&mut self, time: u64) {
+        match self.serial.is_transferring() {
+            true => {
+                let period = self.serial.bit_period(self.cgb_mode, self.double_speed_mode());
+                self.scheduler.schedule(Event::SerialBit, time + period);
+            },
+            false => self.scheduler.cancel(Event::SerialBit),
+        };
+    }
+
+    // Runs every event that is due at the current master clock time
+    pub fn handle_events(&mut self) {
+        while let Some((event, time)) = self.scheduler.pop_due() {
+            match event {
+                Event::TimerOverflow => {
+                    self.sync_timer();
+                    self.schedule_timer();
+                },
+                Event::PPUModeTransition => {
+                    self.sync_ppu();
+                    self.schedule_ppu();
+                },
+                Event::FrameSequencer => {
+                    self.sync_sound();
+                    self.sound.step_frame_sequencer();
+                    self.scheduler.schedule(Event::FrameSequencer, time + FRAME_SEQUENCER_PERIOD);
+                },
+                Event::SerialBit => {
+                    self.serial.shift_bit(&mut self.interrupts);
+                    self.schedule_serial(time);
+                },
+            };
+        }
     }
 
     fn dma_transfer(&mut self, data: u8) {
@@ -241,7 +338,8 @@ impl Bus {
         let mut count: u16 = 0;
         let oam_addr = SPRITE_ATTRIBUTE_TABLE.min().unwrap();
         while count < 160 {
-            self.ppu.write_oam(oam_addr + count, self.read(source + count));
+            let byte = self.read(source + count);
+            self.ppu.write_oam(oam_addr + count, byte);
             count += 1;
         }
     }
@@ -259,3 +357,39 @@ impl Bus {
         self.ppu.set_register(HDMA5_ADDRESS, 0xFF);
     }
 }
+
+impl SaveState for Bus {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bool(self.cgb_mode);
+        state.write_bool(self.double_speed_mode);
+        state.write_bool(self.prepare_double_speed_mode);
+        state.write_bytes(&self.data[NOT_USABLE.min().unwrap() as usize..]);
+        self.rom.save_state(state);
+        self.ram.save_state(state);
+        self.ppu.save_state(state);
+        self.joypad.save_state(state);
+        self.timer.save_state(state);
+        self.sound.save_state(state);
+        self.interrupts.save_state(state);
+        self.serial.save_state(state);
+        self.scheduler.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        if state.read_bool()? != self.cgb_mode {
+            return Err(SaveStateError::InvalidData("hardware model"));
+        }
+        self.double_speed_mode = state.read_bool()?;
+        self.prepare_double_speed_mode = state.read_bool()?;
+        state.read_bytes_into(&mut self.data[NOT_USABLE.min().unwrap() as usize..])?;
+        self.rom.load_state(state)?;
+        self.ram.load_state(state)?;
+        self.ppu.load_state(state)?;
+        self.joypad.load_state(state)?;
+        self.timer.load_state(state)?;
+        self.sound.load_state(state)?;
+        self.interrupts.load_state(state)?;
+        self.serial.load_state(state)?;
+        self.scheduler.load_state(state)
+    }
+}
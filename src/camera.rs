@@ -0,0 +1,325 @@
+use crate::savestate::{SaveState, SaveStateError, StateReader, StateWriter};
+
+// Part of the M64282FP sensor that ends up in the picture, as 8 bit brightness values
+pub const CAMERA_WIDTH: usize = 128;
+pub const CAMERA_HEIGHT: usize = 112;
+// The processed picture is stored as 2bpp tiles, 16 tiles wide and 14 tall
+pub const CAMERA_IMAGE_SIZE: usize = CAMERA_WIDTH * CAMERA_HEIGHT / 4;
+pub const CAMERA_REGISTERS: usize = 0x36;
+
+const CAPTURE_REGISTER: usize = 0x00;
+const GAIN_REGISTER: usize = 0x01;
+const EXPOSURE_HIGH_REGISTER: usize = 0x02;
+const EXPOSURE_LOW_REGISTER: usize = 0x03;
+const EDGE_REGISTER: usize = 0x04;
+const DITHER_MATRIX_START: usize = 0x06;
+
+const CAPTURE_BIT: u8 = 0b0000_0001;
+const EXCLUSIVE_EDGE_BIT: u8 = 0b1000_0000;
+const INVERT_BIT: u8 = 0b0000_1000;
+
+// The capture takes this many M-cycles, plus 16 for every step of exposure
+const CAPTURE_BASE_CYCLES: u64 = 32446;
+const CAPTURE_NON_EXCLUSIVE_CYCLES: u64 = 512;
+
+// Values taken from SameBoy, measured on real hardware
+const GAIN_VALUES: [f64; 32] = [
+    0.8809390, 0.9149149, 0.9457498, 0.9739758, 1.0000000, 1.0241412, 1.0466537, 1.0677433,
+    1.0875793, 1.1240310, 1.1568911, 1.1868043, 1.2142561, 1.2396208, 1.2743837, 1.3157323,
+    1.3525190, 1.3856512, 1.4157897, 1.4434309, 1.4689574, 1.4926697, 1.5148087, 1.5355703,
+    1.5551159, 1.5735801, 1.5910762, 1.6077008, 1.6235366, 1.6386550, 1.6531183, 1.6669808,
+];
+const EDGE_RATIOS: [f64; 8] = [0.5, 0.75, 1.0, 1.25, 2.0, 3.0, 4.0, 5.0];
+
+// Picture shown to the camera when no image was given: a gradient with a few shapes on it
+pub fn test_pattern() -> Vec<u8> {
+    let mut image = vec![0; CAMERA_WIDTH * CAMERA_HEIGHT];
+    for y in 0..CAMERA_HEIGHT {
+        for x in 0..CAMERA_WIDTH {
+            let gradient = (x + y) * 255 / (CAMERA_WIDTH + CAMERA_HEIGHT);
+            let (dx, dy) = (x as i32 - 64, y as i32 - 56);
+            let value = if dx * dx + dy * dy < 30 * 30 {
+                255 - gradient
+            } else if (x / 16 + y / 16) % 2 == 0 && y >= 96 {
+                0
+            } else {
+                gradient
+            };
+            image[(y * CAMERA_WIDTH) + x] = value as u8;
+        }
+    }
+    image
+}
+
+// Loads a picture for the camera, converted to grayscale and stretched to the sensor size
+#[cfg(feature = "camera-png")]
+pub fn load_png(filename: &str) -> Result<Vec<u8>, Box<dyn std::error::Error>> {
+    let mut decoder = png::Decoder::new(std::fs::File::open(filename)?);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info()?;
+    let mut data = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut data)?;
+    let channels = info.color_type.samples();
+    let (width, height) = (info.width as usize, info.height as usize);
+
+    let mut image = vec![0; CAMERA_WIDTH * CAMERA_HEIGHT];
+    for y in 0..CAMERA_HEIGHT {
+        for x in 0..CAMERA_WIDTH {
+            let source_x = x * width / CAMERA_WIDTH;
+            let source_y = y * height / CAMERA_HEIGHT;
+            let pixel = &data[(source_y * info.line_size) + (source_x * channels)..];
+            image[(y * CAMERA_WIDTH) + x] = match channels {
+                1 | 2 => pixel[0],
+                _ => ((pixel[0] as u32 * 299 + pixel[1] as u32 * 587 + pixel[2] as u32 * 114) / 1000) as u8,
+            };
+        }
+    }
+    Ok(image)
+}
+
+// Mitsubishi M64282FP artificial retina used by the Game Boy Camera. Instead of light,
+// it sees the image given by the user and processes it like the real sensor would
+pub struct M64282FP {
+    registers: [u8; CAMERA_REGISTERS],
+    image: Vec<u8>,
+    // Clock at which the capture in progress finishes
+    capture_end: Option<u64>,
+}
+
+impl M64282FP {
+    pub fn new() -> Self {
+        Self {
+            registers: [0; CAMERA_REGISTERS],
+            image: test_pattern(),
+            capture_end: None,
+        }
+    }
+
+    // Expects CAMERA_WIDTH * CAMERA_HEIGHT brightness values, anything else is ignored
+    pub fn set_image(&mut self, image: &[u8]) {
+        if image.len() == CAMERA_WIDTH * CAMERA_HEIGHT {
+            self.image = image.to_vec();
+        }
+    }
+
+    pub fn is_capturing(&self) -> bool {
+        self.capture_end.is_some()
+    }
+
+    // Only the capture register can be read back, the rest are write only
+    pub fn read_register(&self, register: usize) -> u8 {
+        match register {
+            CAPTURE_REGISTER => (self.registers[CAPTURE_REGISTER] & !CAPTURE_BIT) | self.is_capturing() as u8,
+            _ => 0x00,
+        }
+    }
+
+    pub fn write_register(&mut self, register: usize, data: u8, now: u64) {
+        match register {
+            CAPTURE_REGISTER => {
+                self.registers[CAPTURE_REGISTER] = data & 0b111;
+                if data & CAPTURE_BIT != 0 && !self.is_capturing() {
+                    self.capture_end = Some(now + self.capture_cycles());
+                }
+            },
+            register if register < CAMERA_REGISTERS => self.registers[register] = data,
+            _ => {},
+        };
+    }
+
+    fn exposure(&self) -> u16 {
+        ((self.registers[EXPOSURE_HIGH_REGISTER] as u16) << 8) | self.registers[EXPOSURE_LOW_REGISTER] as u16
+    }
+
+    // Length of a capture in T-cycles
+    pub fn capture_cycles(&self) -> u64 {
+        let extra = match self.registers[GAIN_REGISTER] & EXCLUSIVE_EDGE_BIT {
+            0 => CAPTURE_NON_EXCLUSIVE_CYCLES,
+            _ => 0,
+        };
+        (CAPTURE_BASE_CYCLES + extra + (16 * self.exposure() as u64)) * 4
+    }
+
+    // Returns the picture once the capture in progress is done
+    pub fn update(&mut self, now: u64) -> Option<Vec<u8>> {
+        match self.capture_end {
+            Some(end) if now >= end => {
+                self.capture_end = None;
+                self.registers[CAPTURE_REGISTER] &= !CAPTURE_BIT;
+                Some(self.capture())
+            },
+            _ => None,
+        }
+    }
+
+    // Brightness after the analog gain and exposure, out of bounds pixels repeat the edges
+    fn sensor_value(&self, x: i32, y: i32) -> f64 {
+        let x = x.clamp(0, CAMERA_WIDTH as i32 - 1) as usize;
+        let y = y.clamp(0, CAMERA_HEIGHT as i32 - 1) as usize;
+        let value = self.image[(y * CAMERA_WIDTH) + x] as f64;
+        let value = match self.registers[EDGE_REGISTER] & INVERT_BIT {
+            0 => value,
+            _ => 255.0 - value,
+        };
+        value * GAIN_VALUES[(self.registers[GAIN_REGISTER] & 0x1F) as usize] * self.exposure() as f64 / 0x1000 as f64
+    }
+
+    fn processed_value(&self, x: i32, y: i32) -> f64 {
+        let value = self.sensor_value(x, y);
+        let ratio = EDGE_RATIOS[((self.registers[EDGE_REGISTER] >> 4) & 0b111) as usize];
+        // VH selects which neighbours are used to enhance the edges
+        let neighbours: &[(i32, i32)] = match (self.registers[GAIN_REGISTER] >> 5) & 0b11 {
+            0b01 => &[(-1, 0), (1, 0)],
+            0b10 => &[(0, -1), (0, 1)],
+            0b11 => &[(-1, 0), (1, 0), (0, -1), (0, 1)],
+            _ => &[],
+        };
+        neighbours.iter().fold(value, |result, (dx, dy)| {
+            result + ((value - self.sensor_value(x + dx, y + dy)) * ratio)
+        })
+    }
+
+    // Every pixel is compared against the 3 thresholds of its position in the 4x4 dithering matrix
+    fn shade(&self, x: usize, y: usize) -> u8 {
+        let value = self.processed_value(x as i32, y as i32);
+        let thresholds = DITHER_MATRIX_START + (((x & 3) + ((y & 3) * 4)) * 3);
+        match value {
+            value if value < self.registers[thresholds] as f64 => 3,
+            value if value < self.registers[thresholds + 1] as f64 => 2,
+            value if value < self.registers[thresholds + 2] as f64 => 1,
+            _ => 0,
+        }
+    }
+
+    pub fn capture(&self) -> Vec<u8> {
+        let mut tiles = vec![0; CAMERA_IMAGE_SIZE];
+        for y in 0..CAMERA_HEIGHT {
+            for x in 0..CAMERA_WIDTH {
+                let shade = self.shade(x, y);
+                let tile = ((y / 8) * (CAMERA_WIDTH / 8)) + (x / 8);
+                let address = (tile * 16) + ((y % 8) * 2);
+                let bit = 7 - (x % 8);
+                tiles[address] |= (shade & 1) << bit;
+                tiles[address + 1] |= ((shade >> 1) & 1) << bit;
+            }
+        }
+        tiles
+    }
+}
+
+impl Default for M64282FP {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SaveState for M64282FP {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.registers);
+        state.write_bool(self.capture_end.is_some());
+        state.write_u64(self.capture_end.unwrap_or(0));
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        state.read_bytes_into(&mut self.registers)?;
+        let capturing = state.read_bool()?;
+        let capture_end = state.read_u64()?;
+        self.capture_end = match capturing {
+            true => Some(capture_end),
+            false => None,
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Dithering matrix with the same thresholds at every position
+    fn flat_thresholds(sensor: &mut M64282FP, thresholds: [u8; 3]) {
+        for position in 0..16 {
+            for (index, threshold) in thresholds.iter().enumerate() {
+                sensor.write_register(DITHER_MATRIX_START + (position * 3) + index, *threshold, 0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_capture_timing() {
+        let mut sensor = M64282FP::new();
+        sensor.write_register(EXPOSURE_HIGH_REGISTER, 0x10, 0);
+        sensor.write_register(CAPTURE_REGISTER, CAPTURE_BIT, 100);
+        let cycles = (CAPTURE_BASE_CYCLES + CAPTURE_NON_EXCLUSIVE_CYCLES + (16 * 0x1000)) * 4;
+        assert_eq!(sensor.read_register(CAPTURE_REGISTER), CAPTURE_BIT);
+        assert!(sensor.update(100 + cycles - 1).is_none());
+        assert!(sensor.update(100 + cycles).is_some());
+        assert_eq!(sensor.read_register(CAPTURE_REGISTER), 0);
+    }
+
+    #[test]
+    fn test_capture_shades() {
+        let mut sensor = M64282FP::new();
+        let mut image = vec![0; CAMERA_WIDTH * CAMERA_HEIGHT];
+        // Left half black, right half white
+        for y in 0..CAMERA_HEIGHT {
+            for x in CAMERA_WIDTH / 2..CAMERA_WIDTH {
+                image[(y * CAMERA_WIDTH) + x] = 0xFF;
+            }
+        }
+        sensor.set_image(&image);
+        sensor.write_register(GAIN_REGISTER, 0x04, 0);
+        sensor.write_register(EXPOSURE_HIGH_REGISTER, 0x10, 0);
+        flat_thresholds(&mut sensor, [0x40, 0x80, 0xC0]);
+        let tiles = sensor.capture();
+        // The first tile is black and the last one of the row white
+        assert_eq!(&tiles[0..2], &[0xFF, 0xFF]);
+        assert_eq!(&tiles[15 * 16..(15 * 16) + 2], &[0x00, 0x00]);
+
+        sensor.write_register(EDGE_REGISTER, INVERT_BIT, 0);
+        let tiles = sensor.capture();
+        assert_eq!(&tiles[0..2], &[0x00, 0x00]);
+    }
+
+    #[cfg(feature = "camera-png")]
+    fn write_png(filename: &std::path::Path, width: u32, height: u32, color_type: png::ColorType, data: &[u8]) {
+        let mut encoder = png::Encoder::new(std::fs::File::create(filename).unwrap(), width, height);
+        encoder.set_color(color_type);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.write_header().unwrap().write_image_data(data).unwrap();
+    }
+
+    #[test]
+    #[cfg(feature = "camera-png")]
+    fn test_load_png() {
+        let dir = std::env::temp_dir().join(format!("rmg-001-camera-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let rgb_path = dir.join("rgb.png");
+        let gray_path = dir.join("gray.png");
+        // Red and blue over green and white
+        write_png(&rgb_path, 2, 2, png::ColorType::Rgb, &[
+            255, 0, 0, 0, 0, 255,
+            0, 255, 0, 255, 255, 255,
+        ]);
+        write_png(&gray_path, 4, 1, png::ColorType::Grayscale, &[0, 85, 170, 255]);
+        let rgb = load_png(rgb_path.to_str().unwrap());
+        let gray = load_png(gray_path.to_str().unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let rgb = rgb.unwrap();
+        assert_eq!(rgb.len(), CAMERA_WIDTH * CAMERA_HEIGHT);
+        let pixel = |image: &[u8], x: usize, y: usize| image[(y * CAMERA_WIDTH) + x];
+        assert_eq!(pixel(&rgb, 0, 0), 76);
+        assert_eq!(pixel(&rgb, 63, 55), 76);
+        assert_eq!(pixel(&rgb, 64, 0), 29);
+        assert_eq!(pixel(&rgb, 0, 56), 149);
+        assert_eq!(pixel(&rgb, 127, 111), 255);
+
+        let gray = gray.unwrap();
+        assert_eq!(gray.len(), CAMERA_WIDTH * CAMERA_HEIGHT);
+        for (x, value) in [(0, 0), (31, 0), (32, 85), (64, 170), (127, 255)] {
+            assert_eq!(pixel(&gray, x, 0), value);
+            assert_eq!(pixel(&gray, x, 111), value);
+        }
+    }
+}
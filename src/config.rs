@@ -0,0 +1,107 @@
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum HardwareModel {
+    // Original Gameboy, CGB features are ignored even if the cartridge supports them
+    DMG,
+    // Gameboy Color, CGB mode is used when the cartridge supports it
+    CGB,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum FramePacing {
+    // Wait for vsync and limit the emulation to ~60 frames per second
+    Limited,
+    // Run frames as fast as possible
+    Unlocked,
+}
+
+#[derive(Debug, Clone)]
+pub struct EmulatorConfig {
+    hardware_model: HardwareModel,
+    audio: bool,
+    cpu_trace: bool,
+    frame_pacing: FramePacing,
+    // Frames between rewind snapshots, 0 disables rewinding
+    rewind_interval: usize,
+    rewind_capacity: usize,
+    // IPS, UPS or BPS file applied to the ROM, otherwise one next to it is looked for
+    patch: Option<String>,
+}
+
+impl EmulatorConfig {
+    pub fn new() -> Self {
+        Self {
+            hardware_model: HardwareModel::CGB,
+            audio: false,
+            cpu_trace: false,
+            frame_pacing: FramePacing::Limited,
+            rewind_interval: 0,
+            rewind_capacity: 0,
+            patch: None,
+        }
+    }
+
+    pub fn with_hardware_model(mut self, hardware_model: HardwareModel) -> Self {
+        self.hardware_model = hardware_model;
+        self
+    }
+
+    pub fn with_audio(mut self, audio: bool) -> Self {
+        self.audio = audio;
+        self
+    }
+
+    pub fn with_cpu_trace(mut self, cpu_trace: bool) -> Self {
+        self.cpu_trace = cpu_trace;
+        self
+    }
+
+    pub fn with_frame_pacing(mut self, frame_pacing: FramePacing) -> Self {
+        self.frame_pacing = frame_pacing;
+        self
+    }
+
+    pub fn with_rewind(mut self, interval: usize, capacity: usize) -> Self {
+        self.rewind_interval = interval;
+        self.rewind_capacity = capacity;
+        self
+    }
+
+    pub fn with_patch(mut self, patch: &str) -> Self {
+        self.patch = Some(patch.to_string());
+        self
+    }
+
+    pub fn hardware_model(&self) -> HardwareModel {
+        self.hardware_model
+    }
+
+    pub fn audio(&self) -> bool {
+        self.audio
+    }
+
+    pub fn cpu_trace(&self) -> bool {
+        self.cpu_trace
+    }
+
+    pub fn frame_pacing(&self) -> FramePacing {
+        self.frame_pacing
+    }
+
+    pub fn rewind_interval(&self) -> usize {
+        self.rewind_interval
+    }
+
+    pub fn rewind_capacity(&self) -> usize {
+        self.rewind_capacity
+    }
+
+    pub fn patch(&self) -> Option<&str> {
+        self.patch.as_deref()
+    }
+}
+
+impl Default for EmulatorConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
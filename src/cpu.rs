@@ -1,4 +1,5 @@
-use std::env;
+use std::sync::OnceLock;
+
 use crate::utils::{
     BitIndex,
     get_bit,
@@ -17,6 +18,7 @@ use crate::interrupts::{
     INTERRUPT_ENABLE_ADDRESS,
     INTERRUPT_FLAG_ADDRESS,
 };
+use crate::savestate::{SaveState, SaveStateError, StateReader, StateWriter};
 
 #[derive(Debug, Copy, Clone)]
 pub enum Register {
@@ -177,7 +179,36 @@ impl Registers {
     }
 }
 
-#[derive(Debug)]
+impl SaveState for Registers {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.a);
+        state.write_u8(self.f);
+        state.write_u8(self.b);
+        state.write_u8(self.c);
+        state.write_u8(self.d);
+        state.write_u8(self.e);
+        state.write_u8(self.h);
+        state.write_u8(self.l);
+        state.write_u16(self.sp);
+        state.write_u16(self.pc);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.a = state.read_u8()?;
+        self.f = state.read_u8()?;
+        self.b = state.read_u8()?;
+        self.c = state.read_u8()?;
+        self.d = state.read_u8()?;
+        self.e = state.read_u8()?;
+        self.h = state.read_u8()?;
+        self.l = state.read_u8()?;
+        self.sp = state.read_u16()?;
+        self.pc = state.read_u16()?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
 #[allow(non_camel_case_types)]
 pub enum OpcodeParameter {
     Register(Register),
@@ -223,12 +254,77 @@ pub enum OpcodeParameter {
     NoParam,
 }
 
+impl OpcodeParameter {
+    // Amount of bytes that follow the opcode byte
+    pub fn immediate_length(&self) -> u8 {
+        match self {
+            OpcodeParameter::Register_U8(..) |
+            OpcodeParameter::Register_I8(..) |
+            OpcodeParameter::U8_Register(..) |
+            OpcodeParameter::I8_Register(..) |
+            OpcodeParameter::Register_FF00plusU8(..) |
+            OpcodeParameter::FF00plusU8_Register(..) |
+            OpcodeParameter::Register_RegisterPlusI8(..) |
+            OpcodeParameter::U8(..) |
+            OpcodeParameter::I8(..) |
+            OpcodeParameter::FlagRegisterReset_U8(..) |
+            OpcodeParameter::FlagRegisterSet_U8(..) |
+            OpcodeParameter::FlagRegisterReset_I8(..) |
+            OpcodeParameter::FlagRegisterSet_I8(..) => 1,
+            OpcodeParameter::Register_U16(..) |
+            OpcodeParameter::Register_I16(..) |
+            OpcodeParameter::U16_Register(..) |
+            OpcodeParameter::I16_Register(..) |
+            OpcodeParameter::Register_16BitAddress(..) |
+            OpcodeParameter::U16(..) |
+            OpcodeParameter::I16(..) |
+            OpcodeParameter::FlagRegisterReset_U16(..) |
+            OpcodeParameter::FlagRegisterSet_U16(..) |
+            OpcodeParameter::FlagRegisterReset_I16(..) |
+            OpcodeParameter::FlagRegisterSet_I16(..) => 2,
+            _ => 0,
+        }
+    }
+
+    // Replaces the immediate value, 8 bit parameters take the lower byte
+    pub fn with_immediate(self, value: u16) -> Self {
+        let byte = value as u8;
+        match self {
+            OpcodeParameter::Register_U8(register, _) => OpcodeParameter::Register_U8(register, byte),
+            OpcodeParameter::Register_I8(register, _) => OpcodeParameter::Register_I8(register, byte as i8),
+            OpcodeParameter::U8_Register(_, register) => OpcodeParameter::U8_Register(byte, register),
+            OpcodeParameter::I8_Register(_, register) => OpcodeParameter::I8_Register(byte, register),
+            OpcodeParameter::Register_FF00plusU8(register, _) => OpcodeParameter::Register_FF00plusU8(register, byte),
+            OpcodeParameter::FF00plusU8_Register(_, register) => OpcodeParameter::FF00plusU8_Register(byte, register),
+            OpcodeParameter::Register_RegisterPlusI8(reg1, reg2, _) => OpcodeParameter::Register_RegisterPlusI8(reg1, reg2, byte as i8),
+            OpcodeParameter::U8(_) => OpcodeParameter::U8(byte),
+            OpcodeParameter::I8(_) => OpcodeParameter::I8(byte as i8),
+            OpcodeParameter::FlagRegisterReset_U8(flag, _) => OpcodeParameter::FlagRegisterReset_U8(flag, byte),
+            OpcodeParameter::FlagRegisterSet_U8(flag, _) => OpcodeParameter::FlagRegisterSet_U8(flag, byte),
+            OpcodeParameter::FlagRegisterReset_I8(flag, _) => OpcodeParameter::FlagRegisterReset_I8(flag, byte as i8),
+            OpcodeParameter::FlagRegisterSet_I8(flag, _) => OpcodeParameter::FlagRegisterSet_I8(flag, byte as i8),
+            OpcodeParameter::Register_U16(register, _) => OpcodeParameter::Register_U16(register, value),
+            OpcodeParameter::Register_I16(register, _) => OpcodeParameter::Register_I16(register, value),
+            OpcodeParameter::U16_Register(_, register) => OpcodeParameter::U16_Register(value, register),
+            OpcodeParameter::I16_Register(_, register) => OpcodeParameter::I16_Register(value, register),
+            OpcodeParameter::Register_16BitAddress(register, _) => OpcodeParameter::Register_16BitAddress(register, value),
+            OpcodeParameter::U16(_) => OpcodeParameter::U16(value),
+            OpcodeParameter::I16(_) => OpcodeParameter::I16(value as i16),
+            OpcodeParameter::FlagRegisterReset_U16(flag, _) => OpcodeParameter::FlagRegisterReset_U16(flag, value),
+            OpcodeParameter::FlagRegisterSet_U16(flag, _) => OpcodeParameter::FlagRegisterSet_U16(flag, value),
+            OpcodeParameter::FlagRegisterReset_I16(flag, _) => OpcodeParameter::FlagRegisterReset_I16(flag, value as i16),
+            OpcodeParameter::FlagRegisterSet_I16(flag, _) => OpcodeParameter::FlagRegisterSet_I16(flag, value as i16),
+            params => params,
+        }
+    }
+}
+
 #[derive(Debug, Copy, Clone)]
 pub struct OpcodeParameterBytes(u8, u8, u8, u8);
 
 impl OpcodeParameterBytes {
 
-    pub fn from_address(address: u16, bus: &Bus)-> OpcodeParameterBytes {
+    pub fn from_address(address: u16, bus: &mut Bus)-> OpcodeParameterBytes {
         OpcodeParameterBytes(
             bus.read(address),
             bus.read(address.wrapping_add(1)),
@@ -237,528 +333,585 @@ impl OpcodeParameterBytes {
         )
     }
 
-    pub fn parse_opcode(&self) -> (Opcode, Cycles) {
+    pub fn parse_opcode(&self) -> (Opcode, MCycles) {
         let opcode = self.0;
         let two_byte_param = join_bytes(self.2, self.1);
         match opcode {
-            0x06 => (Opcode::LD(OpcodeParameter::Register_U8(Register::B, self.1)), Cycles(2.0)),
-            0x0E => (Opcode::LD(OpcodeParameter::Register_U8(Register::C, self.1)), Cycles(2.0)),
-            0x16 => (Opcode::LD(OpcodeParameter::Register_U8(Register::D, self.1)), Cycles(2.0)),
-            0x1E => (Opcode::LD(OpcodeParameter::Register_U8(Register::E, self.1)), Cycles(2.0)),
-            0x26 => (Opcode::LD(OpcodeParameter::Register_U8(Register::H, self.1)), Cycles(2.0)),
-            0x2E => (Opcode::LD(OpcodeParameter::Register_U8(Register::L, self.1)), Cycles(2.0)),
-            0x7F => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::A)), Cycles(1.0)),
-            0x78 => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::B)), Cycles(1.0)),
-            0x79 => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::C)), Cycles(1.0)),
-            0x7A => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::D)), Cycles(1.0)),
-            0x7B => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::E)), Cycles(1.0)),
-            0x7C => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::H)), Cycles(1.0)),
-            0x7D => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::L)), Cycles(1.0)),
-            0x7E => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::HL)), Cycles(2.0)),
-            0x40 => (Opcode::LD(OpcodeParameter::Register_Register(Register::B, Register::B)), Cycles(1.0)),
-            0x41 => (Opcode::LD(OpcodeParameter::Register_Register(Register::B, Register::C)), Cycles(1.0)),
-            0x42 => (Opcode::LD(OpcodeParameter::Register_Register(Register::B, Register::D)), Cycles(1.0)),
-            0x43 => (Opcode::LD(OpcodeParameter::Register_Register(Register::B, Register::E)), Cycles(1.0)),
-            0x44 => (Opcode::LD(OpcodeParameter::Register_Register(Register::B, Register::H)), Cycles(1.0)),
-            0x45 => (Opcode::LD(OpcodeParameter::Register_Register(Register::B, Register::L)), Cycles(1.0)),
-            0x46 => (Opcode::LD(OpcodeParameter::Register_Register(Register::B, Register::HL)), Cycles(2.0)),
-            0x48 => (Opcode::LD(OpcodeParameter::Register_Register(Register::C, Register::B)), Cycles(1.0)),
-            0x49 => (Opcode::LD(OpcodeParameter::Register_Register(Register::C, Register::C)), Cycles(1.0)),
-            0x4A => (Opcode::LD(OpcodeParameter::Register_Register(Register::C, Register::D)), Cycles(1.0)),
-            0x4B => (Opcode::LD(OpcodeParameter::Register_Register(Register::C, Register::E)), Cycles(1.0)),
-            0x4C => (Opcode::LD(OpcodeParameter::Register_Register(Register::C, Register::H)), Cycles(1.0)),
-            0x4D => (Opcode::LD(OpcodeParameter::Register_Register(Register::C, Register::L)), Cycles(1.0)),
-            0x4E => (Opcode::LD(OpcodeParameter::Register_Register(Register::C, Register::HL)), Cycles(2.0)),
-            0x50 => (Opcode::LD(OpcodeParameter::Register_Register(Register::D, Register::B)), Cycles(1.0)),
-            0x51 => (Opcode::LD(OpcodeParameter::Register_Register(Register::D, Register::C)), Cycles(1.0)),
-            0x52 => (Opcode::LD(OpcodeParameter::Register_Register(Register::D, Register::D)), Cycles(1.0)),
-            0x53 => (Opcode::LD(OpcodeParameter::Register_Register(Register::D, Register::E)), Cycles(1.0)),
-            0x54 => (Opcode::LD(OpcodeParameter::Register_Register(Register::D, Register::H)), Cycles(1.0)),
-            0x55 => (Opcode::LD(OpcodeParameter::Register_Register(Register::D, Register::L)), Cycles(1.0)),
-            0x56 => (Opcode::LD(OpcodeParameter::Register_Register(Register::D, Register::HL)), Cycles(2.0)),
-            0x58 => (Opcode::LD(OpcodeParameter::Register_Register(Register::E, Register::B)), Cycles(1.0)),
-            0x59 => (Opcode::LD(OpcodeParameter::Register_Register(Register::E, Register::C)), Cycles(1.0)),
-            0x5A => (Opcode::LD(OpcodeParameter::Register_Register(Register::E, Register::D)), Cycles(1.0)),
-            0x5B => (Opcode::LD(OpcodeParameter::Register_Register(Register::E, Register::E)), Cycles(1.0)),
-            0x5C => (Opcode::LD(OpcodeParameter::Register_Register(Register::E, Register::H)), Cycles(1.0)),
-            0x5D => (Opcode::LD(OpcodeParameter::Register_Register(Register::E, Register::L)), Cycles(1.0)),
-            0x5E => (Opcode::LD(OpcodeParameter::Register_Register(Register::E, Register::HL)), Cycles(2.0)),
-            0x60 => (Opcode::LD(OpcodeParameter::Register_Register(Register::H, Register::B)), Cycles(1.0)),
-            0x61 => (Opcode::LD(OpcodeParameter::Register_Register(Register::H, Register::C)), Cycles(1.0)),
-            0x62 => (Opcode::LD(OpcodeParameter::Register_Register(Register::H, Register::D)), Cycles(1.0)),
-            0x63 => (Opcode::LD(OpcodeParameter::Register_Register(Register::H, Register::E)), Cycles(1.0)),
-            0x64 => (Opcode::LD(OpcodeParameter::Register_Register(Register::H, Register::H)), Cycles(1.0)),
-            0x65 => (Opcode::LD(OpcodeParameter::Register_Register(Register::H, Register::L)), Cycles(1.0)),
-            0x66 => (Opcode::LD(OpcodeParameter::Register_Register(Register::H, Register::HL)), Cycles(2.0)),
-            0x68 => (Opcode::LD(OpcodeParameter::Register_Register(Register::L, Register::B)), Cycles(1.0)),
-            0x69 => (Opcode::LD(OpcodeParameter::Register_Register(Register::L, Register::C)), Cycles(1.0)),
-            0x6A => (Opcode::LD(OpcodeParameter::Register_Register(Register::L, Register::D)), Cycles(1.0)),
-            0x6B => (Opcode::LD(OpcodeParameter::Register_Register(Register::L, Register::E)), Cycles(1.0)),
-            0x6C => (Opcode::LD(OpcodeParameter::Register_Register(Register::L, Register::H)), Cycles(1.0)),
-            0x6D => (Opcode::LD(OpcodeParameter::Register_Register(Register::L, Register::L)), Cycles(1.0)),
-            0x6E => (Opcode::LD(OpcodeParameter::Register_Register(Register::L, Register::HL)), Cycles(2.0)),
-            0x70 => (Opcode::LD(OpcodeParameter::Register_Register(Register::HL, Register::B)), Cycles(2.0)),
-            0x71 => (Opcode::LD(OpcodeParameter::Register_Register(Register::HL, Register::C)), Cycles(2.0)),
-            0x72 => (Opcode::LD(OpcodeParameter::Register_Register(Register::HL, Register::D)), Cycles(2.0)),
-            0x73 => (Opcode::LD(OpcodeParameter::Register_Register(Register::HL, Register::E)), Cycles(2.0)),
-            0x74 => (Opcode::LD(OpcodeParameter::Register_Register(Register::HL, Register::H)), Cycles(2.0)),
-            0x75 => (Opcode::LD(OpcodeParameter::Register_Register(Register::HL, Register::L)), Cycles(2.0)),
-            0x47 => (Opcode::LD(OpcodeParameter::Register_Register(Register::B, Register::A)), Cycles(1.0)),
-            0x4F => (Opcode::LD(OpcodeParameter::Register_Register(Register::C, Register::A)), Cycles(1.0)),
-            0x57 => (Opcode::LD(OpcodeParameter::Register_Register(Register::D, Register::A)), Cycles(1.0)),
-            0x5F => (Opcode::LD(OpcodeParameter::Register_Register(Register::E, Register::A)), Cycles(1.0)),
-            0x67 => (Opcode::LD(OpcodeParameter::Register_Register(Register::H, Register::A)), Cycles(1.0)),
-            0x6F => (Opcode::LD(OpcodeParameter::Register_Register(Register::L, Register::A)), Cycles(1.0)),
-            0x02 => (Opcode::LD(OpcodeParameter::Register_Register(Register::BC, Register::A)), Cycles(2.0)),
-            0x12 => (Opcode::LD(OpcodeParameter::Register_Register(Register::DE, Register::A)), Cycles(2.0)),
-            0x77 => (Opcode::LD(OpcodeParameter::Register_Register(Register::HL, Register::A)), Cycles(2.0)),
-            0x36 => (Opcode::LD(OpcodeParameter::Register_U8(Register::HL, self.1)), Cycles(3.0)),
-            0x0A => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::BC)), Cycles(2.0)),
-            0x1A => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::DE)), Cycles(2.0)),
-            0xFA => (Opcode::LD(OpcodeParameter::Register_U16(Register::A, two_byte_param)), Cycles(4.0)),
-            0x3E => (Opcode::LD(OpcodeParameter::Register_U8(Register::A, self.1)), Cycles(2.0)),
-            0xEA => (Opcode::LD(OpcodeParameter::U16_Register(two_byte_param, Register::A)), Cycles(4.0)),
-            0xF2 => (Opcode::LD(OpcodeParameter::Register_FF00plusRegister(Register::A, Register::C)), Cycles(2.0)),
-            0xE2 => (Opcode::LD(OpcodeParameter::FF00plusRegister_Register(Register::C, Register::A)), Cycles(2.0)),
-            0x3A => (Opcode::LDD(OpcodeParameter::Register_RegisterDecrement(Register::A, Register::HL)), Cycles(2.0)),
-            0x32 => (Opcode::LDD(OpcodeParameter::RegisterDecrement_Register(Register::HL, Register::A)), Cycles(2.0)),
-            0x2A => (Opcode::LDI(OpcodeParameter::Register_RegisterIncrement(Register::A, Register::HL)), Cycles(2.0)),
-            0x22 => (Opcode::LDI(OpcodeParameter::RegisterIncrement_Register(Register::HL, Register::A)), Cycles(2.0)),
-            0xE0 => (Opcode::LD(OpcodeParameter::FF00plusU8_Register(self.1, Register::A)), Cycles(3.0)),
-            0xF0 => (Opcode::LD(OpcodeParameter::Register_FF00plusU8(Register::A, self.1)), Cycles(3.0)),
-            0x01 => (Opcode::LD(OpcodeParameter::Register_U16(Register::BC, two_byte_param)), Cycles(3.0)),
-            0x11 => (Opcode::LD(OpcodeParameter::Register_U16(Register::DE, two_byte_param)), Cycles(3.0)),
-            0x21 => (Opcode::LD(OpcodeParameter::Register_U16(Register::HL, two_byte_param)), Cycles(3.0)),
-            0x31 => (Opcode::LD(OpcodeParameter::Register_U16(Register::SP, two_byte_param)), Cycles(3.0)),
-            0xF9 => (Opcode::LD(OpcodeParameter::Register_Register(Register::SP, Register::HL)), Cycles(2.0)),
-            0xF8 => (Opcode::LD(OpcodeParameter::Register_RegisterPlusI8(Register::HL, Register::SP, self.1 as i8)), Cycles(3.0)),
-            0x08 => (Opcode::LD(OpcodeParameter::U16_Register(two_byte_param, Register::SP)), Cycles(5.0)),
-            0xC5 => (Opcode::PUSH(Register::BC), Cycles(4.0)),
-            0xD5 => (Opcode::PUSH(Register::DE), Cycles(4.0)),
-            0xE5 => (Opcode::PUSH(Register::HL), Cycles(4.0)),
-            0xF5 => (Opcode::PUSH(Register::AF), Cycles(4.0)),
-            0xC1 => (Opcode::POP(Register::BC), Cycles(3.0)),
-            0xD1 => (Opcode::POP(Register::DE), Cycles(3.0)),
-            0xE1 => (Opcode::POP(Register::HL), Cycles(3.0)),
-            0xF1 => (Opcode::POP(Register::AF), Cycles(3.0)),
-            0x87 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::A, Register::A)), Cycles(1.0)),
-            0x80 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::A, Register::B)), Cycles(1.0)),
-            0x81 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::A, Register::C)), Cycles(1.0)),
-            0x82 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::A, Register::D)), Cycles(1.0)),
-            0x83 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::A, Register::E)), Cycles(1.0)),
-            0x84 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::A, Register::H)), Cycles(1.0)),
-            0x85 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::A, Register::L)), Cycles(1.0)),
-            0x86 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::A, Register::HL)), Cycles(2.0)),
-            0xC6 => (Opcode::ADD(OpcodeParameter::Register_U8(Register::A, self.1)), Cycles(2.0)),
-            0x09 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::HL, Register::BC)), Cycles(2.0)),
-            0x19 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::HL, Register::DE)), Cycles(2.0)),
-            0x29 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::HL, Register::HL)), Cycles(2.0)),
-            0x39 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::HL, Register::SP)), Cycles(2.0)),
-            0xE8 => (Opcode::ADD(OpcodeParameter::Register_I8(Register::SP, self.1 as i8)), Cycles(4.0)),
-            0x8F => (Opcode::ADC(OpcodeParameter::Register_Register(Register::A, Register::A)), Cycles(1.0)),
-            0x88 => (Opcode::ADC(OpcodeParameter::Register_Register(Register::A, Register::B)), Cycles(1.0)),
-            0x89 => (Opcode::ADC(OpcodeParameter::Register_Register(Register::A, Register::C)), Cycles(1.0)),
-            0x8A => (Opcode::ADC(OpcodeParameter::Register_Register(Register::A, Register::D)), Cycles(1.0)),
-            0x8B => (Opcode::ADC(OpcodeParameter::Register_Register(Register::A, Register::E)), Cycles(1.0)),
-            0x8C => (Opcode::ADC(OpcodeParameter::Register_Register(Register::A, Register::H)), Cycles(1.0)),
-            0x8D => (Opcode::ADC(OpcodeParameter::Register_Register(Register::A, Register::L)), Cycles(1.0)),
-            0x8E => (Opcode::ADC(OpcodeParameter::Register_Register(Register::A, Register::HL)), Cycles(2.0)),
-            0xCE => (Opcode::ADC(OpcodeParameter::Register_U8(Register::A, self.1)), Cycles(2.0)),
-            0x97 => (Opcode::SUB(OpcodeParameter::Register_Register(Register::A, Register::A)), Cycles(1.0)),
-            0x90 => (Opcode::SUB(OpcodeParameter::Register_Register(Register::A, Register::B)), Cycles(1.0)),
-            0x91 => (Opcode::SUB(OpcodeParameter::Register_Register(Register::A, Register::C)), Cycles(1.0)),
-            0x92 => (Opcode::SUB(OpcodeParameter::Register_Register(Register::A, Register::D)), Cycles(1.0)),
-            0x93 => (Opcode::SUB(OpcodeParameter::Register_Register(Register::A, Register::E)), Cycles(1.0)),
-            0x94 => (Opcode::SUB(OpcodeParameter::Register_Register(Register::A, Register::H)), Cycles(1.0)),
-            0x95 => (Opcode::SUB(OpcodeParameter::Register_Register(Register::A, Register::L)), Cycles(1.0)),
-            0x96 => (Opcode::SUB(OpcodeParameter::Register_Register(Register::A, Register::HL)), Cycles(2.0)),
-            0xD6 => (Opcode::SUB(OpcodeParameter::Register_U8(Register::A, self.1)), Cycles(2.0)),
-            0x9F => (Opcode::SBC(OpcodeParameter::Register_Register(Register::A, Register::A)), Cycles(1.0)),
-            0x98 => (Opcode::SBC(OpcodeParameter::Register_Register(Register::A, Register::B)), Cycles(1.0)),
-            0x99 => (Opcode::SBC(OpcodeParameter::Register_Register(Register::A, Register::C)), Cycles(1.0)),
-            0x9A => (Opcode::SBC(OpcodeParameter::Register_Register(Register::A, Register::D)), Cycles(1.0)),
-            0x9B => (Opcode::SBC(OpcodeParameter::Register_Register(Register::A, Register::E)), Cycles(1.0)),
-            0x9C => (Opcode::SBC(OpcodeParameter::Register_Register(Register::A, Register::H)), Cycles(1.0)),
-            0x9D => (Opcode::SBC(OpcodeParameter::Register_Register(Register::A, Register::L)), Cycles(1.0)),
-            0x9E => (Opcode::SBC(OpcodeParameter::Register_Register(Register::A, Register::HL)), Cycles(2.0)),
-            0xDE => (Opcode::SBC(OpcodeParameter::Register_U8(Register::A, self.1)), Cycles(2.0)),
-            0xA7 => (Opcode::AND(OpcodeParameter::Register_Register(Register::A, Register::A)), Cycles(1.0)),
-            0xA0 => (Opcode::AND(OpcodeParameter::Register_Register(Register::A, Register::B)), Cycles(1.0)),
-            0xA1 => (Opcode::AND(OpcodeParameter::Register_Register(Register::A, Register::C)), Cycles(1.0)),
-            0xA2 => (Opcode::AND(OpcodeParameter::Register_Register(Register::A, Register::D)), Cycles(1.0)),
-            0xA3 => (Opcode::AND(OpcodeParameter::Register_Register(Register::A, Register::E)), Cycles(1.0)),
-            0xA4 => (Opcode::AND(OpcodeParameter::Register_Register(Register::A, Register::H)), Cycles(1.0)),
-            0xA5 => (Opcode::AND(OpcodeParameter::Register_Register(Register::A, Register::L)), Cycles(1.0)),
-            0xA6 => (Opcode::AND(OpcodeParameter::Register_Register(Register::A, Register::HL)), Cycles(2.0)),
-            0xE6 => (Opcode::AND(OpcodeParameter::Register_U8(Register::A, self.1)), Cycles(2.0)),
-            0xB7 => (Opcode::OR(OpcodeParameter::Register_Register(Register::A, Register::A)), Cycles(1.0)),
-            0xB0 => (Opcode::OR(OpcodeParameter::Register_Register(Register::A, Register::B)), Cycles(1.0)),
-            0xB1 => (Opcode::OR(OpcodeParameter::Register_Register(Register::A, Register::C)), Cycles(1.0)),
-            0xB2 => (Opcode::OR(OpcodeParameter::Register_Register(Register::A, Register::D)), Cycles(1.0)),
-            0xB3 => (Opcode::OR(OpcodeParameter::Register_Register(Register::A, Register::E)), Cycles(1.0)),
-            0xB4 => (Opcode::OR(OpcodeParameter::Register_Register(Register::A, Register::H)), Cycles(1.0)),
-            0xB5 => (Opcode::OR(OpcodeParameter::Register_Register(Register::A, Register::L)), Cycles(1.0)),
-            0xB6 => (Opcode::OR(OpcodeParameter::Register_Register(Register::A, Register::HL)), Cycles(2.0)),
-            0xF6 => (Opcode::OR(OpcodeParameter::Register_U8(Register::A, self.1)), Cycles(2.0)),
-            0xAF => (Opcode::XOR(OpcodeParameter::Register_Register(Register::A, Register::A)), Cycles(1.0)),
-            0xA8 => (Opcode::XOR(OpcodeParameter::Register_Register(Register::A, Register::B)), Cycles(1.0)),
-            0xA9 => (Opcode::XOR(OpcodeParameter::Register_Register(Register::A, Register::C)), Cycles(1.0)),
-            0xAA => (Opcode::XOR(OpcodeParameter::Register_Register(Register::A, Register::D)), Cycles(1.0)),
-            0xAB => (Opcode::XOR(OpcodeParameter::Register_Register(Register::A, Register::E)), Cycles(1.0)),
-            0xAC => (Opcode::XOR(OpcodeParameter::Register_Register(Register::A, Register::H)), Cycles(1.0)),
-            0xAD => (Opcode::XOR(OpcodeParameter::Register_Register(Register::A, Register::L)), Cycles(1.0)),
-            0xAE => (Opcode::XOR(OpcodeParameter::Register_Register(Register::A, Register::HL)), Cycles(2.0)),
-            0xEE => (Opcode::XOR(OpcodeParameter::Register_U8(Register::A, self.1)), Cycles(2.0)),
-            0xBF => (Opcode::CP(OpcodeParameter::Register_Register(Register::A, Register::A)), Cycles(1.0)),
-            0xB8 => (Opcode::CP(OpcodeParameter::Register_Register(Register::A, Register::B)), Cycles(1.0)),
-            0xB9 => (Opcode::CP(OpcodeParameter::Register_Register(Register::A, Register::C)), Cycles(1.0)),
-            0xBA => (Opcode::CP(OpcodeParameter::Register_Register(Register::A, Register::D)), Cycles(1.0)),
-            0xBB => (Opcode::CP(OpcodeParameter::Register_Register(Register::A, Register::E)), Cycles(1.0)),
-            0xBC => (Opcode::CP(OpcodeParameter::Register_Register(Register::A, Register::H)), Cycles(1.0)),
-            0xBD => (Opcode::CP(OpcodeParameter::Register_Register(Register::A, Register::L)), Cycles(1.0)),
-            0xBE => (Opcode::CP(OpcodeParameter::Register_Register(Register::A, Register::HL)), Cycles(2.0)),
-            0xFE => (Opcode::CP(OpcodeParameter::Register_U8(Register::A, self.1)), Cycles(2.0)),
-            0x3C => (Opcode::INC(true, false, Register::A), Cycles(1.0)),
-            0x04 => (Opcode::INC(true, false, Register::B), Cycles(1.0)),
-            0x0C => (Opcode::INC(true, false, Register::C), Cycles(1.0)),
-            0x14 => (Opcode::INC(true, false, Register::D), Cycles(1.0)),
-            0x1C => (Opcode::INC(true, false, Register::E), Cycles(1.0)),
-            0x24 => (Opcode::INC(true, false, Register::H), Cycles(1.0)),
-            0x2C => (Opcode::INC(true, false, Register::L), Cycles(1.0)),
-            0x34 => (Opcode::INC(true, true, Register::HL), Cycles(3.0)),
-            0x03 => (Opcode::INC(false, false, Register::BC), Cycles(2.0)),
-            0x13 => (Opcode::INC(false, false, Register::DE), Cycles(2.0)),
-            0x23 => (Opcode::INC(false, false, Register::HL), Cycles(2.0)),
-            0x33 => (Opcode::INC(false, false, Register::SP), Cycles(2.0)),
-            0x3D => (Opcode::DEC(true, false, Register::A), Cycles(1.0)),
-            0x05 => (Opcode::DEC(true, false, Register::B), Cycles(1.0)),
-            0x0D => (Opcode::DEC(true, false, Register::C), Cycles(1.0)),
-            0x15 => (Opcode::DEC(true, false, Register::D), Cycles(1.0)),
-            0x1D => (Opcode::DEC(true, false, Register::E), Cycles(1.0)),
-            0x25 => (Opcode::DEC(true, false, Register::H), Cycles(1.0)),
-            0x2D => (Opcode::DEC(true, false, Register::L), Cycles(1.0)),
-            0x35 => (Opcode::DEC(true, true, Register::HL), Cycles(3.0)),
-            0x0B => (Opcode::DEC(false, false, Register::BC), Cycles(2.0)),
-            0x1B => (Opcode::DEC(false, false, Register::DE), Cycles(2.0)),
-            0x2B => (Opcode::DEC(false, false, Register::HL), Cycles(2.0)),
-            0x3B => (Opcode::DEC(false, false, Register::SP), Cycles(2.0)),
-            0x27 => (Opcode::DAA, Cycles(1.0)),
-            0x2F => (Opcode::CPL, Cycles(1.0)),
-            0x3F => (Opcode::CCF, Cycles(1.0)),
-            0x37 => (Opcode::SCF, Cycles(1.0)),
-            0x17 => (Opcode::RLA, Cycles(1.0)),
-            0x07 => (Opcode::RLCA, Cycles(1.0)),
-            0x0F => (Opcode::RRCA, Cycles(1.0)),
-            0x1F => (Opcode::RRA, Cycles(1.0)),
+            0x06 => (Opcode::LD(OpcodeParameter::Register_U8(Register::B, self.1)), MCycles(2)),
+            0x0E => (Opcode::LD(OpcodeParameter::Register_U8(Register::C, self.1)), MCycles(2)),
+            0x16 => (Opcode::LD(OpcodeParameter::Register_U8(Register::D, self.1)), MCycles(2)),
+            0x1E => (Opcode::LD(OpcodeParameter::Register_U8(Register::E, self.1)), MCycles(2)),
+            0x26 => (Opcode::LD(OpcodeParameter::Register_U8(Register::H, self.1)), MCycles(2)),
+            0x2E => (Opcode::LD(OpcodeParameter::Register_U8(Register::L, self.1)), MCycles(2)),
+            0x7F => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::A)), MCycles(1)),
+            0x78 => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::B)), MCycles(1)),
+            0x79 => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::C)), MCycles(1)),
+            0x7A => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::D)), MCycles(1)),
+            0x7B => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::E)), MCycles(1)),
+            0x7C => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::H)), MCycles(1)),
+            0x7D => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::L)), MCycles(1)),
+            0x7E => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::HL)), MCycles(2)),
+            0x40 => (Opcode::LD(OpcodeParameter::Register_Register(Register::B, Register::B)), MCycles(1)),
+            0x41 => (Opcode::LD(OpcodeParameter::Register_Register(Register::B, Register::C)), MCycles(1)),
+            0x42 => (Opcode::LD(OpcodeParameter::Register_Register(Register::B, Register::D)), MCycles(1)),
+            0x43 => (Opcode::LD(OpcodeParameter::Register_Register(Register::B, Register::E)), MCycles(1)),
+            0x44 => (Opcode::LD(OpcodeParameter::Register_Register(Register::B, Register::H)), MCycles(1)),
+            0x45 => (Opcode::LD(OpcodeParameter::Register_Register(Register::B, Register::L)), MCycles(1)),
+            0x46 => (Opcode::LD(OpcodeParameter::Register_Register(Register::B, Register::HL)), MCycles(2)),
+            0x48 => (Opcode::LD(OpcodeParameter::Register_Register(Register::C, Register::B)), MCycles(1)),
+            0x49 => (Opcode::LD(OpcodeParameter::Register_Register(Register::C, Register::C)), MCycles(1)),
+            0x4A => (Opcode::LD(OpcodeParameter::Register_Register(Register::C, Register::D)), MCycles(1)),
+            0x4B => (Opcode::LD(OpcodeParameter::Register_Register(Register::C, Register::E)), MCycles(1)),
+            0x4C => (Opcode::LD(OpcodeParameter::Register_Register(Register::C, Register::H)), MCycles(1)),
+            0x4D => (Opcode::LD(OpcodeParameter::Register_Register(Register::C, Register::L)), MCycles(1)),
+            0x4E => (Opcode::LD(OpcodeParameter::Register_Register(Register::C, Register::HL)), MCycles(2)),
+            0x50 => (Opcode::LD(OpcodeParameter::Register_Register(Register::D, Register::B)), MCycles(1)),
+            0x51 => (Opcode::LD(OpcodeParameter::Register_Register(Register::D, Register::C)), MCycles(1)),
+            0x52 => (Opcode::LD(OpcodeParameter::Register_Register(Register::D, Register::D)), MCycles(1)),
+            0x53 => (Opcode::LD(OpcodeParameter::Register_Register(Register::D, Register::E)), MCycles(1)),
+            0x54 => (Opcode::LD(OpcodeParameter::Register_Register(Register::D, Register::H)), MCycles(1)),
+            0x55 => (Opcode::LD(OpcodeParameter::Register_Register(Register::D, Register::L)), MCycles(1)),
+            0x56 => (Opcode::LD(OpcodeParameter::Register_Register(Register::D, Register::HL)), MCycles(2)),
+            0x58 => (Opcode::LD(OpcodeParameter::Register_Register(Register::E, Register::B)), MCycles(1)),
+            0x59 => (Opcode::LD(OpcodeParameter::Register_Register(Register::E, Register::C)), MCycles(1)),
+            0x5A => (Opcode::LD(OpcodeParameter::Register_Register(Register::E, Register::D)), MCycles(1)),
+            0x5B => (Opcode::LD(OpcodeParameter::Register_Register(Register::E, Register::E)), MCycles(1)),
+            0x5C => (Opcode::LD(OpcodeParameter::Register_Register(Register::E, Register::H)), MCycles(1)),
+            0x5D => (Opcode::LD(OpcodeParameter::Register_Register(Register::E, Register::L)), MCycles(1)),
+            0x5E => (Opcode::LD(OpcodeParameter::Register_Register(Register::E, Register::HL)), MCycles(2)),
+            0x60 => (Opcode::LD(OpcodeParameter::Register_Register(Register::H, Register::B)), MCycles(1)),
+            0x61 => (Opcode::LD(OpcodeParameter::Register_Register(Register::H, Register::C)), MCycles(1)),
+            0x62 => (Opcode::LD(OpcodeParameter::Register_Register(Register::H, Register::D)), MCycles(1)),
+            0x63 => (Opcode::LD(OpcodeParameter::Register_Register(Register::H, Register::E)), MCycles(1)),
+            0x64 => (Opcode::LD(OpcodeParameter::Register_Register(Register::H, Register::H)), MCycles(1)),
+            0x65 => (Opcode::LD(OpcodeParameter::Register_Register(Register::H, Register::L)), MCycles(1)),
+            0x66 => (Opcode::LD(OpcodeParameter::Register_Register(Register::H, Register::HL)), MCycles(2)),
+            0x68 => (Opcode::LD(OpcodeParameter::Register_Register(Register::L, Register::B)), MCycles(1)),
+            0x69 => (Opcode::LD(OpcodeParameter::Register_Register(Register::L, Register::C)), MCycles(1)),
+            0x6A => (Opcode::LD(OpcodeParameter::Register_Register(Register::L, Register::D)), MCycles(1)),
+            0x6B => (Opcode::LD(OpcodeParameter::Register_Register(Register::L, Register::E)), MCycles(1)),
+            0x6C => (Opcode::LD(OpcodeParameter::Register_Register(Register::L, Register::H)), MCycles(1)),
+            0x6D => (Opcode::LD(OpcodeParameter::Register_Register(Register::L, Register::L)), MCycles(1)),
+            0x6E => (Opcode::LD(OpcodeParameter::Register_Register(Register::L, Register::HL)), MCycles(2)),
+            0x70 => (Opcode::LD(OpcodeParameter::Register_Register(Register::HL, Register::B)), MCycles(2)),
+            0x71 => (Opcode::LD(OpcodeParameter::Register_Register(Register::HL, Register::C)), MCycles(2)),
+            0x72 => (Opcode::LD(OpcodeParameter::Register_Register(Register::HL, Register::D)), MCycles(2)),
+            0x73 => (Opcode::LD(OpcodeParameter::Register_Register(Register::HL, Register::E)), MCycles(2)),
+            0x74 => (Opcode::LD(OpcodeParameter::Register_Register(Register::HL, Register::H)), MCycles(2)),
+            0x75 => (Opcode::LD(OpcodeParameter::Register_Register(Register::HL, Register::L)), MCycles(2)),
+            0x47 => (Opcode::LD(OpcodeParameter::Register_Register(Register::B, Register::A)), MCycles(1)),
+            0x4F => (Opcode::LD(OpcodeParameter::Register_Register(Register::C, Register::A)), MCycles(1)),
+            0x57 => (Opcode::LD(OpcodeParameter::Register_Register(Register::D, Register::A)), MCycles(1)),
+            0x5F => (Opcode::LD(OpcodeParameter::Register_Register(Register::E, Register::A)), MCycles(1)),
+            0x67 => (Opcode::LD(OpcodeParameter::Register_Register(Register::H, Register::A)), MCycles(1)),
+            0x6F => (Opcode::LD(OpcodeParameter::Register_Register(Register::L, Register::A)), MCycles(1)),
+            0x02 => (Opcode::LD(OpcodeParameter::Register_Register(Register::BC, Register::A)), MCycles(2)),
+            0x12 => (Opcode::LD(OpcodeParameter::Register_Register(Register::DE, Register::A)), MCycles(2)),
+            0x77 => (Opcode::LD(OpcodeParameter::Register_Register(Register::HL, Register::A)), MCycles(2)),
+            0x36 => (Opcode::LD(OpcodeParameter::Register_U8(Register::HL, self.1)), MCycles(3)),
+            0x0A => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::BC)), MCycles(2)),
+            0x1A => (Opcode::LD(OpcodeParameter::Register_Register(Register::A, Register::DE)), MCycles(2)),
+            0xFA => (Opcode::LD(OpcodeParameter::Register_U16(Register::A, two_byte_param)), MCycles(4)),
+            0x3E => (Opcode::LD(OpcodeParameter::Register_U8(Register::A, self.1)), MCycles(2)),
+            0xEA => (Opcode::LD(OpcodeParameter::U16_Register(two_byte_param, Register::A)), MCycles(4)),
+            0xF2 => (Opcode::LD(OpcodeParameter::Register_FF00plusRegister(Register::A, Register::C)), MCycles(2)),
+            0xE2 => (Opcode::LD(OpcodeParameter::FF00plusRegister_Register(Register::C, Register::A)), MCycles(2)),
+            0x3A => (Opcode::LDD(OpcodeParameter::Register_RegisterDecrement(Register::A, Register::HL)), MCycles(2)),
+            0x32 => (Opcode::LDD(OpcodeParameter::RegisterDecrement_Register(Register::HL, Register::A)), MCycles(2)),
+            0x2A => (Opcode::LDI(OpcodeParameter::Register_RegisterIncrement(Register::A, Register::HL)), MCycles(2)),
+            0x22 => (Opcode::LDI(OpcodeParameter::RegisterIncrement_Register(Register::HL, Register::A)), MCycles(2)),
+            0xE0 => (Opcode::LD(OpcodeParameter::FF00plusU8_Register(self.1, Register::A)), MCycles(3)),
+            0xF0 => (Opcode::LD(OpcodeParameter::Register_FF00plusU8(Register::A, self.1)), MCycles(3)),
+            0x01 => (Opcode::LD(OpcodeParameter::Register_U16(Register::BC, two_byte_param)), MCycles(3)),
+            0x11 => (Opcode::LD(OpcodeParameter::Register_U16(Register::DE, two_byte_param)), MCycles(3)),
+            0x21 => (Opcode::LD(OpcodeParameter::Register_U16(Register::HL, two_byte_param)), MCycles(3)),
+            0x31 => (Opcode::LD(OpcodeParameter::Register_U16(Register::SP, two_byte_param)), MCycles(3)),
+            0xF9 => (Opcode::LD(OpcodeParameter::Register_Register(Register::SP, Register::HL)), MCycles(2)),
+            0xF8 => (Opcode::LD(OpcodeParameter::Register_RegisterPlusI8(Register::HL, Register::SP, self.1 as i8)), MCycles(3)),
+            0x08 => (Opcode::LD(OpcodeParameter::U16_Register(two_byte_param, Register::SP)), MCycles(5)),
+            0xC5 => (Opcode::PUSH(Register::BC), MCycles(4)),
+            0xD5 => (Opcode::PUSH(Register::DE), MCycles(4)),
+            0xE5 => (Opcode::PUSH(Register::HL), MCycles(4)),
+            0xF5 => (Opcode::PUSH(Register::AF), MCycles(4)),
+            0xC1 => (Opcode::POP(Register::BC), MCycles(3)),
+            0xD1 => (Opcode::POP(Register::DE), MCycles(3)),
+            0xE1 => (Opcode::POP(Register::HL), MCycles(3)),
+            0xF1 => (Opcode::POP(Register::AF), MCycles(3)),
+            0x87 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::A, Register::A)), MCycles(1)),
+            0x80 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::A, Register::B)), MCycles(1)),
+            0x81 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::A, Register::C)), MCycles(1)),
+            0x82 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::A, Register::D)), MCycles(1)),
+            0x83 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::A, Register::E)), MCycles(1)),
+            0x84 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::A, Register::H)), MCycles(1)),
+            0x85 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::A, Register::L)), MCycles(1)),
+            0x86 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::A, Register::HL)), MCycles(2)),
+            0xC6 => (Opcode::ADD(OpcodeParameter::Register_U8(Register::A, self.1)), MCycles(2)),
+            0x09 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::HL, Register::BC)), MCycles(2)),
+            0x19 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::HL, Register::DE)), MCycles(2)),
+            0x29 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::HL, Register::HL)), MCycles(2)),
+            0x39 => (Opcode::ADD(OpcodeParameter::Register_Register(Register::HL, Register::SP)), MCycles(2)),
+            0xE8 => (Opcode::ADD(OpcodeParameter::Register_I8(Register::SP, self.1 as i8)), MCycles(4)),
+            0x8F => (Opcode::ADC(OpcodeParameter::Register_Register(Register::A, Register::A)), MCycles(1)),
+            0x88 => (Opcode::ADC(OpcodeParameter::Register_Register(Register::A, Register::B)), MCycles(1)),
+            0x89 => (Opcode::ADC(OpcodeParameter::Register_Register(Register::A, Register::C)), MCycles(1)),
+            0x8A => (Opcode::ADC(OpcodeParameter::Register_Register(Register::A, Register::D)), MCycles(1)),
+            0x8B => (Opcode::ADC(OpcodeParameter::Register_Register(Register::A, Register::E)), MCycles(1)),
+            0x8C => (Opcode::ADC(OpcodeParameter::Register_Register(Register::A, Register::H)), MCycles(1)),
+            0x8D => (Opcode::ADC(OpcodeParameter::Register_Register(Register::A, Register::L)), MCycles(1)),
+            0x8E => (Opcode::ADC(OpcodeParameter::Register_Register(Register::A, Register::HL)), MCycles(2)),
+            0xCE => (Opcode::ADC(OpcodeParameter::Register_U8(Register::A, self.1)), MCycles(2)),
+            0x97 => (Opcode::SUB(OpcodeParameter::Register_Register(Register::A, Register::A)), MCycles(1)),
+            0x90 => (Opcode::SUB(OpcodeParameter::Register_Register(Register::A, Register::B)), MCycles(1)),
+            0x91 => (Opcode::SUB(OpcodeParameter::Register_Register(Register::A, Register::C)), MCycles(1)),
+            0x92 => (Opcode::SUB(OpcodeParameter::Register_Register(Register::A, Register::D)), MCycles(1)),
+            0x93 => (Opcode::SUB(OpcodeParameter::Register_Register(Register::A, Register::E)), MCycles(1)),
+            0x94 => (Opcode::SUB(OpcodeParameter::Register_Register(Register::A, Register::H)), MCycles(1)),
+            0x95 => (Opcode::SUB(OpcodeParameter::Register_Register(Register::A, Register::L)), MCycles(1)),
+            0x96 => (Opcode::SUB(OpcodeParameter::Register_Register(Register::A, Register::HL)), MCycles(2)),
+            0xD6 => (Opcode::SUB(OpcodeParameter::Register_U8(Register::A, self.1)), MCycles(2)),
+            0x9F => (Opcode::SBC(OpcodeParameter::Register_Register(Register::A, Register::A)), MCycles(1)),
+            0x98 => (Opcode::SBC(OpcodeParameter::Register_Register(Register::A, Register::B)), MCycles(1)),
+            0x99 => (Opcode::SBC(OpcodeParameter::Register_Register(Register::A, Register::C)), MCycles(1)),
+            0x9A => (Opcode::SBC(OpcodeParameter::Register_Register(Register::A, Register::D)), MCycles(1)),
+            0x9B => (Opcode::SBC(OpcodeParameter::Register_Register(Register::A, Register::E)), MCycles(1)),
+            0x9C => (Opcode::SBC(OpcodeParameter::Register_Register(Register::A, Register::H)), MCycles(1)),
+            0x9D => (Opcode::SBC(OpcodeParameter::Register_Register(Register::A, Register::L)), MCycles(1)),
+            0x9E => (Opcode::SBC(OpcodeParameter::Register_Register(Register::A, Register::HL)), MCycles(2)),
+            0xDE => (Opcode::SBC(OpcodeParameter::Register_U8(Register::A, self.1)), MCycles(2)),
+            0xA7 => (Opcode::AND(OpcodeParameter::Register_Register(Register::A, Register::A)), MCycles(1)),
+            0xA0 => (Opcode::AND(OpcodeParameter::Register_Register(Register::A, Register::B)), MCycles(1)),
+            0xA1 => (Opcode::AND(OpcodeParameter::Register_Register(Register::A, Register::C)), MCycles(1)),
+            0xA2 => (Opcode::AND(OpcodeParameter::Register_Register(Register::A, Register::D)), MCycles(1)),
+            0xA3 => (Opcode::AND(OpcodeParameter::Register_Register(Register::A, Register::E)), MCycles(1)),
+            0xA4 => (Opcode::AND(OpcodeParameter::Register_Register(Register::A, Register::H)), MCycles(1)),
+            0xA5 => (Opcode::AND(OpcodeParameter::Register_Register(Register::A, Register::L)), MCycles(1)),
+            0xA6 => (Opcode::AND(OpcodeParameter::Register_Register(Register::A, Register::HL)), MCycles(2)),
+            0xE6 => (Opcode::AND(OpcodeParameter::Register_U8(Register::A, self.1)), MCycles(2)),
+            0xB7 => (Opcode::OR(OpcodeParameter::Register_Register(Register::A, Register::A)), MCycles(1)),
+            0xB0 => (Opcode::OR(OpcodeParameter::Register_Register(Register::A, Register::B)), MCycles(1)),
+            0xB1 => (Opcode::OR(OpcodeParameter::Register_Register(Register::A, Register::C)), MCycles(1)),
+            0xB2 => (Opcode::OR(OpcodeParameter::Register_Register(Register::A, Register::D)), MCycles(1)),
+            0xB3 => (Opcode::OR(OpcodeParameter::Register_Register(Register::A, Register::E)), MCycles(1)),
+            0xB4 => (Opcode::OR(OpcodeParameter::Register_Register(Register::A, Register::H)), MCycles(1)),
+            0xB5 => (Opcode::OR(OpcodeParameter::Register_Register(Register::A, Register::L)), MCycles(1)),
+            0xB6 => (Opcode::OR(OpcodeParameter::Register_Register(Register::A, Register::HL)), MCycles(2)),
+            0xF6 => (Opcode::OR(OpcodeParameter::Register_U8(Register::A, self.1)), MCycles(2)),
+            0xAF => (Opcode::XOR(OpcodeParameter::Register_Register(Register::A, Register::A)), MCycles(1)),
+            0xA8 => (Opcode::XOR(OpcodeParameter::Register_Register(Register::A, Register::B)), MCycles(1)),
+            0xA9 => (Opcode::XOR(OpcodeParameter::Register_Register(Register::A, Register::C)), MCycles(1)),
+            0xAA => (Opcode::XOR(OpcodeParameter::Register_Register(Register::A, Register::D)), MCycles(1)),
+            0xAB => (Opcode::XOR(OpcodeParameter::Register_Register(Register::A, Register::E)), MCycles(1)),
+            0xAC => (Opcode::XOR(OpcodeParameter::Register_Register(Register::A, Register::H)), MCycles(1)),
+            0xAD => (Opcode::XOR(OpcodeParameter::Register_Register(Register::A, Register::L)), MCycles(1)),
+            0xAE => (Opcode::XOR(OpcodeParameter::Register_Register(Register::A, Register::HL)), MCycles(2)),
+            0xEE => (Opcode::XOR(OpcodeParameter::Register_U8(Register::A, self.1)), MCycles(2)),
+            0xBF => (Opcode::CP(OpcodeParameter::Register_Register(Register::A, Register::A)), MCycles(1)),
+            0xB8 => (Opcode::CP(OpcodeParameter::Register_Register(Register::A, Register::B)), MCycles(1)),
+            0xB9 => (Opcode::CP(OpcodeParameter::Register_Register(Register::A, Register::C)), MCycles(1)),
+            0xBA => (Opcode::CP(OpcodeParameter::Register_Register(Register::A, Register::D)), MCycles(1)),
+            0xBB => (Opcode::CP(OpcodeParameter::Register_Register(Register::A, Register::E)), MCycles(1)),
+            0xBC => (Opcode::CP(OpcodeParameter::Register_Register(Register::A, Register::H)), MCycles(1)),
+            0xBD => (Opcode::CP(OpcodeParameter::Register_Register(Register::A, Register::L)), MCycles(1)),
+            0xBE => (Opcode::CP(OpcodeParameter::Register_Register(Register::A, Register::HL)), MCycles(2)),
+            0xFE => (Opcode::CP(OpcodeParameter::Register_U8(Register::A, self.1)), MCycles(2)),
+            0x3C => (Opcode::INC(true, false, Register::A), MCycles(1)),
+            0x04 => (Opcode::INC(true, false, Register::B), MCycles(1)),
+            0x0C => (Opcode::INC(true, false, Register::C), MCycles(1)),
+            0x14 => (Opcode::INC(true, false, Register::D), MCycles(1)),
+            0x1C => (Opcode::INC(true, false, Register::E), MCycles(1)),
+            0x24 => (Opcode::INC(true, false, Register::H), MCycles(1)),
+            0x2C => (Opcode::INC(true, false, Register::L), MCycles(1)),
+            0x34 => (Opcode::INC(true, true, Register::HL), MCycles(3)),
+            0x03 => (Opcode::INC(false, false, Register::BC), MCycles(2)),
+            0x13 => (Opcode::INC(false, false, Register::DE), MCycles(2)),
+            0x23 => (Opcode::INC(false, false, Register::HL), MCycles(2)),
+            0x33 => (Opcode::INC(false, false, Register::SP), MCycles(2)),
+            0x3D => (Opcode::DEC(true, false, Register::A), MCycles(1)),
+            0x05 => (Opcode::DEC(true, false, Register::B), MCycles(1)),
+            0x0D => (Opcode::DEC(true, false, Register::C), MCycles(1)),
+            0x15 => (Opcode::DEC(true, false, Register::D), MCycles(1)),
+            0x1D => (Opcode::DEC(true, false, Register::E), MCycles(1)),
+            0x25 => (Opcode::DEC(true, false, Register::H), MCycles(1)),
+            0x2D => (Opcode::DEC(true, false, Register::L), MCycles(1)),
+            0x35 => (Opcode::DEC(true, true, Register::HL), MCycles(3)),
+            0x0B => (Opcode::DEC(false, false, Register::BC), MCycles(2)),
+            0x1B => (Opcode::DEC(false, false, Register::DE), MCycles(2)),
+            0x2B => (Opcode::DEC(false, false, Register::HL), MCycles(2)),
+            0x3B => (Opcode::DEC(false, false, Register::SP), MCycles(2)),
+            0x27 => (Opcode::DAA, MCycles(1)),
+            0x2F => (Opcode::CPL, MCycles(1)),
+            0x3F => (Opcode::CCF, MCycles(1)),
+            0x37 => (Opcode::SCF, MCycles(1)),
+            0x17 => (Opcode::RLA, MCycles(1)),
+            0x07 => (Opcode::RLCA, MCycles(1)),
+            0x0F => (Opcode::RRCA, MCycles(1)),
+            0x1F => (Opcode::RRA, MCycles(1)),
             0xCB => match self.1 {
-                0x00 => (Opcode::PrefixCB(CBOpcode::RLC(Register::B)), Cycles(2.0)),
-                0x01 => (Opcode::PrefixCB(CBOpcode::RLC(Register::C)), Cycles(2.0)),
-                0x02 => (Opcode::PrefixCB(CBOpcode::RLC(Register::D)), Cycles(2.0)),
-                0x03 => (Opcode::PrefixCB(CBOpcode::RLC(Register::E)), Cycles(2.0)),
-                0x04 => (Opcode::PrefixCB(CBOpcode::RLC(Register::H)), Cycles(2.0)),
-                0x05 => (Opcode::PrefixCB(CBOpcode::RLC(Register::L)), Cycles(2.0)),
-                0x06 => (Opcode::PrefixCB(CBOpcode::RLC(Register::HL)), Cycles(4.0)),
-                0x07 => (Opcode::PrefixCB(CBOpcode::RLC(Register::A)), Cycles(2.0)),
-
-                0x08 => (Opcode::PrefixCB(CBOpcode::RRC(Register::B)), Cycles(2.0)),
-                0x09 => (Opcode::PrefixCB(CBOpcode::RRC(Register::C)), Cycles(2.0)),
-                0x0A => (Opcode::PrefixCB(CBOpcode::RRC(Register::D)), Cycles(2.0)),
-                0x0B => (Opcode::PrefixCB(CBOpcode::RRC(Register::E)), Cycles(2.0)),
-                0x0C => (Opcode::PrefixCB(CBOpcode::RRC(Register::H)), Cycles(2.0)),
-                0x0D => (Opcode::PrefixCB(CBOpcode::RRC(Register::L)), Cycles(2.0)),
-                0x0E => (Opcode::PrefixCB(CBOpcode::RRC(Register::HL)), Cycles(4.0)),
-                0x0F => (Opcode::PrefixCB(CBOpcode::RRC(Register::A)), Cycles(2.0)),
-
-                0x10 => (Opcode::PrefixCB(CBOpcode::RL(Register::B)), Cycles(2.0)),
-                0x11 => (Opcode::PrefixCB(CBOpcode::RL(Register::C)), Cycles(2.0)),
-                0x12 => (Opcode::PrefixCB(CBOpcode::RL(Register::D)), Cycles(2.0)),
-                0x13 => (Opcode::PrefixCB(CBOpcode::RL(Register::E)), Cycles(2.0)),
-                0x14 => (Opcode::PrefixCB(CBOpcode::RL(Register::H)), Cycles(2.0)),
-                0x15 => (Opcode::PrefixCB(CBOpcode::RL(Register::L)), Cycles(2.0)),
-                0x16 => (Opcode::PrefixCB(CBOpcode::RL(Register::HL)), Cycles(4.0)),
-                0x17 => (Opcode::PrefixCB(CBOpcode::RL(Register::A)), Cycles(2.0)),
-
-                0x18 => (Opcode::PrefixCB(CBOpcode::RR(Register::B)), Cycles(2.0)),
-                0x19 => (Opcode::PrefixCB(CBOpcode::RR(Register::C)), Cycles(2.0)),
-                0x1A => (Opcode::PrefixCB(CBOpcode::RR(Register::D)), Cycles(2.0)),
-                0x1B => (Opcode::PrefixCB(CBOpcode::RR(Register::E)), Cycles(2.0)),
-                0x1C => (Opcode::PrefixCB(CBOpcode::RR(Register::H)), Cycles(2.0)),
-                0x1D => (Opcode::PrefixCB(CBOpcode::RR(Register::L)), Cycles(2.0)),
-                0x1E => (Opcode::PrefixCB(CBOpcode::RR(Register::HL)), Cycles(4.0)),
-                0x1F => (Opcode::PrefixCB(CBOpcode::RR(Register::A)), Cycles(2.0)),
-
-                0x20 => (Opcode::PrefixCB(CBOpcode::SLA(Register::B)), Cycles(2.0)),
-                0x21 => (Opcode::PrefixCB(CBOpcode::SLA(Register::C)), Cycles(2.0)),
-                0x22 => (Opcode::PrefixCB(CBOpcode::SLA(Register::D)), Cycles(2.0)),
-                0x23 => (Opcode::PrefixCB(CBOpcode::SLA(Register::E)), Cycles(2.0)),
-                0x24 => (Opcode::PrefixCB(CBOpcode::SLA(Register::H)), Cycles(2.0)),
-                0x25 => (Opcode::PrefixCB(CBOpcode::SLA(Register::L)), Cycles(2.0)),
-                0x26 => (Opcode::PrefixCB(CBOpcode::SLA(Register::HL)), Cycles(4.0)),
-                0x27 => (Opcode::PrefixCB(CBOpcode::SLA(Register::A)), Cycles(2.0)),
-
-                0x28 => (Opcode::PrefixCB(CBOpcode::SRA(Register::B)), Cycles(2.0)),
-                0x29 => (Opcode::PrefixCB(CBOpcode::SRA(Register::C)), Cycles(2.0)),
-                0x2A => (Opcode::PrefixCB(CBOpcode::SRA(Register::D)), Cycles(2.0)),
-                0x2B => (Opcode::PrefixCB(CBOpcode::SRA(Register::E)), Cycles(2.0)),
-                0x2C => (Opcode::PrefixCB(CBOpcode::SRA(Register::H)), Cycles(2.0)),
-                0x2D => (Opcode::PrefixCB(CBOpcode::SRA(Register::L)), Cycles(2.0)),
-                0x2E => (Opcode::PrefixCB(CBOpcode::SRA(Register::HL)), Cycles(4.0)),
-                0x2F => (Opcode::PrefixCB(CBOpcode::SRA(Register::A)), Cycles(2.0)),
-
-                0x30 => (Opcode::PrefixCB(CBOpcode::SWAP(Register::B)), Cycles(2.0)),
-                0x31 => (Opcode::PrefixCB(CBOpcode::SWAP(Register::C)), Cycles(2.0)),
-                0x32 => (Opcode::PrefixCB(CBOpcode::SWAP(Register::D)), Cycles(2.0)),
-                0x33 => (Opcode::PrefixCB(CBOpcode::SWAP(Register::E)), Cycles(2.0)),
-                0x34 => (Opcode::PrefixCB(CBOpcode::SWAP(Register::H)), Cycles(2.0)),
-                0x35 => (Opcode::PrefixCB(CBOpcode::SWAP(Register::L)), Cycles(2.0)),
-                0x36 => (Opcode::PrefixCB(CBOpcode::SWAP(Register::HL)), Cycles(4.0)),
-                0x37 => (Opcode::PrefixCB(CBOpcode::SWAP(Register::A)), Cycles(2.0)),
-
-                0x38 => (Opcode::PrefixCB(CBOpcode::SRL(Register::B)), Cycles(2.0)),
-                0x39 => (Opcode::PrefixCB(CBOpcode::SRL(Register::C)), Cycles(2.0)),
-                0x3A => (Opcode::PrefixCB(CBOpcode::SRL(Register::D)), Cycles(2.0)),
-                0x3B => (Opcode::PrefixCB(CBOpcode::SRL(Register::E)), Cycles(2.0)),
-                0x3C => (Opcode::PrefixCB(CBOpcode::SRL(Register::H)), Cycles(2.0)),
-                0x3D => (Opcode::PrefixCB(CBOpcode::SRL(Register::L)), Cycles(2.0)),
-                0x3E => (Opcode::PrefixCB(CBOpcode::SRL(Register::HL)), Cycles(4.0)),
-                0x3F => (Opcode::PrefixCB(CBOpcode::SRL(Register::A)), Cycles(2.0)),
-
-                0x40 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I0, Register::B)), Cycles(2.0)),
-                0x41 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I0, Register::C)), Cycles(2.0)),
-                0x42 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I0, Register::D)), Cycles(2.0)),
-                0x43 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I0, Register::E)), Cycles(2.0)),
-                0x44 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I0, Register::H)), Cycles(2.0)),
-                0x45 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I0, Register::L)), Cycles(2.0)),
-                0x46 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I0, Register::HL)), Cycles(3.0)),
-                0x47 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I0, Register::A)), Cycles(2.0)),
-                0x48 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I1, Register::B)), Cycles(2.0)),
-                0x49 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I1, Register::C)), Cycles(2.0)),
-                0x4A => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I1, Register::D)), Cycles(2.0)),
-                0x4B => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I1, Register::E)), Cycles(2.0)),
-                0x4C => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I1, Register::H)), Cycles(2.0)),
-                0x4D => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I1, Register::L)), Cycles(2.0)),
-                0x4E => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I1, Register::HL)), Cycles(3.0)),
-                0x4F => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I1, Register::A)), Cycles(2.0)),
-                0x50 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I2, Register::B)), Cycles(2.0)),
-                0x51 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I2, Register::C)), Cycles(2.0)),
-                0x52 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I2, Register::D)), Cycles(2.0)),
-                0x53 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I2, Register::E)), Cycles(2.0)),
-                0x54 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I2, Register::H)), Cycles(2.0)),
-                0x55 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I2, Register::L)), Cycles(2.0)),
-                0x56 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I2, Register::HL)), Cycles(3.0)),
-                0x57 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I2, Register::A)), Cycles(2.0)),
-                0x58 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I3, Register::B)), Cycles(2.0)),
-                0x59 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I3, Register::C)), Cycles(2.0)),
-                0x5A => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I3, Register::D)), Cycles(2.0)),
-                0x5B => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I3, Register::E)), Cycles(2.0)),
-                0x5C => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I3, Register::H)), Cycles(2.0)),
-                0x5D => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I3, Register::L)), Cycles(2.0)),
-                0x5E => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I3, Register::HL)), Cycles(3.0)),
-                0x5F => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I3, Register::A)), Cycles(2.0)),
-                0x60 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I4, Register::B)), Cycles(2.0)),
-                0x61 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I4, Register::C)), Cycles(2.0)),
-                0x62 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I4, Register::D)), Cycles(2.0)),
-                0x63 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I4, Register::E)), Cycles(2.0)),
-                0x64 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I4, Register::H)), Cycles(2.0)),
-                0x65 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I4, Register::L)), Cycles(2.0)),
-                0x66 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I4, Register::HL)), Cycles(3.0)),
-                0x67 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I4, Register::A)), Cycles(2.0)),
-                0x68 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I5, Register::B)), Cycles(2.0)),
-                0x69 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I5, Register::C)), Cycles(2.0)),
-                0x6A => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I5, Register::D)), Cycles(2.0)),
-                0x6B => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I5, Register::E)), Cycles(2.0)),
-                0x6C => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I5, Register::H)), Cycles(2.0)),
-                0x6D => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I5, Register::L)), Cycles(2.0)),
-                0x6E => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I5, Register::HL)), Cycles(3.0)),
-                0x6F => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I5, Register::A)), Cycles(2.0)),
-                0x70 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I6, Register::B)), Cycles(2.0)),
-                0x71 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I6, Register::C)), Cycles(2.0)),
-                0x72 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I6, Register::D)), Cycles(2.0)),
-                0x73 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I6, Register::E)), Cycles(2.0)),
-                0x74 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I6, Register::H)), Cycles(2.0)),
-                0x75 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I6, Register::L)), Cycles(2.0)),
-                0x76 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I6, Register::HL)), Cycles(3.0)),
-                0x77 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I6, Register::A)), Cycles(2.0)),
-                0x78 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I7, Register::B)), Cycles(2.0)),
-                0x79 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I7, Register::C)), Cycles(2.0)),
-                0x7A => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I7, Register::D)), Cycles(2.0)),
-                0x7B => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I7, Register::E)), Cycles(2.0)),
-                0x7C => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I7, Register::H)), Cycles(2.0)),
-                0x7D => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I7, Register::L)), Cycles(2.0)),
-                0x7E => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I7, Register::HL)), Cycles(3.0)),
-                0x7F => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I7, Register::A)), Cycles(2.0)),
-
-                0x80 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I0, Register::B)), Cycles(2.0)),
-                0x81 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I0, Register::C)), Cycles(2.0)),
-                0x82 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I0, Register::D)), Cycles(2.0)),
-                0x83 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I0, Register::E)), Cycles(2.0)),
-                0x84 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I0, Register::H)), Cycles(2.0)),
-                0x85 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I0, Register::L)), Cycles(2.0)),
-                0x86 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I0, Register::HL)), Cycles(4.0)),
-                0x87 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I0, Register::A)), Cycles(2.0)),
-                0x88 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I1, Register::B)), Cycles(2.0)),
-                0x89 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I1, Register::C)), Cycles(2.0)),
-                0x8A => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I1, Register::D)), Cycles(2.0)),
-                0x8B => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I1, Register::E)), Cycles(2.0)),
-                0x8C => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I1, Register::H)), Cycles(2.0)),
-                0x8D => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I1, Register::L)), Cycles(2.0)),
-                0x8E => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I1, Register::HL)), Cycles(4.0)),
-                0x8F => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I1, Register::A)), Cycles(2.0)),
-                0x90 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I2, Register::B)), Cycles(2.0)),
-                0x91 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I2, Register::C)), Cycles(2.0)),
-                0x92 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I2, Register::D)), Cycles(2.0)),
-                0x93 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I2, Register::E)), Cycles(2.0)),
-                0x94 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I2, Register::H)), Cycles(2.0)),
-                0x95 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I2, Register::L)), Cycles(2.0)),
-                0x96 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I2, Register::HL)), Cycles(4.0)),
-                0x97 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I2, Register::A)), Cycles(2.0)),
-                0x98 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I3, Register::B)), Cycles(2.0)),
-                0x99 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I3, Register::C)), Cycles(2.0)),
-                0x9A => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I3, Register::D)), Cycles(2.0)),
-                0x9B => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I3, Register::E)), Cycles(2.0)),
-                0x9C => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I3, Register::H)), Cycles(2.0)),
-                0x9D => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I3, Register::L)), Cycles(2.0)),
-                0x9E => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I3, Register::HL)), Cycles(4.0)),
-                0x9F => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I3, Register::A)), Cycles(2.0)),
-                0xA0 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I4, Register::B)), Cycles(2.0)),
-                0xA1 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I4, Register::C)), Cycles(2.0)),
-                0xA2 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I4, Register::D)), Cycles(2.0)),
-                0xA3 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I4, Register::E)), Cycles(2.0)),
-                0xA4 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I4, Register::H)), Cycles(2.0)),
-                0xA5 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I4, Register::L)), Cycles(2.0)),
-                0xA6 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I4, Register::HL)), Cycles(4.0)),
-                0xA7 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I4, Register::A)), Cycles(2.0)),
-                0xA8 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I5, Register::B)), Cycles(2.0)),
-                0xA9 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I5, Register::C)), Cycles(2.0)),
-                0xAA => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I5, Register::D)), Cycles(2.0)),
-                0xAB => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I5, Register::E)), Cycles(2.0)),
-                0xAC => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I5, Register::H)), Cycles(2.0)),
-                0xAD => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I5, Register::L)), Cycles(2.0)),
-                0xAE => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I5, Register::HL)), Cycles(4.0)),
-                0xAF => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I5, Register::A)), Cycles(2.0)),
-                0xB0 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I6, Register::B)), Cycles(2.0)),
-                0xB1 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I6, Register::C)), Cycles(2.0)),
-                0xB2 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I6, Register::D)), Cycles(2.0)),
-                0xB3 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I6, Register::E)), Cycles(2.0)),
-                0xB4 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I6, Register::H)), Cycles(2.0)),
-                0xB5 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I6, Register::L)), Cycles(2.0)),
-                0xB6 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I6, Register::HL)), Cycles(4.0)),
-                0xB7 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I6, Register::A)), Cycles(2.0)),
-                0xB8 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I7, Register::B)), Cycles(2.0)),
-                0xB9 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I7, Register::C)), Cycles(2.0)),
-                0xBA => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I7, Register::D)), Cycles(2.0)),
-                0xBB => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I7, Register::E)), Cycles(2.0)),
-                0xBC => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I7, Register::H)), Cycles(2.0)),
-                0xBD => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I7, Register::L)), Cycles(2.0)),
-                0xBE => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I7, Register::HL)), Cycles(4.0)),
-                0xBF => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I7, Register::A)), Cycles(2.0)),
-
-                0xC0 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I0, Register::B)), Cycles(2.0)),
-                0xC1 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I0, Register::C)), Cycles(2.0)),
-                0xC2 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I0, Register::D)), Cycles(2.0)),
-                0xC3 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I0, Register::E)), Cycles(2.0)),
-                0xC4 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I0, Register::H)), Cycles(2.0)),
-                0xC5 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I0, Register::L)), Cycles(2.0)),
-                0xC6 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I0, Register::HL)), Cycles(4.0)),
-                0xC7 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I0, Register::A)), Cycles(2.0)),
-                0xC8 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I1, Register::B)), Cycles(2.0)),
-                0xC9 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I1, Register::C)), Cycles(2.0)),
-                0xCA => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I1, Register::D)), Cycles(2.0)),
-                0xCB => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I1, Register::E)), Cycles(2.0)),
-                0xCC => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I1, Register::H)), Cycles(2.0)),
-                0xCD => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I1, Register::L)), Cycles(2.0)),
-                0xCE => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I1, Register::HL)), Cycles(4.0)),
-                0xCF => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I1, Register::A)), Cycles(2.0)),
-                0xD0 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I2, Register::B)), Cycles(2.0)),
-                0xD1 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I2, Register::C)), Cycles(2.0)),
-                0xD2 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I2, Register::D)), Cycles(2.0)),
-                0xD3 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I2, Register::E)), Cycles(2.0)),
-                0xD4 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I2, Register::H)), Cycles(2.0)),
-                0xD5 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I2, Register::L)), Cycles(2.0)),
-                0xD6 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I2, Register::HL)), Cycles(4.0)),
-                0xD7 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I2, Register::A)), Cycles(2.0)),
-                0xD8 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I3, Register::B)), Cycles(2.0)),
-                0xD9 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I3, Register::C)), Cycles(2.0)),
-                0xDA => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I3, Register::D)), Cycles(2.0)),
-                0xDB => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I3, Register::E)), Cycles(2.0)),
-                0xDC => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I3, Register::H)), Cycles(2.0)),
-                0xDD => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I3, Register::L)), Cycles(2.0)),
-                0xDE => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I3, Register::HL)), Cycles(4.0)),
-                0xDF => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I3, Register::A)), Cycles(2.0)),
-                0xE0 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I4, Register::B)), Cycles(2.0)),
-                0xE1 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I4, Register::C)), Cycles(2.0)),
-                0xE2 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I4, Register::D)), Cycles(2.0)),
-                0xE3 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I4, Register::E)), Cycles(2.0)),
-                0xE4 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I4, Register::H)), Cycles(2.0)),
-                0xE5 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I4, Register::L)), Cycles(2.0)),
-                0xE6 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I4, Register::HL)), Cycles(4.0)),
-                0xE7 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I4, Register::A)), Cycles(2.0)),
-                0xE8 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I5, Register::B)), Cycles(2.0)),
-                0xE9 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I5, Register::C)), Cycles(2.0)),
-                0xEA => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I5, Register::D)), Cycles(2.0)),
-                0xEB => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I5, Register::E)), Cycles(2.0)),
-                0xEC => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I5, Register::H)), Cycles(2.0)),
-                0xED => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I5, Register::L)), Cycles(2.0)),
-                0xEE => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I5, Register::HL)), Cycles(4.0)),
-                0xEF => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I5, Register::A)), Cycles(2.0)),
-                0xF0 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I6, Register::B)), Cycles(2.0)),
-                0xF1 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I6, Register::C)), Cycles(2.0)),
-                0xF2 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I6, Register::D)), Cycles(2.0)),
-                0xF3 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I6, Register::E)), Cycles(2.0)),
-                0xF4 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I6, Register::H)), Cycles(2.0)),
-                0xF5 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I6, Register::L)), Cycles(2.0)),
-                0xF6 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I6, Register::HL)), Cycles(4.0)),
-                0xF7 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I6, Register::A)), Cycles(2.0)),
-                0xF8 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I7, Register::B)), Cycles(2.0)),
-                0xF9 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I7, Register::C)), Cycles(2.0)),
-                0xFA => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I7, Register::D)), Cycles(2.0)),
-                0xFB => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I7, Register::E)), Cycles(2.0)),
-                0xFC => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I7, Register::H)), Cycles(2.0)),
-                0xFD => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I7, Register::L)), Cycles(2.0)),
-                0xFE => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I7, Register::HL)), Cycles(4.0)),
-                0xFF => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I7, Register::A)), Cycles(2.0)),
+                0x00 => (Opcode::PrefixCB(CBOpcode::RLC(Register::B)), MCycles(2)),
+                0x01 => (Opcode::PrefixCB(CBOpcode::RLC(Register::C)), MCycles(2)),
+                0x02 => (Opcode::PrefixCB(CBOpcode::RLC(Register::D)), MCycles(2)),
+                0x03 => (Opcode::PrefixCB(CBOpcode::RLC(Register::E)), MCycles(2)),
+                0x04 => (Opcode::PrefixCB(CBOpcode::RLC(Register::H)), MCycles(2)),
+                0x05 => (Opcode::PrefixCB(CBOpcode::RLC(Register::L)), MCycles(2)),
+                0x06 => (Opcode::PrefixCB(CBOpcode::RLC(Register::HL)), MCycles(4)),
+                0x07 => (Opcode::PrefixCB(CBOpcode::RLC(Register::A)), MCycles(2)),
+
+                0x08 => (Opcode::PrefixCB(CBOpcode::RRC(Register::B)), MCycles(2)),
+                0x09 => (Opcode::PrefixCB(CBOpcode::RRC(Register::C)), MCycles(2)),
+                0x0A => (Opcode::PrefixCB(CBOpcode::RRC(Register::D)), MCycles(2)),
+                0x0B => (Opcode::PrefixCB(CBOpcode::RRC(Register::E)), MCycles(2)),
+                0x0C => (Opcode::PrefixCB(CBOpcode::RRC(Register::H)), MCycles(2)),
+                0x0D => (Opcode::PrefixCB(CBOpcode::RRC(Register::L)), MCycles(2)),
+                0x0E => (Opcode::PrefixCB(CBOpcode::RRC(Register::HL)), MCycles(4)),
+                0x0F => (Opcode::PrefixCB(CBOpcode::RRC(Register::A)), MCycles(2)),
+
+                0x10 => (Opcode::PrefixCB(CBOpcode::RL(Register::B)), MCycles(2)),
+                0x11 => (Opcode::PrefixCB(CBOpcode::RL(Register::C)), MCycles(2)),
+                0x12 => (Opcode::PrefixCB(CBOpcode::RL(Register::D)), MCycles(2)),
+                0x13 => (Opcode::PrefixCB(CBOpcode::RL(Register::E)), MCycles(2)),
+                0x14 => (Opcode::PrefixCB(CBOpcode::RL(Register::H)), MCycles(2)),
+                0x15 => (Opcode::PrefixCB(CBOpcode::RL(Register::L)), MCycles(2)),
+                0x16 => (Opcode::PrefixCB(CBOpcode::RL(Register::HL)), MCycles(4)),
+                0x17 => (Opcode::PrefixCB(CBOpcode::RL(Register::A)), MCycles(2)),
+
+                0x18 => (Opcode::PrefixCB(CBOpcode::RR(Register::B)), MCycles(2)),
+                0x19 => (Opcode::PrefixCB(CBOpcode::RR(Register::C)), MCycles(2)),
+                0x1A => (Opcode::PrefixCB(CBOpcode::RR(Register::D)), MCycles(2)),
+                0x1B => (Opcode::PrefixCB(CBOpcode::RR(Register::E)), MCycles(2)),
+                0x1C => (Opcode::PrefixCB(CBOpcode::RR(Register::H)), MCycles(2)),
+                0x1D => (Opcode::PrefixCB(CBOpcode::RR(Register::L)), MCycles(2)),
+                0x1E => (Opcode::PrefixCB(CBOpcode::RR(Register::HL)), MCycles(4)),
+                0x1F => (Opcode::PrefixCB(CBOpcode::RR(Register::A)), MCycles(2)),
+
+                0x20 => (Opcode::PrefixCB(CBOpcode::SLA(Register::B)), MCycles(2)),
+                0x21 => (Opcode::PrefixCB(CBOpcode::SLA(Register::C)), MCycles(2)),
+                0x22 => (Opcode::PrefixCB(CBOpcode::SLA(Register::D)), MCycles(2)),
+                0x23 => (Opcode::PrefixCB(CBOpcode::SLA(Register::E)), MCycles(2)),
+                0x24 => (Opcode::PrefixCB(CBOpcode::SLA(Register::H)), MCycles(2)),
+                0x25 => (Opcode::PrefixCB(CBOpcode::SLA(Register::L)), MCycles(2)),
+                0x26 => (Opcode::PrefixCB(CBOpcode::SLA(Register::HL)), MCycles(4)),
+                0x27 => (Opcode::PrefixCB(CBOpcode::SLA(Register::A)), MCycles(2)),
+
+                0x28 => (Opcode::PrefixCB(CBOpcode::SRA(Register::B)), MCycles(2)),
+                0x29 => (Opcode::PrefixCB(CBOpcode::SRA(Register::C)), MCycles(2)),
+                0x2A => (Opcode::PrefixCB(CBOpcode::SRA(Register::D)), MCycles(2)),
+                0x2B => (Opcode::PrefixCB(CBOpcode::SRA(Register::E)), MCycles(2)),
+                0x2C => (Opcode::PrefixCB(CBOpcode::SRA(Register::H)), MCycles(2)),
+                0x2D => (Opcode::PrefixCB(CBOpcode::SRA(Register::L)), MCycles(2)),
+                0x2E => (Opcode::PrefixCB(CBOpcode::SRA(Register::HL)), MCycles(4)),
+                0x2F => (Opcode::PrefixCB(CBOpcode::SRA(Register::A)), MCycles(2)),
+
+                0x30 => (Opcode::PrefixCB(CBOpcode::SWAP(Register::B)), MCycles(2)),
+                0x31 => (Opcode::PrefixCB(CBOpcode::SWAP(Register::C)), MCycles(2)),
+                0x32 => (Opcode::PrefixCB(CBOpcode::SWAP(Register::D)), MCycles(2)),
+                0x33 => (Opcode::PrefixCB(CBOpcode::SWAP(Register::E)), MCycles(2)),
+                0x34 => (Opcode::PrefixCB(CBOpcode::SWAP(Register::H)), MCycles(2)),
+                0x35 => (Opcode::PrefixCB(CBOpcode::SWAP(Register::L)), MCycles(2)),
+                0x36 => (Opcode::PrefixCB(CBOpcode::SWAP(Register::HL)), MCycles(4)),
+                0x37 => (Opcode::PrefixCB(CBOpcode::SWAP(Register::A)), MCycles(2)),
+
+                0x38 => (Opcode::PrefixCB(CBOpcode::SRL(Register::B)), MCycles(2)),
+                0x39 => (Opcode::PrefixCB(CBOpcode::SRL(Register::C)), MCycles(2)),
+                0x3A => (Opcode::PrefixCB(CBOpcode::SRL(Register::D)), MCycles(2)),
+                0x3B => (Opcode::PrefixCB(CBOpcode::SRL(Register::E)), MCycles(2)),
+                0x3C => (Opcode::PrefixCB(CBOpcode::SRL(Register::H)), MCycles(2)),
+                0x3D => (Opcode::PrefixCB(CBOpcode::SRL(Register::L)), MCycles(2)),
+                0x3E => (Opcode::PrefixCB(CBOpcode::SRL(Register::HL)), MCycles(4)),
+                0x3F => (Opcode::PrefixCB(CBOpcode::SRL(Register::A)), MCycles(2)),
+
+                0x40 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I0, Register::B)), MCycles(2)),
+                0x41 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I0, Register::C)), MCycles(2)),
+                0x42 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I0, Register::D)), MCycles(2)),
+                0x43 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I0, Register::E)), MCycles(2)),
+                0x44 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I0, Register::H)), MCycles(2)),
+                0x45 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I0, Register::L)), MCycles(2)),
+                0x46 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I0, Register::HL)), MCycles(3)),
+                0x47 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I0, Register::A)), MCycles(2)),
+                0x48 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I1, Register::B)), MCycles(2)),
+                0x49 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I1, Register::C)), MCycles(2)),
+                0x4A => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I1, Register::D)), MCycles(2)),
+                0x4B => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I1, Register::E)), MCycles(2)),
+                0x4C => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I1, Register::H)), MCycles(2)),
+                0x4D => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I1, Register::L)), MCycles(2)),
+                0x4E => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I1, Register::HL)), MCycles(3)),
+                0x4F => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I1, Register::A)), MCycles(2)),
+                0x50 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I2, Register::B)), MCycles(2)),
+                0x51 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I2, Register::C)), MCycles(2)),
+                0x52 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I2, Register::D)), MCycles(2)),
+                0x53 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I2, Register::E)), MCycles(2)),
+                0x54 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I2, Register::H)), MCycles(2)),
+                0x55 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I2, Register::L)), MCycles(2)),
+                0x56 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I2, Register::HL)), MCycles(3)),
+                0x57 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I2, Register::A)), MCycles(2)),
+                0x58 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I3, Register::B)), MCycles(2)),
+                0x59 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I3, Register::C)), MCycles(2)),
+                0x5A => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I3, Register::D)), MCycles(2)),
+                0x5B => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I3, Register::E)), MCycles(2)),
+                0x5C => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I3, Register::H)), MCycles(2)),
+                0x5D => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I3, Register::L)), MCycles(2)),
+                0x5E => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I3, Register::HL)), MCycles(3)),
+                0x5F => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I3, Register::A)), MCycles(2)),
+                0x60 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I4, Register::B)), MCycles(2)),
+                0x61 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I4, Register::C)), MCycles(2)),
+                0x62 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I4, Register::D)), MCycles(2)),
+                0x63 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I4, Register::E)), MCycles(2)),
+                0x64 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I4, Register::H)), MCycles(2)),
+                0x65 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I4, Register::L)), MCycles(2)),
+                0x66 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I4, Register::HL)), MCycles(3)),
+                0x67 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I4, Register::A)), MCycles(2)),
+                0x68 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I5, Register::B)), MCycles(2)),
+                0x69 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I5, Register::C)), MCycles(2)),
+                0x6A => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I5, Register::D)), MCycles(2)),
+                0x6B => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I5, Register::E)), MCycles(2)),
+                0x6C => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I5, Register::H)), MCycles(2)),
+                0x6D => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I5, Register::L)), MCycles(2)),
+                0x6E => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I5, Register::HL)), MCycles(3)),
+                0x6F => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I5, Register::A)), MCycles(2)),
+                0x70 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I6, Register::B)), MCycles(2)),
+                0x71 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I6, Register::C)), MCycles(2)),
+                0x72 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I6, Register::D)), MCycles(2)),
+                0x73 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I6, Register::E)), MCycles(2)),
+                0x74 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I6, Register::H)), MCycles(2)),
+                0x75 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I6, Register::L)), MCycles(2)),
+                0x76 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I6, Register::HL)), MCycles(3)),
+                0x77 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I6, Register::A)), MCycles(2)),
+                0x78 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I7, Register::B)), MCycles(2)),
+                0x79 => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I7, Register::C)), MCycles(2)),
+                0x7A => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I7, Register::D)), MCycles(2)),
+                0x7B => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I7, Register::E)), MCycles(2)),
+                0x7C => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I7, Register::H)), MCycles(2)),
+                0x7D => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I7, Register::L)), MCycles(2)),
+                0x7E => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I7, Register::HL)), MCycles(3)),
+                0x7F => (Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I7, Register::A)), MCycles(2)),
+
+                0x80 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I0, Register::B)), MCycles(2)),
+                0x81 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I0, Register::C)), MCycles(2)),
+                0x82 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I0, Register::D)), MCycles(2)),
+                0x83 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I0, Register::E)), MCycles(2)),
+                0x84 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I0, Register::H)), MCycles(2)),
+                0x85 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I0, Register::L)), MCycles(2)),
+                0x86 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I0, Register::HL)), MCycles(4)),
+                0x87 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I0, Register::A)), MCycles(2)),
+                0x88 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I1, Register::B)), MCycles(2)),
+                0x89 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I1, Register::C)), MCycles(2)),
+                0x8A => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I1, Register::D)), MCycles(2)),
+                0x8B => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I1, Register::E)), MCycles(2)),
+                0x8C => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I1, Register::H)), MCycles(2)),
+                0x8D => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I1, Register::L)), MCycles(2)),
+                0x8E => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I1, Register::HL)), MCycles(4)),
+                0x8F => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I1, Register::A)), MCycles(2)),
+                0x90 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I2, Register::B)), MCycles(2)),
+                0x91 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I2, Register::C)), MCycles(2)),
+                0x92 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I2, Register::D)), MCycles(2)),
+                0x93 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I2, Register::E)), MCycles(2)),
+                0x94 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I2, Register::H)), MCycles(2)),
+                0x95 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I2, Register::L)), MCycles(2)),
+                0x96 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I2, Register::HL)), MCycles(4)),
+                0x97 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I2, Register::A)), MCycles(2)),
+                0x98 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I3, Register::B)), MCycles(2)),
+                0x99 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I3, Register::C)), MCycles(2)),
+                0x9A => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I3, Register::D)), MCycles(2)),
+                0x9B => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I3, Register::E)), MCycles(2)),
+                0x9C => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I3, Register::H)), MCycles(2)),
+                0x9D => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I3, Register::L)), MCycles(2)),
+                0x9E => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I3, Register::HL)), MCycles(4)),
+                0x9F => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I3, Register::A)), MCycles(2)),
+                0xA0 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I4, Register::B)), MCycles(2)),
+                0xA1 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I4, Register::C)), MCycles(2)),
+                0xA2 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I4, Register::D)), MCycles(2)),
+                0xA3 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I4, Register::E)), MCycles(2)),
+                0xA4 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I4, Register::H)), MCycles(2)),
+                0xA5 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I4, Register::L)), MCycles(2)),
+                0xA6 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I4, Register::HL)), MCycles(4)),
+                0xA7 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I4, Register::A)), MCycles(2)),
+                0xA8 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I5, Register::B)), MCycles(2)),
+                0xA9 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I5, Register::C)), MCycles(2)),
+                0xAA => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I5, Register::D)), MCycles(2)),
+                0xAB => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I5, Register::E)), MCycles(2)),
+                0xAC => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I5, Register::H)), MCycles(2)),
+                0xAD => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I5, Register::L)), MCycles(2)),
+                0xAE => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I5, Register::HL)), MCycles(4)),
+                0xAF => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I5, Register::A)), MCycles(2)),
+                0xB0 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I6, Register::B)), MCycles(2)),
+                0xB1 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I6, Register::C)), MCycles(2)),
+                0xB2 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I6, Register::D)), MCycles(2)),
+                0xB3 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I6, Register::E)), MCycles(2)),
+                0xB4 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I6, Register::H)), MCycles(2)),
+                0xB5 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I6, Register::L)), MCycles(2)),
+                0xB6 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I6, Register::HL)), MCycles(4)),
+                0xB7 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I6, Register::A)), MCycles(2)),
+                0xB8 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I7, Register::B)), MCycles(2)),
+                0xB9 => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I7, Register::C)), MCycles(2)),
+                0xBA => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I7, Register::D)), MCycles(2)),
+                0xBB => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I7, Register::E)), MCycles(2)),
+                0xBC => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I7, Register::H)), MCycles(2)),
+                0xBD => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I7, Register::L)), MCycles(2)),
+                0xBE => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I7, Register::HL)), MCycles(4)),
+                0xBF => (Opcode::PrefixCB(CBOpcode::RES(BitIndex::I7, Register::A)), MCycles(2)),
+
+                0xC0 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I0, Register::B)), MCycles(2)),
+                0xC1 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I0, Register::C)), MCycles(2)),
+                0xC2 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I0, Register::D)), MCycles(2)),
+                0xC3 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I0, Register::E)), MCycles(2)),
+                0xC4 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I0, Register::H)), MCycles(2)),
+                0xC5 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I0, Register::L)), MCycles(2)),
+                0xC6 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I0, Register::HL)), MCycles(4)),
+                0xC7 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I0, Register::A)), MCycles(2)),
+                0xC8 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I1, Register::B)), MCycles(2)),
+                0xC9 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I1, Register::C)), MCycles(2)),
+                0xCA => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I1, Register::D)), MCycles(2)),
+                0xCB => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I1, Register::E)), MCycles(2)),
+                0xCC => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I1, Register::H)), MCycles(2)),
+                0xCD => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I1, Register::L)), MCycles(2)),
+                0xCE => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I1, Register::HL)), MCycles(4)),
+                0xCF => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I1, Register::A)), MCycles(2)),
+                0xD0 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I2, Register::B)), MCycles(2)),
+                0xD1 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I2, Register::C)), MCycles(2)),
+                0xD2 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I2, Register::D)), MCycles(2)),
+                0xD3 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I2, Register::E)), MCycles(2)),
+                0xD4 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I2, Register::H)), MCycles(2)),
+                0xD5 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I2, Register::L)), MCycles(2)),
+                0xD6 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I2, Register::HL)), MCycles(4)),
+                0xD7 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I2, Register::A)), MCycles(2)),
+                0xD8 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I3, Register::B)), MCycles(2)),
+                0xD9 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I3, Register::C)), MCycles(2)),
+                0xDA => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I3, Register::D)), MCycles(2)),
+                0xDB => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I3, Register::E)), MCycles(2)),
+                0xDC => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I3, Register::H)), MCycles(2)),
+                0xDD => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I3, Register::L)), MCycles(2)),
+                0xDE => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I3, Register::HL)), MCycles(4)),
+                0xDF => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I3, Register::A)), MCycles(2)),
+                0xE0 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I4, Register::B)), MCycles(2)),
+                0xE1 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I4, Register::C)), MCycles(2)),
+                0xE2 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I4, Register::D)), MCycles(2)),
+                0xE3 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I4, Register::E)), MCycles(2)),
+                0xE4 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I4, Register::H)), MCycles(2)),
+                0xE5 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I4, Register::L)), MCycles(2)),
+                0xE6 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I4, Register::HL)), MCycles(4)),
+                0xE7 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I4, Register::A)), MCycles(2)),
+                0xE8 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I5, Register::B)), MCycles(2)),
+                0xE9 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I5, Register::C)), MCycles(2)),
+                0xEA => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I5, Register::D)), MCycles(2)),
+                0xEB => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I5, Register::E)), MCycles(2)),
+                0xEC => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I5, Register::H)), MCycles(2)),
+                0xED => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I5, Register::L)), MCycles(2)),
+                0xEE => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I5, Register::HL)), MCycles(4)),
+                0xEF => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I5, Register::A)), MCycles(2)),
+                0xF0 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I6, Register::B)), MCycles(2)),
+                0xF1 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I6, Register::C)), MCycles(2)),
+                0xF2 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I6, Register::D)), MCycles(2)),
+                0xF3 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I6, Register::E)), MCycles(2)),
+                0xF4 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I6, Register::H)), MCycles(2)),
+                0xF5 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I6, Register::L)), MCycles(2)),
+                0xF6 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I6, Register::HL)), MCycles(4)),
+                0xF7 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I6, Register::A)), MCycles(2)),
+                0xF8 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I7, Register::B)), MCycles(2)),
+                0xF9 => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I7, Register::C)), MCycles(2)),
+                0xFA => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I7, Register::D)), MCycles(2)),
+                0xFB => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I7, Register::E)), MCycles(2)),
+                0xFC => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I7, Register::H)), MCycles(2)),
+                0xFD => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I7, Register::L)), MCycles(2)),
+                0xFE => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I7, Register::HL)), MCycles(4)),
+                0xFF => (Opcode::PrefixCB(CBOpcode::SET(BitIndex::I7, Register::A)), MCycles(2)),
+            },
+            0xC3 => (Opcode::JP(OpcodeParameter::U16(two_byte_param)), MCycles(4)),
+            0xC2 => (Opcode::JP(OpcodeParameter::FlagRegisterReset_U16(FlagRegister::Zero, two_byte_param)), MCycles(3)),
+            0xCA => (Opcode::JP(OpcodeParameter::FlagRegisterSet_U16(FlagRegister::Zero, two_byte_param)), MCycles(3)),
+            0xD2 => (Opcode::JP(OpcodeParameter::FlagRegisterReset_U16(FlagRegister::Carry, two_byte_param)), MCycles(3)),
+            0xDA => (Opcode::JP(OpcodeParameter::FlagRegisterSet_U16(FlagRegister::Carry, two_byte_param)), MCycles(3)),
+            0xE9 => (Opcode::JP(OpcodeParameter::Register(Register::HL)), MCycles(1)),
+            0x18 => (Opcode::JR(OpcodeParameter::I8(self.1 as i8)), MCycles(2)),
+            0x20 => (Opcode::JR(OpcodeParameter::FlagRegisterReset_I8(FlagRegister::Zero, self.1 as i8)), MCycles(2)),
+            0x28 => (Opcode::JR(OpcodeParameter::FlagRegisterSet_I8(FlagRegister::Zero, self.1 as i8)), MCycles(2)),
+            0x30 => (Opcode::JR(OpcodeParameter::FlagRegisterReset_I8(FlagRegister::Carry, self.1 as i8)), MCycles(2)),
+            0x38 => (Opcode::JR(OpcodeParameter::FlagRegisterSet_I8(FlagRegister::Carry, self.1 as i8)), MCycles(2)),
+            0xCD => (Opcode::CALL(OpcodeParameter::U16(two_byte_param)), MCycles(6)),
+            0xC4 => (Opcode::CALL(OpcodeParameter::FlagRegisterReset_U16(FlagRegister::Zero, two_byte_param)), MCycles(3)),
+            0xCC => (Opcode::CALL(OpcodeParameter::FlagRegisterSet_U16(FlagRegister::Zero, two_byte_param)), MCycles(3)),
+            0xD4 => (Opcode::CALL(OpcodeParameter::FlagRegisterReset_U16(FlagRegister::Carry, two_byte_param)), MCycles(3)),
+            0xDC => (Opcode::CALL(OpcodeParameter::FlagRegisterSet_U16(FlagRegister::Carry, two_byte_param)), MCycles(3)),
+            0xC7 => (Opcode::RST(0x00), MCycles(4)),
+            0xCF => (Opcode::RST(0x08), MCycles(4)),
+            0xD7 => (Opcode::RST(0x10), MCycles(4)),
+            0xDF => (Opcode::RST(0x18), MCycles(4)),
+            0xE7 => (Opcode::RST(0x20), MCycles(4)),
+            0xEF => (Opcode::RST(0x28), MCycles(4)),
+            0xF7 => (Opcode::RST(0x30), MCycles(4)),
+            0xFF => (Opcode::RST(0x38), MCycles(4)),
+            0xC9 => (Opcode::RET(OpcodeParameter::NoParam), MCycles(4)),
+            0xC0 => (Opcode::RET(OpcodeParameter::FlagRegisterReset(FlagRegister::Zero)), MCycles(2)),
+            0xC8 => (Opcode::RET(OpcodeParameter::FlagRegisterSet(FlagRegister::Zero)), MCycles(2)),
+            0xD0 => (Opcode::RET(OpcodeParameter::FlagRegisterReset(FlagRegister::Carry)), MCycles(2)),
+            0xD8 => (Opcode::RET(OpcodeParameter::FlagRegisterSet(FlagRegister::Carry)), MCycles(2)),
+            0xD9 => (Opcode::RETI, MCycles(4)),
+            0xF3 => (Opcode::DI, MCycles(1)),
+            0xFB => (Opcode::EI, MCycles(1)),
+            0x76 => (Opcode::HALT, MCycles(1)),
+            0x10 => (Opcode::STOP, MCycles(1)),
+            0x00 => (Opcode::NOP, MCycles(1)),
+            _ => (Opcode::IllegalInstruction, MCycles(1)),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
+struct DecodedOpcode {
+    opcode: Opcode,
+    cycles: MCycles,
+    immediate_length: u8,
+}
+
+// Decoded instructions indexed by opcode byte. Entries are built once from `parse_opcode`
+// with empty immediates, which get filled in when the instruction is fetched
+pub struct OpcodeTable {
+    opcodes: [DecodedOpcode; 256],
+    cb_opcodes: [DecodedOpcode; 256],
+}
+
+impl OpcodeTable {
+    fn new() -> Self {
+        let decode = |bytes: OpcodeParameterBytes| {
+            let (opcode, cycles) = bytes.parse_opcode();
+            DecodedOpcode {
+                opcode,
+                cycles,
+                immediate_length: opcode.immediate_length(),
+            }
+        };
+        Self {
+            opcodes: std::array::from_fn(|opcode| decode(OpcodeParameterBytes(opcode as u8, 0, 0, 0))),
+            cb_opcodes: std::array::from_fn(|opcode| decode(OpcodeParameterBytes(0xCB, opcode as u8, 0, 0))),
+        }
+    }
+
+    pub fn get() -> &'static OpcodeTable {
+        static TABLE: OnceLock<OpcodeTable> = OnceLock::new();
+        TABLE.get_or_init(OpcodeTable::new)
+    }
+
+    // Reads the instruction at `address`, fetching only the bytes it actually uses
+    pub fn decode(&self, address: u16, bus: &mut Bus) -> (Opcode, MCycles) {
+        let opcode = bus.read(address);
+        if opcode == 0xCB {
+            let decoded = &self.cb_opcodes[bus.read(address.wrapping_add(1)) as usize];
+            return (decoded.opcode, decoded.cycles);
+        }
+        let decoded = &self.opcodes[opcode as usize];
+        match decoded.immediate_length {
+            0 => (decoded.opcode, decoded.cycles),
+            1 => {
+                let value = bus.read(address.wrapping_add(1)) as u16;
+                (decoded.opcode.with_immediate(value), decoded.cycles)
+            },
+            _ => {
+                let value = join_bytes(bus.read(address.wrapping_add(2)), bus.read(address.wrapping_add(1)));
+                (decoded.opcode.with_immediate(value), decoded.cycles)
             },
-            0xC3 => (Opcode::JP(OpcodeParameter::U16(two_byte_param)), Cycles(4.0)),
-            0xC2 => (Opcode::JP(OpcodeParameter::FlagRegisterReset_U16(FlagRegister::Zero, two_byte_param)), Cycles(3.0)),
-            0xCA => (Opcode::JP(OpcodeParameter::FlagRegisterSet_U16(FlagRegister::Zero, two_byte_param)), Cycles(3.0)),
-            0xD2 => (Opcode::JP(OpcodeParameter::FlagRegisterReset_U16(FlagRegister::Carry, two_byte_param)), Cycles(3.0)),
-            0xDA => (Opcode::JP(OpcodeParameter::FlagRegisterSet_U16(FlagRegister::Carry, two_byte_param)), Cycles(3.0)),
-            0xE9 => (Opcode::JP(OpcodeParameter::Register(Register::HL)), Cycles(1.0)),
-            0x18 => (Opcode::JR(OpcodeParameter::I8(self.1 as i8)), Cycles(2.0)),
-            0x20 => (Opcode::JR(OpcodeParameter::FlagRegisterReset_I8(FlagRegister::Zero, self.1 as i8)), Cycles(2.0)),
-            0x28 => (Opcode::JR(OpcodeParameter::FlagRegisterSet_I8(FlagRegister::Zero, self.1 as i8)), Cycles(2.0)),
-            0x30 => (Opcode::JR(OpcodeParameter::FlagRegisterReset_I8(FlagRegister::Carry, self.1 as i8)), Cycles(2.0)),
-            0x38 => (Opcode::JR(OpcodeParameter::FlagRegisterSet_I8(FlagRegister::Carry, self.1 as i8)), Cycles(2.0)),
-            0xCD => (Opcode::CALL(OpcodeParameter::U16(two_byte_param)), Cycles(6.0)),
-            0xC4 => (Opcode::CALL(OpcodeParameter::FlagRegisterReset_U16(FlagRegister::Zero, two_byte_param)), Cycles(3.0)),
-            0xCC => (Opcode::CALL(OpcodeParameter::FlagRegisterSet_U16(FlagRegister::Zero, two_byte_param)), Cycles(3.0)),
-            0xD4 => (Opcode::CALL(OpcodeParameter::FlagRegisterReset_U16(FlagRegister::Carry, two_byte_param)), Cycles(3.0)),
-            0xDC => (Opcode::CALL(OpcodeParameter::FlagRegisterSet_U16(FlagRegister::Carry, two_byte_param)), Cycles(3.0)),
-            0xC7 => (Opcode::RST(0x00), Cycles(4.0)),
-            0xCF => (Opcode::RST(0x08), Cycles(4.0)),
-            0xD7 => (Opcode::RST(0x10), Cycles(4.0)),
-            0xDF => (Opcode::RST(0x18), Cycles(4.0)),
-            0xE7 => (Opcode::RST(0x20), Cycles(4.0)),
-            0xEF => (Opcode::RST(0x28), Cycles(4.0)),
-            0xF7 => (Opcode::RST(0x30), Cycles(4.0)),
-            0xFF => (Opcode::RST(0x38), Cycles(4.0)),
-            0xC9 => (Opcode::RET(OpcodeParameter::NoParam), Cycles(4.0)),
-            0xC0 => (Opcode::RET(OpcodeParameter::FlagRegisterReset(FlagRegister::Zero)), Cycles(2.0)),
-            0xC8 => (Opcode::RET(OpcodeParameter::FlagRegisterSet(FlagRegister::Zero)), Cycles(2.0)),
-            0xD0 => (Opcode::RET(OpcodeParameter::FlagRegisterReset(FlagRegister::Carry)), Cycles(2.0)),
-            0xD8 => (Opcode::RET(OpcodeParameter::FlagRegisterSet(FlagRegister::Carry)), Cycles(2.0)),
-            0xD9 => (Opcode::RETI, Cycles(4.0)),
-            0xF3 => (Opcode::DI, Cycles(1.0)),
-            0xFB => (Opcode::EI, Cycles(1.0)),
-            0x76 => (Opcode::HALT, Cycles(1.0)),
-            0x10 => (Opcode::STOP, Cycles(1.0)),
-            0x00 => (Opcode::NOP, Cycles(1.0)),
-            _ => (Opcode::IllegalInstruction, Cycles(1.0)),
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone)]
 pub enum Opcode {
     LD(OpcodeParameter),
     LDD(OpcodeParameter),
@@ -799,7 +952,53 @@ pub enum Opcode {
     IllegalInstruction,
 }
 
-#[derive(Debug)]
+impl Opcode {
+    pub fn immediate_length(&self) -> u8 {
+        match self {
+            Opcode::LD(params) |
+            Opcode::LDD(params) |
+            Opcode::LDI(params) |
+            Opcode::LDHL(params) |
+            Opcode::ADD(params) |
+            Opcode::ADC(params) |
+            Opcode::SUB(params) |
+            Opcode::SBC(params) |
+            Opcode::AND(params) |
+            Opcode::OR(params) |
+            Opcode::XOR(params) |
+            Opcode::CP(params) |
+            Opcode::JP(params) |
+            Opcode::JR(params) |
+            Opcode::CALL(params) |
+            Opcode::RET(params) => params.immediate_length(),
+            _ => 0,
+        }
+    }
+
+    pub fn with_immediate(self, value: u16) -> Self {
+        match self {
+            Opcode::LD(params) => Opcode::LD(params.with_immediate(value)),
+            Opcode::LDD(params) => Opcode::LDD(params.with_immediate(value)),
+            Opcode::LDI(params) => Opcode::LDI(params.with_immediate(value)),
+            Opcode::LDHL(params) => Opcode::LDHL(params.with_immediate(value)),
+            Opcode::ADD(params) => Opcode::ADD(params.with_immediate(value)),
+            Opcode::ADC(params) => Opcode::ADC(params.with_immediate(value)),
+            Opcode::SUB(params) => Opcode::SUB(params.with_immediate(value)),
+            Opcode::SBC(params) => Opcode::SBC(params.with_immediate(value)),
+            Opcode::AND(params) => Opcode::AND(params.with_immediate(value)),
+            Opcode::OR(params) => Opcode::OR(params.with_immediate(value)),
+            Opcode::XOR(params) => Opcode::XOR(params.with_immediate(value)),
+            Opcode::CP(params) => Opcode::CP(params.with_immediate(value)),
+            Opcode::JP(params) => Opcode::JP(params.with_immediate(value)),
+            Opcode::JR(params) => Opcode::JR(params.with_immediate(value)),
+            Opcode::CALL(params) => Opcode::CALL(params.with_immediate(value)),
+            Opcode::RET(params) => Opcode::RET(params.with_immediate(value)),
+            opcode => opcode,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone)]
 pub enum CBOpcode {
     SWAP(Register),
     RLC(Register),
@@ -814,20 +1013,28 @@ pub enum CBOpcode {
     RES(BitIndex, Register),
 }
 
-// Store cycles in M
-#[derive(Debug, Copy, Clone)]
-pub struct Cycles(pub f32); 
+// Machine cycles, the unit used by the opcode timings
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct MCycles(pub u32);
 
-impl Cycles {
-    pub fn to_t(&self) -> Self {
-        Self(self.0 * 4.0)
+impl MCycles {
+    // Duration in T-cycles of the normal speed clock
+    pub fn to_t(&self, double_speed: bool) -> TCycles {
+        match double_speed {
+            true => TCycles(self.0 * 2),
+            false => TCycles(self.0 * 4),
+        }
     }
 }
 
+// Clock cycles of the 4.19 MHz main clock. The PPU dots and the master clock are counted in T-cycles
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub struct TCycles(pub u32);
+
 pub struct CPU {
     registers: Registers,
-    cycles: Cycles,
-    last_op_cycles: Cycles,
+    cycles: TCycles,
+    last_op_cycles: TCycles,
     exec_calls_count: usize,
     is_halted: bool,
     ime: bool, // Interrupt Master Enable
@@ -835,39 +1042,46 @@ pub struct CPU {
     enable_logs: bool,
     is_cgb: bool,
     double_speed_mode: bool,
+    opcode_table: &'static OpcodeTable,
 }
 
 impl CPU {
     pub fn new() -> Self {
         Self {
             registers: Registers::new(),
-            cycles: Cycles(0.0),
-            last_op_cycles: Cycles(0.0),
+            cycles: TCycles(0),
+            last_op_cycles: TCycles(0),
             exec_calls_count: 0,
             is_halted: false,
             ei_delay: false,
             ime: true,
-            enable_logs: !env::var("CPU_LOG").is_err() || !env::var("CPU_LOGS").is_err(),
+            enable_logs: false,
             is_cgb: false,
             double_speed_mode: false,
+            opcode_table: OpcodeTable::get(),
         }
     }
 
     pub fn new_cgb() -> Self {
         Self {
             registers: Registers::new_cgb(),
-            cycles: Cycles(0.0),
-            last_op_cycles: Cycles(0.0),
+            cycles: TCycles(0),
+            last_op_cycles: TCycles(0),
             exec_calls_count: 0,
             is_halted: false,
             ei_delay: false,
             ime: true,
-            enable_logs: !env::var("CPU_LOG").is_err() || !env::var("CPU_LOGS").is_err(),
+            enable_logs: false,
             is_cgb: true,
             double_speed_mode: false,
+            opcode_table: OpcodeTable::get(),
         }
     }
 
+    pub fn set_enable_logs(&mut self, enable_logs: bool) {
+        self.enable_logs = enable_logs;
+    }
+
     pub fn get_exec_calls_count(&self) -> usize {
         self.exec_calls_count
     }
@@ -876,30 +1090,39 @@ impl CPU {
         self.exec_calls_count += 1;
     }
 
-    fn increment_cycles(&mut self, cycles: Cycles) {
-        if self.is_cgb && self.double_speed_mode {
-            self.cycles.0 += cycles.0 / 2.0;
-        } else {
-            self.cycles.0 += cycles.0;
-        }
+    fn increment_cycles(&mut self, cycles: MCycles) {
+        self.cycles.0 += cycles.to_t(self.is_cgb && self.double_speed_mode).0;
     }
 
     pub fn reset_cycles(&mut self) {
-        self.cycles = Cycles(0.0);
+        self.cycles = TCycles(0);
     }
 
-    pub fn get_cycles(&mut self) -> Cycles {
+    pub fn get_cycles(&mut self) -> TCycles {
         self.cycles
     }
 
-    pub fn set_last_op_cycles(&mut self, cycles_start: Cycles, cycles_end: Cycles) {
-        self.last_op_cycles = Cycles(cycles_end.0 - cycles_start.0);
+    pub fn set_last_op_cycles(&mut self, cycles_start: TCycles, cycles_end: TCycles) {
+        self.last_op_cycles = TCycles(cycles_end.0 - cycles_start.0);
     }
 
-    pub fn get_last_op_cycles(&self) -> Cycles {
+    pub fn get_last_op_cycles(&self) -> TCycles {
         self.last_op_cycles
     }
 
+    pub fn is_halted(&self) -> bool {
+        self.is_halted
+    }
+
+    // Stays halted for at least `cycles`, rounded up to whole M-cycles. Returns the cycles that elapsed
+    pub fn idle(&mut self, cycles: TCycles) -> TCycles {
+        let m_cycle = MCycles(1).to_t(self.is_cgb && self.double_speed_mode).0;
+        let elapsed = TCycles(cycles.0.div_ceil(m_cycle) * m_cycle);
+        self.cycles.0 += elapsed.0;
+        self.last_op_cycles.0 += elapsed.0;
+        elapsed
+    }
+
     fn log(&self, parameter_bytes: OpcodeParameterBytes) {
         println!("A: {:02X} F: {:02X} B: {:02X} C: {:02X} D: {:02X} E: {:02X} H: {:02X} L: {:02X} SP: {:04X} PC: 00:{:04X} ({:02X} {:02X} {:02X} {:02X})",
             self.registers.get(Register::A),
@@ -961,20 +1184,19 @@ impl CPU {
         let cycles_start = self.get_cycles();
         if let Some(interrupt) = self.check_interrupts(bus) {
             self.handle_interrupt(bus, interrupt);
-            self.increment_cycles(Cycles(5.0));
+            self.increment_cycles(MCycles(5));
         } else if !self.is_halted {
             let program_counter = self.registers.get(Register::PC);
-            let parameter_bytes = OpcodeParameterBytes::from_address(program_counter, bus);
-            let (opcode, cycles) = parameter_bytes.parse_opcode();
             if self.enable_logs {
-                self.log(parameter_bytes);
+                self.log(OpcodeParameterBytes::from_address(program_counter, bus));
                 self.increment_exec_calls_count();
             }
+            let (opcode, cycles) = self.opcode_table.decode(program_counter, bus);
             self.increment_cycles(cycles);
             self.exec(opcode, bus);
             self.ei_delay(bus);
         } else if self.is_halted {
-            self.increment_cycles(Cycles(1.0));
+            self.increment_cycles(MCycles(1));
         }
         let cycles_end = self.get_cycles();
         self.set_last_op_cycles(cycles_start, cycles_end);
@@ -1063,7 +1285,7 @@ impl CPU {
                     _ => unreachable!(),
                 };
                 if condition_met {
-                    self.increment_cycles(Cycles(1.0));
+                    self.increment_cycles(MCycles(1));
                     let pc = (self.registers.get(Register::PC) as i16) + (value as i16);
                     self.registers.set(Register::PC, pc as u16);
                 }
@@ -1449,14 +1671,14 @@ impl CPU {
                     self.registers.increment(Register::PC, 3);
                     if !self.registers.get_flag(flag) {
                         self.registers.set(Register::PC, addr);
-                        self.increment_cycles(Cycles(1.0));
+                        self.increment_cycles(MCycles(1));
                     }
                 },
                 OpcodeParameter::FlagRegisterSet_U16(flag, addr) => {
                     self.registers.increment(Register::PC, 3);
                     if self.registers.get_flag(flag) {
                         self.registers.set(Register::PC, addr);
-                        self.increment_cycles(Cycles(1.0));
+                        self.increment_cycles(MCycles(1));
                     }
                 },
                 _ => unreachable!(),
@@ -1470,12 +1692,12 @@ impl CPU {
                     },
                     OpcodeParameter::FlagRegisterReset_U16(flag, address) => {
                         let condition_met = !self.registers.get_flag(flag);
-                        if condition_met {self.increment_cycles(Cycles(3.0))};
+                        if condition_met {self.increment_cycles(MCycles(3))};
                         (condition_met, address)
                     },
                     OpcodeParameter::FlagRegisterSet_U16(flag, address) => {
                         let condition_met = self.registers.get_flag(flag);
-                        if condition_met {self.increment_cycles(Cycles(3.0))};
+                        if condition_met {self.increment_cycles(MCycles(3))};
                         (condition_met, address)
                     },
                     _ => unreachable!(),
@@ -1521,13 +1743,13 @@ impl CPU {
                     OpcodeParameter::FlagRegisterReset(flag) => {
                         if !self.registers.get_flag(flag) {
                             self.exec(Opcode::POP(Register::PC), bus);
-                            self.increment_cycles(Cycles(3.0));
+                            self.increment_cycles(MCycles(3));
                         }
                     },
                     OpcodeParameter::FlagRegisterSet(flag) => {
                         if self.registers.get_flag(flag) {
                             self.exec(Opcode::POP(Register::PC), bus);
-                            self.increment_cycles(Cycles(3.0));
+                            self.increment_cycles(MCycles(3));
                         }
                     },
                     _ => unreachable!(),
@@ -1814,9 +2036,34 @@ impl CPU {
     }
 }
 
+impl SaveState for CPU {
+    fn save_state(&self, state: &mut StateWriter) {
+        self.registers.save_state(state);
+        state.write_u32(self.cycles.0);
+        state.write_u32(self.last_op_cycles.0);
+        state.write_bool(self.is_halted);
+        state.write_bool(self.ime);
+        state.write_bool(self.ei_delay);
+        state.write_bool(self.double_speed_mode);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.registers.load_state(state)?;
+        self.cycles = TCycles(state.read_u32()?);
+        self.last_op_cycles = TCycles(state.read_u32()?);
+        self.is_halted = state.read_bool()?;
+        self.ime = state.read_bool()?;
+        self.ei_delay = state.read_bool()?;
+        self.double_speed_mode = state.read_bool()?;
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::rom::empty_rom;
+    use crate::config::EmulatorConfig;
 
     #[test]
     fn test_registers_setters_getters() {
@@ -1872,9 +2119,35 @@ mod tests {
         assert_eq!(registers.get(Register::PC), 0b0101010111111111);
     }
 
+    #[test]
+    fn test_opcode_table_matches_parser() {
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
+        let table = OpcodeTable::get();
+        for opcode in (0..=0xFF).filter(|opcode| *opcode != 0xCB) {
+            for (byte1, byte2) in [(0x00, 0x00), (0x12, 0x34), (0xFE, 0xFF)] {
+                let bytes = OpcodeParameterBytes(opcode, byte1, byte2, 0x00);
+                bus.write(0xC000, bytes.0);
+                bus.write(0xC001, bytes.1);
+                bus.write(0xC002, bytes.2);
+                let (expected, expected_cycles) = bytes.parse_opcode();
+                let (decoded, cycles) = table.decode(0xC000, &mut bus);
+                assert_eq!(format!("{:?}", decoded), format!("{:?}", expected));
+                assert_eq!(cycles, expected_cycles);
+            }
+        }
+        for opcode in 0..=0xFF {
+            bus.write(0xC000, 0xCB);
+            bus.write(0xC001, opcode);
+            let (expected, expected_cycles) = OpcodeParameterBytes(0xCB, opcode, 0, 0).parse_opcode();
+            let (decoded, cycles) = table.decode(0xC000, &mut bus);
+            assert_eq!(format!("{:?}", decoded), format!("{:?}", expected));
+            assert_eq!(cycles, expected_cycles);
+        }
+    }
+
     #[test]
     fn test_ld_instructions() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 0xFF);
         cpu.exec(Opcode::LD(OpcodeParameter::Register_Register(Register::B, Register::A)), &mut bus);
@@ -1882,13 +2155,13 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         cpu.exec(Opcode::LD(OpcodeParameter::Register_U16(Register::SP, 0xF1F1)), &mut bus);
         assert_eq!(cpu.registers.get(Register::SP), 0xF1F1);
         assert_eq!(cpu.registers.get(Register::PC), 0x103);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xC000;
         bus.write(addr, 0xF1);
         cpu.exec(Opcode::LD(OpcodeParameter::Register_U16(Register::A, addr)), &mut bus);
@@ -1896,20 +2169,20 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::PC), 0x103);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         cpu.exec(Opcode::LD(OpcodeParameter::Register_U8(Register::B, 0xF1)), &mut bus);
         assert_eq!(cpu.registers.get(Register::B), 0xF1);
         assert_eq!(cpu.registers.get(Register::PC), 0x102);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         cpu.registers.set(Register::SP, 0x1234);
         cpu.exec(Opcode::LD(OpcodeParameter::U16_Register(0xF0F0, Register::SP)), &mut bus);
         assert_eq!(bus.read_16bit(0xF0F0), 0x1234);
         assert_eq!(cpu.registers.get(Register::PC), 0x103);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xC000;
         cpu.registers.set(Register::A, 0x12);
         cpu.exec(Opcode::LD(OpcodeParameter::U16_Register(addr, Register::A)), &mut bus);
@@ -1917,7 +2190,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::PC), 0x103);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xC000;
         cpu.registers.set(Register::A, 0xFF);
         cpu.registers.set(Register::HL, addr);
@@ -1927,7 +2200,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xC000;
         cpu.registers.set(Register::A, 0x00);
         cpu.registers.set(Register::HL, addr);
@@ -1937,7 +2210,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xFF00;
         cpu.registers.set(Register::A, 0xF1);
         cpu.exec(Opcode::LD(OpcodeParameter::FF00plusU8_Register(0x42, Register::A)), &mut bus);
@@ -1945,7 +2218,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::PC), 0x102);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xFF00;
         cpu.registers.set(Register::A, 0x00);
         bus.write(addr + 0x42, 0xF1);
@@ -1999,7 +2272,7 @@ mod tests {
 
     #[test]
     fn test_ldi_instructions() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         let addr = 0xC000;
         cpu.registers.set(Register::A, 0x00);
@@ -2011,7 +2284,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::HL), addr + 1);
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
 
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         let addr = 0xC000;
         cpu.registers.set(Register::A, 0x1F);
@@ -2024,7 +2297,7 @@ mod tests {
 
     #[test]
     fn test_ldd_instructions() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         let addr = 0xC000;
         cpu.registers.set(Register::A, 0x00);
@@ -2036,7 +2309,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::HL), addr - 1);
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
 
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         let addr = 0xC000;
         cpu.registers.set(Register::A, 0x1F);
@@ -2050,7 +2323,7 @@ mod tests {
     #[test]
     fn test_jp_instructions() {
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         cpu.exec(Opcode::JP(OpcodeParameter::U16(0x1F1F)), &mut bus);
         assert_eq!(cpu.registers.get(Register::PC), 0x1F1F);
 
@@ -2084,7 +2357,7 @@ mod tests {
     #[test]
     fn test_jr_instructions() {
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         cpu.registers.set(Register::PC, 100);
         cpu.exec(Opcode::JR(OpcodeParameter::I8(-5)), &mut bus);
         assert_eq!(cpu.registers.get(Register::PC), 95 + 2);
@@ -2122,7 +2395,7 @@ mod tests {
     #[test]
     fn test_di_instructions() {
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         cpu.exec(Opcode::DI, &mut bus);
         assert_eq!(cpu.ime, false);
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
@@ -2131,7 +2404,7 @@ mod tests {
     #[test]
     fn test_ei_instructions() {
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         cpu.exec(Opcode::EI, &mut bus);
         assert_eq!(cpu.ime, true);
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
@@ -2140,7 +2413,7 @@ mod tests {
     #[test]
     fn test_rlca_instructions() {
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         cpu.registers.set_flag(FlagRegister::Carry, false);
         cpu.registers.set(Register::A, 0b00000010);
         cpu.exec(Opcode::RLCA, &mut bus);
@@ -2173,7 +2446,7 @@ mod tests {
 
     #[test]
     fn test_rrca_instructions() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 0b01000000);
         cpu.registers.set_flag(FlagRegister::Carry, false);
@@ -2193,7 +2466,7 @@ mod tests {
 
     #[test]
     fn test_call_instructions() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         let sp = 0xFFDF;
         cpu.registers.set(Register::SP, sp);
@@ -2244,7 +2517,7 @@ mod tests {
 
     #[test]
     fn test_rst_instructions() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         let sp = 0xFFDF;
         cpu.registers.set(Register::SP, sp);
@@ -2258,7 +2531,7 @@ mod tests {
     #[test]
     fn test_push_instructions() {
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xD000;
         cpu.registers.set(Register::SP, addr);
         cpu.registers.set(Register::AF, 0x1234);
@@ -2271,7 +2544,7 @@ mod tests {
     #[test]
     fn test_pop_instructions() {
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xD000;
         cpu.registers.set(Register::SP, addr);
         bus.write_16bit(addr, 0x1234);
@@ -2283,7 +2556,7 @@ mod tests {
     #[test]
     fn test_ret_instructions() {
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let sp = 0xD000;
         cpu.registers.set(Register::SP, sp);
         bus.write_16bit(sp, 0x1234);
@@ -2292,7 +2565,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::SP), sp + 2);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let sp = 0xD000;
         cpu.registers.set_flag(FlagRegister::Zero, false);
         cpu.registers.set(Register::SP, sp);
@@ -2302,7 +2575,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::SP), sp + 2);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let sp = 0xD000;
         cpu.registers.set_flag(FlagRegister::Zero, true);
         cpu.registers.set(Register::SP, sp);
@@ -2312,7 +2585,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::SP), sp);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let sp = 0xD000;
         cpu.registers.set_flag(FlagRegister::Zero, true);
         cpu.registers.set(Register::SP, sp);
@@ -2322,7 +2595,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::SP), sp + 2);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let sp = 0xD000;
         cpu.registers.set_flag(FlagRegister::Zero, false);
         cpu.registers.set(Register::SP, sp);
@@ -2334,7 +2607,7 @@ mod tests {
 
     #[test]
     fn test_and_instructions() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::B, 0xF1);
         cpu.registers.set(Register::C, 0x1F);
@@ -2358,7 +2631,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xC000;
         cpu.registers.set(Register::B, 0x1F);
         cpu.registers.set(Register::HL, addr);
@@ -2372,7 +2645,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xC000;
         cpu.registers.set(Register::B, 0x00);
         cpu.registers.set(Register::HL, addr);
@@ -2408,7 +2681,7 @@ mod tests {
 
     #[test]
     fn test_or_instructions() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::B, 0xF1);
         cpu.registers.set(Register::C, 0x1F);
@@ -2432,7 +2705,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xC000;
         cpu.registers.set(Register::B, 0x1F);
         cpu.registers.set(Register::HL, addr);
@@ -2446,7 +2719,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xC000;
         cpu.registers.set(Register::B, 0x00);
         cpu.registers.set(Register::HL, addr);
@@ -2482,7 +2755,7 @@ mod tests {
 
     #[test]
     fn test_xor_instructions() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::B, 0xF1);
         cpu.registers.set(Register::C, 0x1F);
@@ -2506,7 +2779,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xC000;
         cpu.registers.set(Register::B, 0x1F);
         cpu.registers.set(Register::HL, addr);
@@ -2520,7 +2793,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xC000;
         cpu.registers.set(Register::B, 0x00);
         cpu.registers.set(Register::HL, addr);
@@ -2556,7 +2829,7 @@ mod tests {
 
     #[test]
     fn test_cp_instructions() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::B, 0xF1);
         cpu.exec(Opcode::CP(OpcodeParameter::Register_U8(Register::B, 0xF1)), &mut bus);
@@ -2615,7 +2888,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xC000;
         cpu.registers.set(Register::B, 0xF1);
         cpu.registers.set(Register::HL, addr);
@@ -2628,7 +2901,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xC000;
         cpu.registers.set(Register::B, 0b00110000);
         cpu.registers.set(Register::HL, addr);
@@ -2641,7 +2914,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xC000;
         cpu.registers.set(Register::B, 0b01000000);
         cpu.registers.set(Register::HL, addr);
@@ -2656,8 +2929,8 @@ mod tests {
 
     #[test]
     fn test_add_instructions() {
-        // let mut bus = Bus::new();
-        let mut bus = Bus::new();
+        // let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::B, 0b00001000);
         cpu.registers.set(Register::C, 0b00001000);
@@ -2696,7 +2969,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xC000;
         cpu.registers.set(Register::B, 40);
         cpu.registers.set(Register::HL, addr);
@@ -2893,7 +3166,7 @@ mod tests {
     #[test]
     fn test_adc_instructions() {
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         cpu.registers.set_flag(FlagRegister::Zero, false);
         cpu.registers.set_flag(FlagRegister::Substract, false);
         cpu.registers.set_flag(FlagRegister::HalfCarry, false);
@@ -2939,7 +3212,7 @@ mod tests {
     #[test]
     fn test_sbc_instructions() {
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         cpu.registers.set_flag(FlagRegister::Zero, false);
         cpu.registers.set_flag(FlagRegister::Substract, false);
         cpu.registers.set_flag(FlagRegister::HalfCarry, false);
@@ -2956,7 +3229,7 @@ mod tests {
 
     #[test]
     fn test_sub_instructions() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::B, 0b00001000);
         cpu.registers.set(Register::C, 0b00001000);
@@ -2980,7 +3253,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let addr = 0xC000;
         cpu.registers.set(Register::B, 40);
         cpu.registers.set(Register::HL, addr);
@@ -3002,7 +3275,7 @@ mod tests {
 
     #[test]
     fn test_inc_instructions() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 0);
         cpu.exec(Opcode::INC(true, false, Register::A), &mut bus);
@@ -3039,7 +3312,7 @@ mod tests {
 
     #[test]
     fn test_dec_instructions() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 1);
         cpu.exec(Opcode::DEC(true, false, Register::A), &mut bus);
@@ -3078,7 +3351,7 @@ mod tests {
 
     #[test]
     fn test_rla_instruction() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 0b00000001);
         cpu.registers.set_flag(FlagRegister::Carry, true);
@@ -3104,7 +3377,7 @@ mod tests {
 
     #[test]
     fn test_rra_instruction() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 0b00000001);
         cpu.registers.set_flag(FlagRegister::Carry, true);
@@ -3130,7 +3403,7 @@ mod tests {
 
     #[test]
     fn test_prefix_cb_rlc_instruction() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 0b00000001);
         cpu.exec(Opcode::PrefixCB(CBOpcode::RLC(Register::A)), &mut bus);
@@ -3173,7 +3446,7 @@ mod tests {
 
     #[test]
     fn test_prefix_cb_rrc_instruction() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 0b00000001);
         cpu.exec(Opcode::PrefixCB(CBOpcode::RRC(Register::A)), &mut bus);
@@ -3216,7 +3489,7 @@ mod tests {
 
     #[test]
     fn test_prefix_cb_rl_instruction() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 0b00000001);
         cpu.registers.set_flag(FlagRegister::Carry, true);
@@ -3266,7 +3539,7 @@ mod tests {
 
     #[test]
     fn test_prefix_cb_rr_instruction() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 0b00000001);
         cpu.registers.set_flag(FlagRegister::Carry, true);
@@ -3316,7 +3589,7 @@ mod tests {
 
     #[test]
     fn test_prefix_cb_sla_instruction() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::B, 0x01);
         cpu.registers.set_flag(FlagRegister::Zero, true);
@@ -3334,7 +3607,7 @@ mod tests {
 
     #[test]
     fn test_prefix_cb_sra_instruction() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::B, 0x01);
         cpu.registers.set_flag(FlagRegister::Zero, false);
@@ -3352,7 +3625,7 @@ mod tests {
 
     #[test]
     fn test_prefix_cb_srl_instruction() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 0b00000010);
         cpu.exec(Opcode::PrefixCB(CBOpcode::SRL(Register::A)), &mut bus);
@@ -3363,7 +3636,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::A), 0b00000001);
         assert_eq!(cpu.registers.get(Register::PC), 0x102);
 
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 0b00000001);
         cpu.exec(Opcode::PrefixCB(CBOpcode::SRL(Register::A)), &mut bus);
@@ -3389,7 +3662,7 @@ mod tests {
 
     #[test]
     fn test_prefix_cb_swap_instruction() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 0b11110101);
         cpu.exec(Opcode::PrefixCB(CBOpcode::SWAP(Register::A)), &mut bus);
@@ -3400,7 +3673,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::A), 0b01011111);
         assert_eq!(cpu.registers.get(Register::PC), 0x102);
 
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 0b00000000);
         cpu.exec(Opcode::PrefixCB(CBOpcode::SWAP(Register::A)), &mut bus);
@@ -3411,7 +3684,7 @@ mod tests {
         assert_eq!(cpu.registers.get(Register::A), 0b00000000);
         assert_eq!(cpu.registers.get(Register::PC), 0x102);
 
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         let addr = 0xC000;
         cpu.registers.set(Register::HL, addr);
@@ -3427,7 +3700,7 @@ mod tests {
 
     #[test]
     fn test_prefix_cb_bit_instruction() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 0b11110101);
         cpu.exec(Opcode::PrefixCB(CBOpcode::BIT(BitIndex::I3, Register::A)), &mut bus);
@@ -3457,7 +3730,7 @@ mod tests {
 
     #[test]
     fn test_prefix_cb_res_instruction() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 0b11110101);
         cpu.exec(Opcode::PrefixCB(CBOpcode::RES(BitIndex::I2, Register::A)), &mut bus);
@@ -3475,7 +3748,7 @@ mod tests {
 
     #[test]
     fn test_prefix_cb_set_instruction() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 0b11110101);
         cpu.exec(Opcode::PrefixCB(CBOpcode::SET(BitIndex::I1, Register::A)), &mut bus);
@@ -3493,7 +3766,7 @@ mod tests {
 
     #[test]
     fn test_daa_instruction() {
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         let mut cpu = CPU::new();
         cpu.registers.set(Register::A, 0x0A);
         cpu.registers.set_flag(FlagRegister::Carry, false);
@@ -3533,7 +3806,7 @@ mod tests {
     #[test]
     fn test_cpl_instructions() {
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         cpu.registers.set(Register::A, 0b11110000);
         cpu.exec(Opcode::CPL, &mut bus);
         assert_eq!(cpu.registers.get(Register::A), 0b00001111);
@@ -3545,7 +3818,7 @@ mod tests {
     #[test]
     fn test_ccf_instructions() {
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         cpu.registers.set_flag(FlagRegister::Substract, true);
         cpu.registers.set_flag(FlagRegister::HalfCarry, true);
         cpu.registers.set_flag(FlagRegister::Carry, false);
@@ -3556,7 +3829,7 @@ mod tests {
         assert_eq!(cpu.registers.get_flag(FlagRegister::Carry), true);
 
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         cpu.registers.set_flag(FlagRegister::Substract, true);
         cpu.registers.set_flag(FlagRegister::HalfCarry, true);
         cpu.registers.set_flag(FlagRegister::Carry, true);
@@ -3570,7 +3843,7 @@ mod tests {
     #[test]
     fn test_scf_instructions() {
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         cpu.registers.set_flag(FlagRegister::Substract, true);
         cpu.registers.set_flag(FlagRegister::HalfCarry, true);
         cpu.registers.set_flag(FlagRegister::Carry, false);
@@ -3584,7 +3857,7 @@ mod tests {
     #[test]
     fn test_nop_instructions() {
         let mut cpu = CPU::new();
-        let mut bus = Bus::new();
+        let mut bus = Bus::new(empty_rom(), &EmulatorConfig::new());
         cpu.exec(Opcode::NOP, &mut bus);
         assert_eq!(cpu.registers.get(Register::PC), 0x101);
     }
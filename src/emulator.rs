@@ -1,154 +1,312 @@
 // use std::{thread, time};
-use winit_input_helper::WinitInputHelper;
-use winit::event::VirtualKeyCode;
 
-use crate::cpu::{CPU, Cycles};
+use crate::cpu::{CPU, TCycles};
+use crate::ppu::FRAME_CYCLES;
 use crate::interrupts::Interrupt;
 use crate::bus::Bus;
-use crate::joypad::Button;
-#[cfg(not(test))]
-use crate::rom::{save_file};
+use crate::joypad::{Button, ButtonState};
+use crate::rom::{ROM, ROMInfo, LoadError, load_rom_with_patch, rom_from_bytes, save_file};
+use crate::config::EmulatorConfig;
+use crate::rewind::Rewind;
+use crate::infrared::InfraredPeer;
+use crate::savestate::{
+    SaveState,
+    SaveStateError,
+    StateReader,
+    StateWriter,
+    SAVE_STATE_MAGIC,
+    SAVE_STATE_VERSION,
+};
 
 pub struct Emulator {
     bus: Bus,
     cpu: CPU,
+    config: EmulatorConfig,
+    rewind: Option<Rewind>,
+    rumble: bool,
+    rumble_callback: Option<Box<dyn FnMut(bool)>>,
+    tone: Option<u8>,
+    tone_callback: Option<Box<dyn FnMut(Option<u8>)>>,
 }
 
 impl Emulator {
-    pub fn new() -> Self {
-        let bus = Bus::new();
-        let cpu = match bus.cgb_mode {
+    pub fn from_file(filename: &str, config: EmulatorConfig) -> Result<Self, LoadError> {
+        Ok(Self::with_rom(load_rom_with_patch(filename, config.patch())?, config))
+    }
+
+    pub fn from_rom_bytes(data: Vec<u8>, save: Option<&[u8]>, config: EmulatorConfig) -> Result<Self, LoadError> {
+        Ok(Self::with_rom(rom_from_bytes(data, save)?, config))
+    }
+
+    fn with_rom(rom: Box<dyn ROM>, config: EmulatorConfig) -> Self {
+        let bus = Bus::new(rom, &config);
+        let mut cpu = match bus.cgb_mode {
             true => CPU::new_cgb(),
             false => CPU::new(),
         };
+        cpu.set_enable_logs(config.cpu_trace());
+        let rewind = match config.rewind_interval() > 0 && config.rewind_capacity() > 0 {
+            true => Some(Rewind::new(config.rewind_interval(), config.rewind_capacity())),
+            false => None,
+        };
         Self {
             bus,
             cpu,
+            config,
+            rewind,
+            rumble: false,
+            rumble_callback: None,
+            tone: None,
+            tone_callback: None,
         }
     }
 
+    pub fn config(&self) -> &EmulatorConfig {
+        &self.config
+    }
+
     pub fn close(&self) {
         println!("closing emulator");
 
-        #[cfg(not(test))]
-        match save_file(self.bus.rom.ram(), self.bus.rom.info()) {
-            Err(err) => eprintln!("Could not save file: {}", err),
-            _ => {},
-        };
+        if let Err(err) = save_file(self.bus.rom.as_ref()) {
+            eprintln!("Could not save file: {}", err);
+        }
     }
 
-    pub fn handle_input(&mut self, input: &WinitInputHelper) {
-        let mut change = false;
-        if input.key_pressed(VirtualKeyCode::K) {
-            change = true;
-            self.bus.joypad.press(Button::A);
-        }
-        if input.key_pressed(VirtualKeyCode::J) {
-            change = true;
-            self.bus.joypad.press(Button::B);
-        }
-        if input.key_pressed(VirtualKeyCode::W) {
-            change = true;
-            self.bus.joypad.press(Button::Up);
-        }
-        if input.key_pressed(VirtualKeyCode::S) {
-            change = true;
-            self.bus.joypad.press(Button::Down);
+    // T-cycles elapsed since power on
+    pub fn clock(&self) -> u64 {
+        self.bus.scheduler.now()
+    }
+
+    // State of the motor in rumble cartridges
+    pub fn rumble(&self) -> bool {
+        self.bus.rom.rumble()
+    }
+
+    // Tone played by the speaker of cartridges like HuC3
+    pub fn tone(&self) -> Option<u8> {
+        self.bus.rom.tone()
+    }
+
+    // Tilt for cartridges with an accelerometer, from -1.0 to 1.0 on each axis
+    pub fn set_tilt(&mut self, x: f32, y: f32) {
+        self.bus.rom.set_tilt(x, y);
+    }
+
+    // Connects the infrared port of cartridges like HuC1 to another device
+    pub fn set_infrared_peer(&mut self, peer: Option<Box<dyn InfraredPeer>>) {
+        self.bus.rom.set_infrared_peer(peer);
+    }
+
+    // Picture seen by the Game Boy Camera, CAMERA_WIDTH * CAMERA_HEIGHT brightness values
+    pub fn set_camera_image(&mut self, image: &[u8]) {
+        self.bus.rom.set_camera_image(image);
+    }
+
+    // Called every time the rumble motor is turned on or off
+    pub fn set_rumble_callback(&mut self, callback: Option<Box<dyn FnMut(bool)>>) {
+        self.rumble = self.bus.rom.rumble();
+        self.rumble_callback = callback;
+    }
+
+    fn update_rumble(&mut self) {
+        let rumble = self.bus.rom.rumble();
+        if rumble == self.rumble {
+            return;
         }
-        if input.key_pressed(VirtualKeyCode::A) {
-            change = true;
-            self.bus.joypad.press(Button::Left);
+        self.rumble = rumble;
+        if let Some(callback) = self.rumble_callback.as_mut() {
+            callback(rumble);
         }
-        if input.key_pressed(VirtualKeyCode::D) {
-            change = true;
-            self.bus.joypad.press(Button::Right);
+    }
+
+    // Called every time the speaker starts, stops or changes its tone
+    pub fn set_tone_callback(&mut self, callback: Option<Box<dyn FnMut(Option<u8>)>>) {
+        self.tone = self.bus.rom.tone();
+        self.tone_callback = callback;
+    }
+
+    fn update_tone(&mut self) {
+        let tone = self.bus.rom.tone();
+        if tone == self.tone {
+            return;
         }
-        if input.key_pressed(VirtualKeyCode::N) {
-            change = true;
-            self.bus.joypad.press(Button::Start);
+        self.tone = tone;
+        if let Some(callback) = self.tone_callback.as_mut() {
+            callback(tone);
         }
-        if input.key_pressed(VirtualKeyCode::B) {
-            change = true;
-            self.bus.joypad.press(Button::Select);
+    }
+
+    // Battery backed data in the same format as the .sav file
+    pub fn save_ram(&self) -> Vec<u8> {
+        self.bus.rom.save_data()
+    }
+
+    pub fn rom_info(&self) -> &ROMInfo {
+        self.bus.rom.info()
+    }
+
+    pub fn save_state(&self) -> Vec<u8> {
+        let mut state = StateWriter::new();
+        for byte in SAVE_STATE_MAGIC {
+            state.write_u8(byte);
         }
+        state.write_u16(SAVE_STATE_VERSION);
+        state.write_u8(self.bus.rom.info().header_checksum());
+        self.cpu.save_state(&mut state);
+        self.bus.save_state(&mut state);
+        state.into_bytes()
+    }
 
-        if input.key_released(VirtualKeyCode::K) {
-            change = true;
-            self.bus.joypad.release(Button::A);
+    // The rewind history belongs to the timeline that was left behind, so it's dropped
+    pub fn load_state(&mut self, data: &[u8]) -> Result<(), SaveStateError> {
+        self.restore_state(data)?;
+        if let Some(rewind) = self.rewind.as_mut() {
+            rewind.clear();
         }
-        if input.key_released(VirtualKeyCode::J) {
-            change = true;
-            self.bus.joypad.release(Button::B);
+        Ok(())
+    }
+
+    fn restore_state(&mut self, data: &[u8]) -> Result<(), SaveStateError> {
+        // Keep a copy of the current state so a corrupted file doesn't leave the machine half loaded
+        let backup = self.save_state();
+        if let Err(err) = self.read_state(data) {
+            self.read_state(&backup).expect("Could not restore the previous state");
+            return Err(err);
         }
-        if input.key_released(VirtualKeyCode::W) {
-            change = true;
-            self.bus.joypad.release(Button::Up);
+        Ok(())
+    }
+
+    fn read_state(&mut self, data: &[u8]) -> Result<(), SaveStateError> {
+        let mut state = StateReader::new(data);
+        for byte in SAVE_STATE_MAGIC {
+            if state.read_u8()? != byte {
+                return Err(SaveStateError::InvalidMagic);
+            }
         }
-        if input.key_released(VirtualKeyCode::S) {
-            change = true;
-            self.bus.joypad.release(Button::Down);
+        let version = state.read_u16()?;
+        if version != SAVE_STATE_VERSION {
+            return Err(SaveStateError::UnsupportedVersion(version));
         }
-        if input.key_released(VirtualKeyCode::A) {
-            change = true;
-            self.bus.joypad.release(Button::Left);
+        if state.read_u8()? != self.bus.rom.info().header_checksum() {
+            return Err(SaveStateError::RomMismatch);
         }
-        if input.key_released(VirtualKeyCode::D) {
-            change = true;
-            self.bus.joypad.release(Button::Right);
+        self.cpu.load_state(&mut state)?;
+        self.bus.load_state(&mut state)?;
+        if !state.is_empty() {
+            return Err(SaveStateError::InvalidData("trailing bytes"));
         }
-        if input.key_released(VirtualKeyCode::N) {
-            change = true;
-            self.bus.joypad.release(Button::Start);
+        Ok(())
+    }
+
+    // Goes back at least `frames` frames, rounded up to the closest snapshot.
+    // Returns false if there is nothing left to rewind to
+    pub fn rewind(&mut self, frames: usize) -> bool {
+        let snapshot = match self.rewind.as_mut() {
+            Some(rewind) => rewind.rewind(frames),
+            None => None,
+        };
+        match snapshot {
+            Some(snapshot) => match self.restore_state(&snapshot) {
+                Ok(_) => true,
+                Err(err) => {
+                    eprintln!("Could not rewind: {}", err);
+                    false
+                },
+            },
+            None => false,
         }
-        if input.key_released(VirtualKeyCode::B) {
-            change = true;
-            self.bus.joypad.release(Button::Select);
+    }
+
+    fn take_rewind_snapshot(&mut self) {
+        let snapshot_needed = match self.rewind.as_mut() {
+            Some(rewind) => rewind.frame_completed(),
+            None => false,
+        };
+        if snapshot_needed {
+            let snapshot = self.save_state();
+            if let Some(rewind) = self.rewind.as_mut() {
+                rewind.push(snapshot);
+            }
         }
-        if change {
-            self.bus.interrupts.request(Interrupt::Joypad);
+    }
+
+    pub fn press_button(&mut self, button: Button) {
+        self.bus.joypad.press(button);
+        self.bus.interrupts.request(Interrupt::Joypad);
+    }
+
+    pub fn release_button(&mut self, button: Button) {
+        self.bus.joypad.release(button);
+        self.bus.interrupts.request(Interrupt::Joypad);
+    }
+
+    pub fn buttons(&self) -> ButtonState {
+        self.bus.joypad.buttons()
+    }
+
+    pub fn set_buttons(&mut self, buttons: ButtonState) {
+        if self.bus.joypad.buttons() == buttons {
+            return;
         }
+        self.bus.joypad.set_buttons(buttons);
+        self.bus.interrupts.request(Interrupt::Joypad);
     }
 
-    fn tick(&mut self, frame_buffer: &mut [u8]) {
+    fn tick(&mut self) {
         self.cpu.run(&mut self.bus);
-        let cycles = self.cpu.get_last_op_cycles().to_t();
-        self.bus.ppu.do_cycles(&mut self.bus.interrupts, cycles, frame_buffer);
-        self.bus.sound.do_cycles(cycles);
-        self.bus.timer.do_cycles(&mut self.bus.interrupts, cycles);
-        if self.bus.double_speed_mode() {
-            self.bus.timer.do_cycles(&mut self.bus.interrupts, Cycles(cycles.0 * 3.0));
+        self.bus.scheduler.advance(self.cpu.get_last_op_cycles());
+        if self.cpu.is_halted() {
+            // Only an event can wake the CPU up, so skip straight to the next one
+            let now = self.bus.scheduler.now();
+            let next_event = self.bus.scheduler.next_event();
+            if next_event > now {
+                let cycles = (next_event - now).min(FRAME_CYCLES as u64) as u32;
+                let elapsed = self.cpu.idle(TCycles(cycles));
+                self.bus.scheduler.advance(elapsed);
+            }
+        }
+        self.bus.handle_events();
+        if self.rumble_callback.is_some() {
+            self.update_rumble();
+        }
+        if self.tone_callback.is_some() {
+            self.update_tone();
         }
 
         // 1 CPU cycle = 238.42ns
         // thread::sleep(time::Duration::from_nanos((self.cpu.get_last_op_cycles().0 * 238).try_into().unwrap()));
     }
 
-    pub fn run(&mut self, cpu_cycles: Cycles, frame_buffer: &mut [u8]) {
+    pub fn run(&mut self, cpu_cycles: TCycles, frame_buffer: &mut [u8]) {
         self.cpu.reset_cycles();
-        while self.cpu.get_cycles().to_t().0 <= cpu_cycles.0 {
-            self.tick(frame_buffer);
+        while self.cpu.get_cycles() <= cpu_cycles {
+            self.tick();
         }
+        frame_buffer.copy_from_slice(self.bus.ppu.frame_buffer());
     }
 
     pub fn run_frame(&mut self, frame_buffer: &mut [u8]) {
         self.cpu.reset_cycles();
         let mut frame_started = true;
         while self.bus.ppu.lcd_y() < 144 || frame_started {
-            self.tick(frame_buffer);
+            self.tick();
             if self.bus.ppu.lcd_y() == 0 {
                 frame_started = false;
             }
+            // LY stays at 0 while the LCD is off, end the frame after the time it would have taken
+            if !self.bus.ppu.lcd_enabled() && self.cpu.get_cycles().0 >= FRAME_CYCLES {
+                break;
+            }
         }
+        frame_buffer.copy_from_slice(self.bus.ppu.frame_buffer());
+        self.take_rewind_snapshot();
     }
 
     pub fn cpu_loop(&mut self) {
         let mut exit = false;
-        let mut frame: [u8; 144 * 160 * 4] = [0; 144 * 160 * 4];
         while !exit {
-            self.cpu.run(&mut self.bus);
-            let cycles = self.cpu.get_last_op_cycles().to_t();
-            self.bus.ppu.do_cycles(&mut self.bus.interrupts, cycles, &mut frame);
-            self.bus.timer.do_cycles(&mut self.bus.interrupts, cycles);
+            self.tick();
 
             // exit = self.cpu.get_exec_calls_count() >= 1258895; // log 1
             exit = self.cpu.get_exec_calls_count() >= 161502; // log 2
@@ -165,3 +323,244 @@ impl Emulator {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::HardwareModel;
+
+    fn update_header_checksum(data: &mut [u8]) {
+        let mut checksum: u8 = 0;
+        for byte in &data[0x0134..0x014D] {
+            checksum = checksum.wrapping_sub(*byte).wrapping_sub(1);
+        }
+        data[0x014D] = checksum;
+    }
+
+    fn rom_bytes() -> Vec<u8> {
+        let mut data = vec![0; 0x8000];
+        // JR -2, loop forever at the entry point
+        data[0x0100] = 0x18;
+        data[0x0101] = 0xFE;
+        update_header_checksum(&mut data);
+        data
+    }
+
+    #[test]
+    fn test_from_rom_bytes() {
+        let mut emulator = Emulator::from_rom_bytes(rom_bytes(), None, EmulatorConfig::new()).unwrap();
+        let mut frame = [0; 144 * 160 * 4];
+        emulator.run_frame(&mut frame);
+        assert!(emulator.save_ram().is_empty());
+        let clock = emulator.clock();
+        assert!(clock > 0);
+        emulator.run_frame(&mut frame);
+        assert!(emulator.clock() > clock);
+    }
+
+    #[test]
+    fn test_frame_with_lcd_off() {
+        let mut data = rom_bytes();
+        // LD A, 0x00; LDH (0x40), A; JR -2
+        data[0x0100..0x0106].copy_from_slice(&[0x3E, 0x00, 0xE0, 0x40, 0x18, 0xFE]);
+        update_header_checksum(&mut data);
+        let mut emulator = Emulator::from_rom_bytes(data, None, EmulatorConfig::new()).unwrap();
+        let mut frame = [0; 144 * 160 * 4];
+        emulator.run_frame(&mut frame);
+        let clock = emulator.clock();
+        emulator.run_frame(&mut frame);
+        assert!(!emulator.bus.ppu.lcd_enabled());
+        assert!(emulator.clock() - clock >= FRAME_CYCLES as u64);
+    }
+
+    #[test]
+    fn test_halt_skips_to_next_event() {
+        let mut data = rom_bytes();
+        // DI; HALT never wakes up, the frame should still last exactly the same
+        data[0x0100..0x0104].copy_from_slice(&[0xF3, 0x76, 0x18, 0xFD]);
+        update_header_checksum(&mut data);
+        let mut emulator = Emulator::from_rom_bytes(data, None, EmulatorConfig::new()).unwrap();
+        let mut frame = [0; 144 * 160 * 4];
+        emulator.run_frame(&mut frame);
+        let clock = emulator.clock();
+        emulator.run_frame(&mut frame);
+        assert_eq!(emulator.clock() - clock, FRAME_CYCLES as u64);
+    }
+
+    #[test]
+    fn test_rumble_callback() {
+        let mut data = rom_bytes();
+        data[0x0147] = 0x1C;
+        // LD A, 0x08; LD (0x4000), A; XOR A; LD (0x4000), A; JR -10
+        data[0x0100..0x010B].copy_from_slice(&[0x3E, 0x08, 0xEA, 0x00, 0x40, 0xAF, 0xEA, 0x00, 0x40, 0x18, 0xF5]);
+        update_header_checksum(&mut data);
+        let mut emulator = Emulator::from_rom_bytes(data, None, EmulatorConfig::new()).unwrap();
+        let events = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let callback_events = events.clone();
+        emulator.set_rumble_callback(Some(Box::new(move |rumble| callback_events.borrow_mut().push(rumble))));
+        for _ in 0..4 {
+            emulator.tick();
+        }
+        assert_eq!(*events.borrow(), vec![true, false]);
+        assert!(!emulator.rumble());
+    }
+
+    #[test]
+    fn test_tone_callback() {
+        let mut data = rom_bytes();
+        data[0x0147] = 0xFE;
+        // LD A, 0x0B; LD (0x0000), A; LD A, 0x63; LD (0xA000), A; LD A, 0x60; LD (0xA000), A; JR -2
+        data[0x0100..0x0111].copy_from_slice(&[
+            0x3E, 0x0B, 0xEA, 0x00, 0x00, 0x3E, 0x63, 0xEA, 0x00, 0xA0, 0x3E, 0x60, 0xEA, 0x00, 0xA0, 0x18, 0xFE,
+        ]);
+        update_header_checksum(&mut data);
+        let mut emulator = Emulator::from_rom_bytes(data, None, EmulatorConfig::new()).unwrap();
+        let events = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let callback_events = events.clone();
+        emulator.set_tone_callback(Some(Box::new(move |tone| callback_events.borrow_mut().push(tone))));
+        for _ in 0..7 {
+            emulator.tick();
+        }
+        assert_eq!(*events.borrow(), vec![Some(0x3), None]);
+        assert_eq!(emulator.tone(), None);
+    }
+
+    #[test]
+    fn test_config_per_instance() {
+        let mut data = rom_bytes();
+        data[0x0143] = 0x80;
+        update_header_checksum(&mut data);
+        let cgb = Emulator::from_rom_bytes(data.clone(), None, EmulatorConfig::new()).unwrap();
+        let dmg = Emulator::from_rom_bytes(
+            data,
+            None,
+            EmulatorConfig::new().with_hardware_model(HardwareModel::DMG),
+        ).unwrap();
+        assert!(cgb.bus.cgb_mode);
+        assert!(!dmg.bus.cgb_mode);
+    }
+
+    #[test]
+    fn test_save_state_round_trip() {
+        let mut emulator = Emulator::from_rom_bytes(rom_bytes(), None, EmulatorConfig::new()).unwrap();
+        let mut frame = [0; 144 * 160 * 4];
+        emulator.run_frame(&mut frame);
+        let state = emulator.save_state();
+        emulator.run_frame(&mut frame);
+        emulator.run_frame(&mut frame);
+        let expected = emulator.save_state();
+        assert_ne!(state, expected);
+
+        emulator.load_state(&state).unwrap();
+        assert_eq!(emulator.save_state(), state);
+        emulator.run_frame(&mut frame);
+        emulator.run_frame(&mut frame);
+        assert_eq!(emulator.save_state(), expected);
+    }
+
+    #[test]
+    fn test_rewind() {
+        let mut emulator = Emulator::from_rom_bytes(
+            rom_bytes(),
+            None,
+            EmulatorConfig::new().with_rewind(2, 10),
+        ).unwrap();
+        let mut frame = [0; 144 * 160 * 4];
+        assert!(!emulator.rewind(1));
+        let mut states = Vec::new();
+        for _ in 0..3 {
+            emulator.run_frame(&mut frame);
+            emulator.run_frame(&mut frame);
+            states.push(emulator.save_state());
+        }
+        emulator.run_frame(&mut frame);
+
+        assert!(emulator.rewind(1));
+        assert_eq!(emulator.save_state(), states[2]);
+        assert!(emulator.rewind(4));
+        assert_eq!(emulator.save_state(), states[0]);
+        assert!(!emulator.rewind(1));
+        assert_eq!(emulator.save_state(), states[0]);
+    }
+
+    #[test]
+    fn test_load_state_clears_rewind() {
+        let mut emulator = Emulator::from_rom_bytes(
+            rom_bytes(),
+            None,
+            EmulatorConfig::new().with_rewind(1, 10),
+        ).unwrap();
+        let mut frame = [0; 144 * 160 * 4];
+        let state = emulator.save_state();
+        emulator.run_frame(&mut frame);
+        emulator.run_frame(&mut frame);
+        emulator.load_state(&state).unwrap();
+        assert!(!emulator.rewind(1));
+        assert_eq!(emulator.save_state(), state);
+    }
+
+    #[test]
+    fn test_rewind_disabled() {
+        let mut emulator = Emulator::from_rom_bytes(rom_bytes(), None, EmulatorConfig::new()).unwrap();
+        let mut frame = [0; 144 * 160 * 4];
+        emulator.run_frame(&mut frame);
+        emulator.run_frame(&mut frame);
+        assert!(!emulator.rewind(1));
+    }
+
+    #[test]
+    fn test_input() {
+        let mut emulator = Emulator::from_rom_bytes(rom_bytes(), None, EmulatorConfig::new()).unwrap();
+        emulator.press_button(Button::Start);
+        assert!(emulator.buttons().start);
+        emulator.release_button(Button::Start);
+        assert_eq!(emulator.buttons(), ButtonState::default());
+
+        let buttons = ButtonState {
+            a: true,
+            left: true,
+            ..ButtonState::default()
+        };
+        emulator.set_buttons(buttons);
+        assert_eq!(emulator.buttons(), buttons);
+        // Select the action buttons, A is the lowest bit and reads as 0 when pressed
+        emulator.bus.write(0xFF00, 0b00010000);
+        assert_eq!(emulator.bus.read(0xFF00) & 0x0F, 0b1110);
+    }
+
+    #[test]
+    fn test_load_invalid_state() {
+        let mut emulator = Emulator::from_rom_bytes(rom_bytes(), None, EmulatorConfig::new()).unwrap();
+        let state = emulator.save_state();
+
+        let mut data = state.clone();
+        data[0] = 0;
+        assert!(matches!(emulator.load_state(&data), Err(SaveStateError::InvalidMagic)));
+
+        let mut data = state.clone();
+        data[4] = 0xFF;
+        assert!(matches!(emulator.load_state(&data), Err(SaveStateError::UnsupportedVersion(_))));
+
+        let mut data = state.clone();
+        data[6] = data[6].wrapping_add(1);
+        assert!(matches!(emulator.load_state(&data), Err(SaveStateError::RomMismatch)));
+
+        let data = &state[..state.len() - 1];
+        assert!(matches!(emulator.load_state(data), Err(SaveStateError::UnexpectedEnd)));
+        assert_eq!(emulator.save_state(), state);
+    }
+
+    #[test]
+    fn test_from_rom_bytes_invalid_header() {
+        let mut data = rom_bytes();
+        data[0x014D] = data[0x014D].wrapping_add(1);
+        assert!(matches!(
+            Emulator::from_rom_bytes(data, None, EmulatorConfig::new()),
+            Err(LoadError::ChecksumMismatch { .. }),
+        ));
+        assert!(matches!(
+            Emulator::from_rom_bytes(Vec::new(), None, EmulatorConfig::new()),
+            Err(LoadError::Truncated { .. }),
+        ));
+    }
+}
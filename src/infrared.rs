@@ -0,0 +1,57 @@
+use crate::savestate::{SaveState, SaveStateError, StateReader, StateWriter};
+
+// Device on the other side of an infrared port, such as another Game Boy
+pub trait InfraredPeer {
+    // Called whenever our LED is switched on or off
+    fn set_led(&mut self, on: bool);
+    // Whether the peer is sending light to us right now
+    fn light(&self) -> bool;
+}
+
+// Infrared port of cartridges like HuC1 and HuC3: our LED and whatever is connected to it
+#[derive(Default)]
+pub struct InfraredPort {
+    led: bool,
+    peer: Option<Box<dyn InfraredPeer>>,
+}
+
+impl InfraredPort {
+    // Bit 0 is set while light is being received
+    pub fn read(&self) -> u8 {
+        let light = match &self.peer {
+            Some(peer) => peer.light(),
+            None => false,
+        };
+        0xC0 | light as u8
+    }
+
+    // The peer is only told about changes of the LED
+    pub fn write(&mut self, data: u8) {
+        let led = data & 1 != 0;
+        if led != self.led {
+            self.led = led;
+            if let Some(peer) = self.peer.as_mut() {
+                peer.set_led(led);
+            }
+        }
+    }
+
+    pub fn set_peer(&mut self, peer: Option<Box<dyn InfraredPeer>>) {
+        self.peer = peer;
+        // Let the new peer know if the LED is already on
+        if let Some(peer) = self.peer.as_mut() {
+            peer.set_led(self.led);
+        }
+    }
+}
+
+impl SaveState for InfraredPort {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bool(self.led);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.led = state.read_bool()?;
+        Ok(())
+    }
+}
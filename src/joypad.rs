@@ -1,8 +1,9 @@
 use crate::utils::{BitIndex, get_bit};
+use crate::savestate::{SaveState, SaveStateError, StateReader, StateWriter};
 
 pub const JOYPAD_ADDRESS: u16 = 0xFF00;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Button {
     A,
     B,
@@ -14,6 +15,19 @@ pub enum Button {
     Select
 }
 
+// Snapshot of every button, true means pressed
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct ButtonState {
+    pub a: bool,
+    pub b: bool,
+    pub up: bool,
+    pub down: bool,
+    pub left: bool,
+    pub right: bool,
+    pub start: bool,
+    pub select: bool,
+}
+
 pub struct Joypad {
     a: bool,
     b: bool,
@@ -65,6 +79,30 @@ impl Joypad {
         };
     }
 
+    pub fn buttons(&self) -> ButtonState {
+        ButtonState {
+            a: self.a,
+            b: self.b,
+            up: self.up,
+            down: self.down,
+            left: self.left,
+            right: self.right,
+            start: self.start,
+            select: self.select,
+        }
+    }
+
+    pub fn set_buttons(&mut self, buttons: ButtonState) {
+        self.a = buttons.a;
+        self.b = buttons.b;
+        self.up = buttons.up;
+        self.down = buttons.down;
+        self.left = buttons.left;
+        self.right = buttons.right;
+        self.start = buttons.start;
+        self.select = buttons.select;
+    }
+
     pub fn read(&self, byte: u8) -> u8 {
         let direction = !get_bit(byte, BitIndex::I4);
         let action = !get_bit(byte, BitIndex::I5);
@@ -82,3 +120,28 @@ impl Joypad {
         return data;
     }
 }
+
+impl SaveState for Joypad {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bool(self.a);
+        state.write_bool(self.b);
+        state.write_bool(self.up);
+        state.write_bool(self.down);
+        state.write_bool(self.left);
+        state.write_bool(self.right);
+        state.write_bool(self.start);
+        state.write_bool(self.select);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.a = state.read_bool()?;
+        self.b = state.read_bool()?;
+        self.up = state.read_bool()?;
+        self.down = state.read_bool()?;
+        self.left = state.read_bool()?;
+        self.right = state.read_bool()?;
+        self.start = state.read_bool()?;
+        self.select = state.read_bool()?;
+        Ok(())
+    }
+}
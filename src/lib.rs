@@ -3,11 +3,21 @@ pub mod cpu;
 pub mod ppu;
 pub mod timer;
 pub mod sound;
+pub mod serial;
+pub mod infrared;
+pub mod camera;
+pub mod scheduler;
 pub mod rom;
+pub mod licensee;
+pub mod patch;
 pub mod ram;
 pub mod bus;
 pub mod interrupts;
 pub mod joypad;
+pub mod config;
 pub mod emulator;
+#[cfg(feature = "frontend")]
 pub mod render;
 pub mod frames;
+pub mod savestate;
+pub mod rewind;
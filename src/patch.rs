@@ -0,0 +1,459 @@
+use std::fmt;
+
+// Extensions looked for next to a ROM, in order of preference
+pub const PATCH_EXTENSIONS: [&str; 3] = ["bps", "ups", "ips"];
+
+const IPS_MAGIC: &[u8] = b"PATCH";
+const IPS_EOF: &[u8] = b"EOF";
+const UPS_MAGIC: &[u8] = b"UPS1";
+const BPS_MAGIC: &[u8] = b"BPS1";
+// UPS and BPS end with the CRC32 of the source, the target and the patch itself
+const FOOTER_SIZE: usize = 12;
+// Largest Game Boy ROM, target sizes come straight from the patch so they're checked before allocating
+const MAX_TARGET_SIZE: usize = 0x800000;
+
+#[derive(Debug)]
+pub enum PatchError {
+    UnknownFormat,
+    UnexpectedEnd,
+    // The patch reads or writes past the end of the data
+    OutOfBounds,
+    ChecksumMismatch { checksum: &'static str, expected: u32, actual: u32 },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PatchError::UnknownFormat => write!(f, "Not an IPS, UPS or BPS patch"),
+            PatchError::UnexpectedEnd => write!(f, "Patch is truncated"),
+            PatchError::OutOfBounds => write!(f, "Patch points outside of the ROM"),
+            PatchError::ChecksumMismatch { checksum, expected, actual } => write!(
+                f,
+                "Wrong {} checksum (expected 0x{:08X}, found 0x{:08X}). Is the patch meant for this ROM?",
+                checksum,
+                expected,
+                actual,
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFFFFFF;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = match crc & 1 {
+                1 => (crc >> 1) ^ 0xEDB88320,
+                _ => crc >> 1,
+            };
+        }
+    }
+    !crc
+}
+
+// Patches are only ever read forwards
+struct PatchReader<'a> {
+    data: &'a [u8],
+    index: usize,
+}
+
+impl<'a> PatchReader<'a> {
+    fn new(data: &'a [u8], index: usize) -> Self {
+        Self { data, index }
+    }
+
+    fn read_bytes(&mut self, size: usize) -> Result<&'a [u8], PatchError> {
+        let end = self.index.checked_add(size).ok_or(PatchError::UnexpectedEnd)?;
+        let bytes = self.data.get(self.index..end).ok_or(PatchError::UnexpectedEnd)?;
+        self.index += size;
+        Ok(bytes)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, PatchError> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    fn read_be(&mut self, size: usize) -> Result<usize, PatchError> {
+        Ok(self.read_bytes(size)?.iter().fold(0, |value, byte| (value << 8) | *byte as usize))
+    }
+
+    fn read_le_u32(&mut self) -> Result<u32, PatchError> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    // UPS and BPS numbers: 7 bits per byte, the last one with bit 7 set. Every byte
+    // after the first one also adds one, so each value has a single encoding
+    fn read_number(&mut self) -> Result<usize, PatchError> {
+        let mut value: usize = 0;
+        let mut shift: usize = 1;
+        loop {
+            let byte = self.read_u8()?;
+            value = value.checked_add((byte & 0x7F) as usize * shift).ok_or(PatchError::OutOfBounds)?;
+            if byte & 0x80 != 0 {
+                return Ok(value);
+            }
+            shift = shift.checked_mul(0x80).ok_or(PatchError::OutOfBounds)?;
+            value = value.checked_add(shift).ok_or(PatchError::OutOfBounds)?;
+        }
+    }
+}
+
+// Applies an IPS, UPS or BPS patch, told apart by their magic bytes
+pub fn apply_patch(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    if patch.starts_with(IPS_MAGIC) {
+        apply_ips(rom, patch)
+    } else if patch.starts_with(UPS_MAGIC) {
+        apply_ups(rom, patch)
+    } else if patch.starts_with(BPS_MAGIC) {
+        apply_bps(rom, patch)
+    } else {
+        Err(PatchError::UnknownFormat)
+    }
+}
+
+// Records of a 24 bit offset and 16 bit size followed by the data, a size of 0 means
+// a run of a single byte. The optional 24 bits after EOF truncate the output
+fn apply_ips(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    let mut output = rom.to_vec();
+    let mut reader = PatchReader::new(patch, IPS_MAGIC.len());
+    loop {
+        if reader.data.get(reader.index..reader.index + 3) == Some(IPS_EOF) {
+            reader.index += 3;
+            break;
+        }
+        let offset = reader.read_be(3)?;
+        let (data, size) = match reader.read_be(2)? {
+            0 => {
+                let size = reader.read_be(2)?;
+                (None, size)
+            },
+            size => (Some(reader.read_bytes(size)?), size),
+        };
+        if output.len() < offset + size {
+            output.resize(offset + size, 0);
+        }
+        match data {
+            Some(data) => output[offset..offset + size].copy_from_slice(data),
+            None => output[offset..offset + size].fill(reader.read_u8()?),
+        };
+    }
+    if let Ok(size) = reader.read_be(3) {
+        output.truncate(size);
+    }
+    Ok(output)
+}
+
+// Checks the footer and returns the expected CRC32 of the source and target
+fn check_footer(source: &[u8], patch: &[u8]) -> Result<(u32, u32), PatchError> {
+    if patch.len() < FOOTER_SIZE + 4 {
+        return Err(PatchError::UnexpectedEnd);
+    }
+    let mut footer = PatchReader::new(patch, patch.len() - FOOTER_SIZE);
+    let source_crc = footer.read_le_u32()?;
+    let target_crc = footer.read_le_u32()?;
+    let patch_crc = footer.read_le_u32()?;
+    let actual = crc32(&patch[..patch.len() - 4]);
+    if actual != patch_crc {
+        return Err(PatchError::ChecksumMismatch { checksum: "patch", expected: patch_crc, actual });
+    }
+    let actual = crc32(source);
+    if actual != source_crc {
+        return Err(PatchError::ChecksumMismatch { checksum: "source ROM", expected: source_crc, actual });
+    }
+    Ok((source_crc, target_crc))
+}
+
+fn check_target_size(size: usize) -> Result<usize, PatchError> {
+    match size <= MAX_TARGET_SIZE {
+        true => Ok(size),
+        false => Err(PatchError::OutOfBounds),
+    }
+}
+
+fn check_target(target: &[u8], expected: u32) -> Result<(), PatchError> {
+    let actual = crc32(target);
+    match actual == expected {
+        true => Ok(()),
+        false => Err(PatchError::ChecksumMismatch { checksum: "patched ROM", expected, actual }),
+    }
+}
+
+// Hunks of a relative offset followed by bytes XORed with the source up to a 0
+fn apply_ups(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    let (_, target_crc) = check_footer(rom, patch)?;
+    let end = patch.len() - FOOTER_SIZE;
+    let mut reader = PatchReader::new(&patch[..end], UPS_MAGIC.len());
+    let _source_size = reader.read_number()?;
+    let target_size = check_target_size(reader.read_number()?)?;
+    let mut output = rom.to_vec();
+    output.resize(target_size, 0);
+
+    let mut offset: usize = 0;
+    while reader.index < end {
+        offset = offset.checked_add(reader.read_number()?).ok_or(PatchError::OutOfBounds)?;
+        loop {
+            let byte = reader.read_u8()?;
+            if let Some(output) = output.get_mut(offset) {
+                *output ^= byte;
+            }
+            offset = offset.checked_add(1).ok_or(PatchError::OutOfBounds)?;
+            if byte == 0 {
+                break;
+            }
+        }
+    }
+    check_target(&output, target_crc)?;
+    Ok(output)
+}
+
+const BPS_SOURCE_READ: usize = 0;
+const BPS_TARGET_READ: usize = 1;
+const BPS_SOURCE_COPY: usize = 2;
+const BPS_TARGET_COPY: usize = 3;
+
+// Signed BPS offsets keep the sign in the lowest bit
+fn apply_relative_offset(offset: usize, data: usize) -> Result<usize, PatchError> {
+    let result = match data & 1 {
+        0 => offset.checked_add(data >> 1),
+        _ => offset.checked_sub(data >> 1),
+    };
+    result.ok_or(PatchError::OutOfBounds)
+}
+
+// The target is built by a list of actions copying from the source, the patch
+// or the part of the target written so far
+fn apply_bps(rom: &[u8], patch: &[u8]) -> Result<Vec<u8>, PatchError> {
+    let (_, target_crc) = check_footer(rom, patch)?;
+    let end = patch.len() - FOOTER_SIZE;
+    let mut reader = PatchReader::new(&patch[..end], BPS_MAGIC.len());
+    let _source_size = reader.read_number()?;
+    let target_size = check_target_size(reader.read_number()?)?;
+    let metadata_size = reader.read_number()?;
+    reader.read_bytes(metadata_size)?;
+
+    let mut output = Vec::with_capacity(target_size);
+    let mut source_offset = 0;
+    let mut target_offset = 0;
+    while reader.index < end {
+        let action = reader.read_number()?;
+        let length = (action >> 2) + 1;
+        // Checked before writing anything, the lengths come straight from the patch
+        match output.len().checked_add(length) {
+            Some(size) if size <= target_size => {},
+            _ => return Err(PatchError::OutOfBounds),
+        };
+        match action & 3 {
+            BPS_SOURCE_READ => {
+                let start = output.len();
+                let end = start.checked_add(length).ok_or(PatchError::OutOfBounds)?;
+                let data = rom.get(start..end).ok_or(PatchError::OutOfBounds)?;
+                output.extend_from_slice(data);
+            },
+            BPS_TARGET_READ => output.extend_from_slice(reader.read_bytes(length)?),
+            BPS_SOURCE_COPY => {
+                source_offset = apply_relative_offset(source_offset, reader.read_number()?)?;
+                let end = source_offset.checked_add(length).ok_or(PatchError::OutOfBounds)?;
+                let data = rom.get(source_offset..end).ok_or(PatchError::OutOfBounds)?;
+                output.extend_from_slice(data);
+                source_offset += length;
+            },
+            // The copy can overlap the bytes it writes, so it goes one byte at a time
+            BPS_TARGET_COPY => {
+                target_offset = apply_relative_offset(target_offset, reader.read_number()?)?;
+                for _ in 0..length {
+                    let byte = *output.get(target_offset).ok_or(PatchError::OutOfBounds)?;
+                    output.push(byte);
+                    target_offset += 1;
+                }
+            },
+            _ => unreachable!(),
+        };
+    }
+    check_target(&output, target_crc)?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_number(data: &mut Vec<u8>, mut value: usize) {
+        loop {
+            let byte = (value & 0x7F) as u8;
+            value >>= 7;
+            if value == 0 {
+                data.push(0x80 | byte);
+                return;
+            }
+            data.push(byte);
+            value -= 1;
+        }
+    }
+
+    fn with_footer(mut patch: Vec<u8>, source: &[u8], target: &[u8]) -> Vec<u8> {
+        patch.extend_from_slice(&crc32(source).to_le_bytes());
+        patch.extend_from_slice(&crc32(target).to_le_bytes());
+        let crc = crc32(&patch);
+        patch.extend_from_slice(&crc.to_le_bytes());
+        patch
+    }
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+    }
+
+    #[test]
+    fn test_number_round_trip() {
+        for value in [0, 1, 0x7F, 0x80, 0x407F, 0x4080, 0x123456] {
+            let mut data = Vec::new();
+            write_number(&mut data, value);
+            assert_eq!(PatchReader::new(&data, 0).read_number().unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn test_ips() {
+        let rom = vec![0; 8];
+        let mut patch = IPS_MAGIC.to_vec();
+        // 2 bytes at offset 1, then a run of 3 0xAA going past the end
+        patch.extend_from_slice(&[0x00, 0x00, 0x01, 0x00, 0x02, 0x12, 0x34]);
+        patch.extend_from_slice(&[0x00, 0x00, 0x07, 0x00, 0x00, 0x00, 0x03, 0xAA]);
+        patch.extend_from_slice(IPS_EOF);
+        assert_eq!(apply_patch(&rom, &patch).unwrap(), vec![0, 0x12, 0x34, 0, 0, 0, 0, 0xAA, 0xAA, 0xAA]);
+
+        patch.extend_from_slice(&[0x00, 0x00, 0x04]);
+        assert_eq!(apply_patch(&rom, &patch).unwrap(), vec![0, 0x12, 0x34, 0]);
+
+        let truncated = &patch[..patch.len() - 8];
+        assert!(matches!(apply_patch(&rom, truncated), Err(PatchError::UnexpectedEnd)));
+    }
+
+    #[test]
+    fn test_ups() {
+        let source = b"Hello world".to_vec();
+        let target = b"Hello there, world".to_vec();
+        let mut patch = UPS_MAGIC.to_vec();
+        write_number(&mut patch, source.len());
+        write_number(&mut patch, target.len());
+        // Only the bytes that differ are stored, XORed with the source
+        let mut offset = 0;
+        let mut index = 0;
+        while index < target.len() {
+            let source_byte = |index: usize| *source.get(index).unwrap_or(&0);
+            if source_byte(index) == target[index] {
+                index += 1;
+                continue;
+            }
+            write_number(&mut patch, index - offset);
+            while index < target.len() && source_byte(index) != target[index] {
+                patch.push(source_byte(index) ^ target[index]);
+                index += 1;
+            }
+            patch.push(0);
+            index += 1;
+            offset = index;
+        }
+        let patch = with_footer(patch, &source, &target);
+        assert_eq!(apply_patch(&source, &patch).unwrap(), target);
+
+        assert!(matches!(
+            apply_patch(b"Hello World", &patch),
+            Err(PatchError::ChecksumMismatch { checksum: "source ROM", .. }),
+        ));
+        let mut corrupted = patch.clone();
+        corrupted[6] ^= 1;
+        assert!(matches!(
+            apply_patch(&source, &corrupted),
+            Err(PatchError::ChecksumMismatch { checksum: "patch", .. }),
+        ));
+    }
+
+    #[test]
+    fn test_bps() {
+        let source = b"abcdefgh".to_vec();
+        let target = b"abcXYXYXYfgh-de".to_vec();
+        let mut patch = BPS_MAGIC.to_vec();
+        write_number(&mut patch, source.len());
+        write_number(&mut patch, target.len());
+        write_number(&mut patch, 0);
+        // "abc" from the source
+        write_number(&mut patch, ((3 - 1) << 2) | BPS_SOURCE_READ);
+        // "XY" from the patch
+        write_number(&mut patch, ((2 - 1) << 2) | BPS_TARGET_READ);
+        patch.extend_from_slice(b"XY");
+        // "XYXY" overlapping the target starting at 3
+        write_number(&mut patch, ((4 - 1) << 2) | BPS_TARGET_COPY);
+        write_number(&mut patch, 3 << 1);
+        // "fgh" from the source at 5
+        write_number(&mut patch, ((3 - 1) << 2) | BPS_SOURCE_COPY);
+        write_number(&mut patch, 5 << 1);
+        // A single "-" from the patch
+        write_number(&mut patch, BPS_TARGET_READ);
+        patch.push(b'-');
+        // "de" going back from 8 to 3
+        write_number(&mut patch, ((2 - 1) << 2) | BPS_SOURCE_COPY);
+        write_number(&mut patch, (5 << 1) | 1);
+        let patch = with_footer(patch, &source, &target);
+        assert_eq!(apply_patch(&source, &patch).unwrap(), target);
+
+        let wrong_target = with_footer(patch[..patch.len() - FOOTER_SIZE].to_vec(), &source, b"something else");
+        assert!(matches!(
+            apply_patch(&source, &wrong_target),
+            Err(PatchError::ChecksumMismatch { checksum: "patched ROM", .. }),
+        ));
+    }
+
+    #[test]
+    fn test_oversized_target() {
+        let source = b"abcdefgh".to_vec();
+        let mut bps = BPS_MAGIC.to_vec();
+        write_number(&mut bps, source.len());
+        write_number(&mut bps, usize::MAX);
+        let bps = with_footer(bps, &source, &source);
+        assert!(matches!(apply_patch(&source, &bps), Err(PatchError::OutOfBounds)));
+
+        let mut ups = UPS_MAGIC.to_vec();
+        write_number(&mut ups, source.len());
+        write_number(&mut ups, MAX_TARGET_SIZE + 1);
+        let ups = with_footer(ups, &source, &source);
+        assert!(matches!(apply_patch(&source, &ups), Err(PatchError::OutOfBounds)));
+
+        // A target copy far longer than the declared target
+        let mut bps = BPS_MAGIC.to_vec();
+        write_number(&mut bps, source.len());
+        write_number(&mut bps, source.len());
+        write_number(&mut bps, 0);
+        write_number(&mut bps, (source.len() - 1) << 2 | BPS_SOURCE_READ);
+        write_number(&mut bps, ((1 << 30) - 1) << 2 | BPS_TARGET_COPY);
+        write_number(&mut bps, 0);
+        let bps = with_footer(bps, &source, &source);
+        assert!(matches!(apply_patch(&source, &bps), Err(PatchError::OutOfBounds)));
+
+        // A UPS hunk right before the end of the address space
+        let mut ups = UPS_MAGIC.to_vec();
+        write_number(&mut ups, source.len());
+        write_number(&mut ups, source.len());
+        write_number(&mut ups, usize::MAX);
+        ups.extend_from_slice(&[0x01, 0x00]);
+        let ups = with_footer(ups, &source, &source);
+        assert!(matches!(apply_patch(&source, &ups), Err(PatchError::OutOfBounds)));
+
+        // A metadata size that would overflow the read position
+        let mut bps = BPS_MAGIC.to_vec();
+        write_number(&mut bps, source.len());
+        write_number(&mut bps, source.len());
+        write_number(&mut bps, usize::MAX);
+        let bps = with_footer(bps, &source, &source);
+        assert!(matches!(apply_patch(&source, &bps), Err(PatchError::UnexpectedEnd)));
+    }
+
+    #[test]
+    fn test_unknown_format() {
+        assert!(matches!(apply_patch(&[0; 4], b"NOTAPATCH"), Err(PatchError::UnknownFormat)));
+    }
+}
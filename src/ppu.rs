@@ -5,8 +5,9 @@ use crate::utils::{
     join_bytes,
 };
 use crate::bus::SPRITE_ATTRIBUTE_TABLE;
-use crate::cpu::Cycles;
+use crate::cpu::TCycles;
 use crate::interrupts::{Interrupts, Interrupt};
+use crate::savestate::{SaveState, SaveStateError, StateReader, StateWriter};
 
 pub const LCD_WIDTH: u32 = 160;
 pub const LCD_HEIGHT: u32 = 144;
@@ -14,6 +15,13 @@ pub const WIDTH: u32 = LCD_WIDTH;
 pub const HEIGHT: u32 = LCD_HEIGHT;
 pub const FRAME_BUFFER_LENGTH: u32 = WIDTH * HEIGHT;
 
+// Dots into the scanline at which every mode starts
+const MODE_3_START: u32 = 80;
+const MODE_0_START: u32 = 80 + 172;
+const LINE_CYCLES: u32 = 456;
+// Dots in a whole frame, including the vertical blank lines
+pub const FRAME_CYCLES: u32 = LINE_CYCLES * 154;
+
 pub const LCD_CONTROL_ADDRESS: u16 = 0xFF40;
 pub const LCD_STATUS_ADDRESS: u16 = 0xFF41;
 
@@ -274,13 +282,78 @@ impl Sprite {
     }
 }
 
+impl Sprite {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.x);
+        state.write_u8(self.y);
+        state.write_u8(self.tile_number);
+        state.write_u8(self.palette);
+        state.write_bool(self.palette_zero);
+        state.write_bool(self.x_flip);
+        state.write_bool(self.y_flip);
+        state.write_bool(self.over_bg);
+        match self.bit_pixels {
+            Some(bit_pixels) => {
+                state.write_bool(true);
+                state.write_bytes(&bit_pixels);
+            },
+            None => state.write_bool(false),
+        };
+        state.write_u8(self.vram_bank);
+        state.write_u8(self.palette_number);
+    }
+
+    fn load_state(state: &mut StateReader) -> Result<Self, SaveStateError> {
+        Ok(Self {
+            x: state.read_u8()?,
+            y: state.read_u8()?,
+            tile_number: state.read_u8()?,
+            palette: state.read_u8()?,
+            palette_zero: state.read_bool()?,
+            x_flip: state.read_bool()?,
+            y_flip: state.read_bool()?,
+            over_bg: state.read_bool()?,
+            bit_pixels: match state.read_bool()? {
+                true => {
+                    let mut bit_pixels = [0; 8];
+                    state.read_bytes_into(&mut bit_pixels)?;
+                    Some(bit_pixels)
+                },
+                false => None,
+            },
+            vram_bank: state.read_u8()?,
+            palette_number: state.read_u8()?,
+        })
+    }
+}
+
+fn save_tile_pixels(state: &mut StateWriter, pixels: Option<([u8; 8], u8)>) {
+    match pixels {
+        Some((bit_pixels, palette_number)) => {
+            state.write_bool(true);
+            state.write_bytes(&bit_pixels);
+            state.write_u8(palette_number);
+        },
+        None => state.write_bool(false),
+    };
+}
+
+fn load_tile_pixels(state: &mut StateReader) -> Result<Option<([u8; 8], u8)>, SaveStateError> {
+    if !state.read_bool()? {
+        return Ok(None);
+    }
+    let mut bit_pixels = [0; 8];
+    state.read_bytes_into(&mut bit_pixels)?;
+    Ok(Some((bit_pixels, state.read_u8()?)))
+}
+
 pub struct PPU {
     state: bool,
     background_priority: bool,
     window_enable: bool,
     lcd_enable: bool,
     window_drawn: bool,
-    cycles: Cycles,
+    cycles: TCycles,
     sprite_buffer: Vec<Sprite>,
     window_y_counter: u8,
     last_bg_index: u8,
@@ -306,6 +379,8 @@ pub struct PPU {
     hdma_destination: u16,
     hdma_start: u8,
     cgb_mode: bool,
+    frame_buffer: Vec<u8>,
+    last_sync: u64,
 }
 
 impl PPU {
@@ -316,7 +391,7 @@ impl PPU {
             window_enable: false,
             window_drawn: false,
             lcd_enable: false,
-            cycles: Cycles(0.0),
+            cycles: TCycles(0),
             sprite_buffer: Vec::new(),
             window_y_counter: 0,
             last_bg_index: 0,
@@ -342,6 +417,8 @@ impl PPU {
             hdma_destination: 0,
             hdma_start: 0,
             cgb_mode,
+            frame_buffer: vec![0; (LCD_WIDTH * LCD_HEIGHT * 4) as usize],
+            last_sync: 0,
         }
     }
 
@@ -349,6 +426,10 @@ impl PPU {
         self.lcd_y
     }
 
+    pub fn lcd_enabled(&self) -> bool {
+        self.lcd_enable
+    }
+
     pub fn set_vram_bank(&mut self, bank: u8) {
         if self.cgb_mode {
             self.vram_bank = bank & 1;
@@ -478,33 +559,73 @@ impl PPU {
     }
 
     pub fn reset_cycles(&mut self) {
-        self.cycles.0 = 0.0;
+        self.cycles.0 = 0;
     }
 
-    pub fn increment_cycles(&mut self, cycles: Cycles) {
+    pub fn increment_cycles(&mut self, cycles: TCycles) {
         self.cycles.0 += cycles.0;
     }
 
-    pub fn do_cycles(&mut self, interrupts: &mut Interrupts, cycles: Cycles, frame_buffer: &mut [u8]) {
+    pub fn frame_buffer(&self) -> &[u8] {
+        &self.frame_buffer
+    }
+
+    // Runs the PPU up to the master clock time `now`
+    pub fn catch_up(&mut self, interrupts: &mut Interrupts, now: u64) {
+        while now > self.last_sync {
+            let cycles = (now - self.last_sync).min(u32::MAX as u64);
+            self.do_cycles(interrupts, TCycles(cycles as u32));
+            self.last_sync += cycles;
+        }
+    }
+
+    // Master clock time of the next mode transition, nothing happens while the LCD is off
+    pub fn next_transition(&self) -> Option<u64> {
         if !self.lcd_enable {
-            self.increment_cycles(cycles);
+            return None;
+        }
+        Some(self.last_sync + self.cycles_to_transition() as u64)
+    }
+
+    fn cycles_to_transition(&self) -> u32 {
+        if self.lcd_y >= 144 || self.cycles.0 >= MODE_0_START {
+            return LINE_CYCLES - self.cycles.0;
+        } else if self.cycles.0 >= MODE_3_START {
+            return MODE_0_START - self.cycles.0;
+        }
+        MODE_3_START - self.cycles.0
+    }
+
+    pub fn do_cycles(&mut self, interrupts: &mut Interrupts, cycles: TCycles) {
+        if !self.lcd_enable {
+            self.reset_cycles();
             return;
         }
 
+        // Split the work at every mode transition so each step stays inside a single mode
+        let mut remaining = cycles.0;
+        while remaining > 0 {
+            let step = remaining.min(self.cycles_to_transition());
+            self.step(interrupts, TCycles(step));
+            remaining -= step;
+        }
+    }
+
+    fn step(&mut self, interrupts: &mut Interrupts, cycles: TCycles) {
         if self.lcd_y < 144 {
-            if self.cycles.0 <= 80.0 && !self.get_lcd_status(LCDStatus::ModeFlag(LCDStatusModeFlag::SearchingOAM)) {
+            if self.cycles.0 < MODE_3_START && !self.get_lcd_status(LCDStatus::ModeFlag(LCDStatusModeFlag::SearchingOAM)) {
                 // Mode 2 OAM scan
                 self.set_lcd_status(LCDStatus::ModeFlag(LCDStatusModeFlag::SearchingOAM), true);
                 self.stat_interrupt(interrupts);
                 self.oam_search();
-            } else if self.cycles.0 > 80.0 && self.cycles.0 <= 80.0 + 172.0 {
+            } else if self.cycles.0 >= MODE_3_START && self.cycles.0 < MODE_0_START {
                 // Mode 3 drawing pixel line. This could also last 289 cycles
                 if !self.get_lcd_status(LCDStatus::ModeFlag(LCDStatusModeFlag::TransferringToLCD)) {
                     self.window_drawn = false;
                     self.set_lcd_status(LCDStatus::ModeFlag(LCDStatusModeFlag::TransferringToLCD), true);
                 }
-                self.draw_line(cycles, frame_buffer);
-            } else if self.cycles.0 > 80.0 + 172.0 && self.cycles.0 <= 80.0 + 172.0 + 204.0 && !self.get_lcd_status(LCDStatus::ModeFlag(LCDStatusModeFlag::HBlank)) {
+                self.draw_line(cycles);
+            } else if self.cycles.0 >= MODE_0_START && !self.get_lcd_status(LCDStatus::ModeFlag(LCDStatusModeFlag::HBlank)) {
                 // Mode 0 Horizontal blank. This could last 87 or 204 cycles depending on the mode 3
                 self.set_lcd_status(LCDStatus::ModeFlag(LCDStatusModeFlag::HBlank), true);
                 self.stat_interrupt(interrupts);
@@ -519,7 +640,7 @@ impl PPU {
         self.increment_cycles(cycles);
 
         // Horizontal scan completed
-        if self.cycles.0 > 456.0 {
+        if self.cycles.0 >= LINE_CYCLES {
             self.reset_cycles();
 
             self.lcd_y = self.lcd_y.wrapping_add(1);
@@ -838,7 +959,7 @@ impl PPU {
         Some((PPU::get_pixel(bit_pixel), palette_number))
     }
 
-    fn draw_line(&mut self, cycles: Cycles, frame_buffer: &mut [u8]) {
+    fn draw_line(&mut self, cycles: TCycles) {
         if self.lcd_y as u32 >= LCD_HEIGHT {
             return;
         }
@@ -851,7 +972,7 @@ impl PPU {
         self.current_background_pixels = None;
         self.current_window_pixels = None;
         self.bg_palette = self.get_register(BACKGROUND_PALETTE_ADDRESS);
-        let mut count = 0.0;
+        let mut count = 0;
         while count < cycles.0 && (self.lcd_x as u32) < LCD_WIDTH {
             let idx = (self.lcd_x as usize + (self.lcd_y as usize * LCD_WIDTH as usize)) * 4;
 
@@ -862,18 +983,18 @@ impl PPU {
                     false => WINDOW_COLORS,
                 };
                 let rgba = PPU::get_rgba(window_pixel, colors);
-                frame_buffer[idx]     = rgba[0];
-                frame_buffer[idx + 1] = rgba[1];
-                frame_buffer[idx + 2] = rgba[2];
+                self.frame_buffer[idx]     = rgba[0];
+                self.frame_buffer[idx + 1] = rgba[1];
+                self.frame_buffer[idx + 2] = rgba[2];
             } else if let Some((background_pixel, palette_number)) = self.get_background_pixel() {
                 let colors = match self.cgb_mode {
                     true => ColorPalette::new_cgb(&self.bg_cram, palette_number),
                     false => BACKGROUND_COLORS,
                 };
                 let rgba = PPU::get_rgba(background_pixel, colors);
-                frame_buffer[idx]     = rgba[0];
-                frame_buffer[idx + 1] = rgba[1];
-                frame_buffer[idx + 2] = rgba[2];
+                self.frame_buffer[idx]     = rgba[0];
+                self.frame_buffer[idx + 1] = rgba[1];
+                self.frame_buffer[idx + 2] = rgba[2];
             }
             if self.get_lcd_control(LCDControl::ObjectEnable) {
                 if let Some((sprite_pixel, palette_zero, palette_number)) = self.find_sprite_pixel() {
@@ -885,14 +1006,14 @@ impl PPU {
                         },
                     };
                     let rgba = PPU::get_rgba(sprite_pixel, colors);
-                    frame_buffer[idx]     = rgba[0];
-                    frame_buffer[idx + 1] = rgba[1];
-                    frame_buffer[idx + 2] = rgba[2];
+                    self.frame_buffer[idx]     = rgba[0];
+                    self.frame_buffer[idx + 1] = rgba[1];
+                    self.frame_buffer[idx + 2] = rgba[2];
                 }
             }
 
             self.lcd_x += 1;
-            count += 1.0;
+            count += 1;
         }
     }
 
@@ -938,3 +1059,84 @@ impl PPU {
         ]
     }
 }
+
+impl SaveState for PPU {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bool(self.state);
+        state.write_bool(self.background_priority);
+        state.write_bool(self.window_enable);
+        state.write_bool(self.lcd_enable);
+        state.write_bool(self.window_drawn);
+        state.write_u32(self.cycles.0);
+        state.write_u8(self.sprite_buffer.len() as u8);
+        for sprite in &self.sprite_buffer {
+            sprite.save_state(state);
+        }
+        state.write_u8(self.window_y_counter);
+        state.write_u8(self.last_bg_index);
+        state.write_bool(self.last_bg_priority);
+        state.write_u8(self.bg_palette);
+        state.write_u8(self.lcd_control);
+        save_tile_pixels(state, self.current_background_pixels);
+        save_tile_pixels(state, self.current_window_pixels);
+        state.write_u8(self.lcd_y);
+        state.write_u8(self.lcd_x);
+        state.write_u8(self.scroll_x);
+        state.write_u8(self.scroll_y);
+        state.write_u8(self.window_x);
+        state.write_u8(self.window_y);
+        state.write_bytes(&self.io_registers);
+        state.write_bytes(&self.cram_registers);
+        state.write_bytes(&self.vram);
+        state.write_bytes(&self.bg_cram);
+        state.write_bytes(&self.obj_cram);
+        state.write_bytes(&self.oam);
+        state.write_u8(self.vram_bank);
+        state.write_u16(self.hdma_source);
+        state.write_u16(self.hdma_destination);
+        state.write_u8(self.hdma_start);
+        state.write_u64(self.last_sync);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.state = state.read_bool()?;
+        self.background_priority = state.read_bool()?;
+        self.window_enable = state.read_bool()?;
+        self.lcd_enable = state.read_bool()?;
+        self.window_drawn = state.read_bool()?;
+        self.cycles = TCycles(state.read_u32()?);
+        let sprites = state.read_u8()?;
+        if sprites > 10 {
+            return Err(SaveStateError::InvalidData("sprite buffer"));
+        }
+        self.sprite_buffer = Vec::new();
+        for _ in 0..sprites {
+            self.sprite_buffer.push(Sprite::load_state(state)?);
+        }
+        self.window_y_counter = state.read_u8()?;
+        self.last_bg_index = state.read_u8()?;
+        self.last_bg_priority = state.read_bool()?;
+        self.bg_palette = state.read_u8()?;
+        self.lcd_control = state.read_u8()?;
+        self.current_background_pixels = load_tile_pixels(state)?;
+        self.current_window_pixels = load_tile_pixels(state)?;
+        self.lcd_y = state.read_u8()?;
+        self.lcd_x = state.read_u8()?;
+        self.scroll_x = state.read_u8()?;
+        self.scroll_y = state.read_u8()?;
+        self.window_x = state.read_u8()?;
+        self.window_y = state.read_u8()?;
+        state.read_bytes_into(&mut self.io_registers)?;
+        state.read_bytes_into(&mut self.cram_registers)?;
+        state.read_bytes_into(&mut self.vram)?;
+        state.read_bytes_into(&mut self.bg_cram)?;
+        state.read_bytes_into(&mut self.obj_cram)?;
+        state.read_bytes_into(&mut self.oam)?;
+        self.vram_bank = state.read_u8()? & 1;
+        self.hdma_source = state.read_u16()?;
+        self.hdma_destination = state.read_u16()?;
+        self.hdma_start = state.read_u8()?;
+        self.last_sync = state.read_u64()?;
+        Ok(())
+    }
+}
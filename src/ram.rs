@@ -1,8 +1,9 @@
 use crate::bus::{ECHO_RAM, WORK_RAM_1};
+use crate::savestate::{SaveState, SaveStateError, StateReader, StateWriter};
 
 pub const WRAM_BANK_SELECT_ADDRESS: u16 = 0xFF70;
 
-pub trait RAM {
+pub trait RAM: SaveState {
     fn read(&self, address: u16) -> u8;
     fn write(&mut self, address: u16, value: u8);
 }
@@ -45,6 +46,15 @@ impl RAM for DMGRAM {
     }
 }
 
+impl SaveState for DMGRAM {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.data);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        state.read_bytes_into(&mut self.data)
+    }
+}
 
 pub struct CGBRAM {
     data: [u8; 4096 * 8],
@@ -93,3 +103,16 @@ impl RAM for CGBRAM {
         self.data[((address - 0xD000) as usize) + (4096 * (self.bank as usize))] = value;
     }
 }
+
+impl SaveState for CGBRAM {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.data);
+        state.write_u8(self.bank);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        state.read_bytes_into(&mut self.data)?;
+        self.switch_bank(state.read_u8()?);
+        Ok(())
+    }
+}
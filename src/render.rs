@@ -1,8 +1,12 @@
 use crate::emulator::Emulator;
 use crate::frames::Frames;
 use crate::ppu::{WIDTH, HEIGHT};
+use crate::config::FramePacing;
+use crate::joypad::Button;
+
+use std::error::Error;
+use std::fs;
 
-use std::env;
 use log::error;
 use pixels::{wgpu, Pixels, PixelsBuilder, SurfaceTexture};
 use winit::dpi::LogicalSize;
@@ -11,11 +15,57 @@ use winit::event_loop::{ControlFlow, EventLoop};
 use winit::window::{Window, WindowBuilder};
 use winit_input_helper::WinitInputHelper;
 
-fn is_fps_unlocked() -> bool {
-    !env::var("UNLOCK_FPS").is_err()
+const KEY_MAP: [(VirtualKeyCode, Button); 8] = [
+    (VirtualKeyCode::K, Button::A),
+    (VirtualKeyCode::J, Button::B),
+    (VirtualKeyCode::W, Button::Up),
+    (VirtualKeyCode::S, Button::Down),
+    (VirtualKeyCode::A, Button::Left),
+    (VirtualKeyCode::D, Button::Right),
+    (VirtualKeyCode::N, Button::Start),
+    (VirtualKeyCode::B, Button::Select),
+];
+
+fn handle_input(emulator: &mut Emulator, input: &WinitInputHelper) {
+    for (key, button) in KEY_MAP {
+        if input.key_pressed(key) {
+            emulator.press_button(button);
+        }
+        if input.key_released(key) {
+            emulator.release_button(button);
+        }
+    }
+    // The arrow keys tilt cartridges that have an accelerometer
+    let axis = |negative, positive| (input.key_held(positive) as i8 - input.key_held(negative) as i8) as f32;
+    emulator.set_tilt(
+        axis(VirtualKeyCode::Left, VirtualKeyCode::Right),
+        axis(VirtualKeyCode::Up, VirtualKeyCode::Down),
+    );
 }
 
-pub fn create_pixels(width: u32, height: u32, window: &Window) -> Pixels {
+// Game name from the cartridge header, when it has one
+fn window_title(emulator: &Emulator) -> String {
+    match emulator.rom_info().title() {
+        "" => "rmg-001".to_string(),
+        title => format!("{} - rmg-001", title),
+    }
+}
+
+fn state_filename(emulator: &Emulator) -> String {
+    format!("{}.state", emulator.rom_info().filename())
+}
+
+fn save_state_file(emulator: &Emulator) -> std::io::Result<()> {
+    fs::write(state_filename(emulator), emulator.save_state())
+}
+
+fn load_state_file(emulator: &mut Emulator) -> Result<(), Box<dyn Error>> {
+    let data = fs::read(state_filename(emulator))?;
+    emulator.load_state(&data)?;
+    Ok(())
+}
+
+pub fn create_pixels(width: u32, height: u32, vsync: bool, window: &Window) -> Pixels {
     let window_size = window.inner_size();
     let surface_texture = SurfaceTexture::new(window_size.width, window_size.height, window);
     PixelsBuilder::new(width, height, surface_texture)
@@ -28,7 +78,7 @@ pub fn create_pixels(width: u32, height: u32, window: &Window) -> Pixels {
             },
             ..wgpu::DeviceDescriptor::default()
         })
-        .enable_vsync(!is_fps_unlocked())
+        .enable_vsync(vsync)
         .build()
         .unwrap()
 }
@@ -43,8 +93,8 @@ pub fn create_window<T>(width: u32, height: u32, title: String, event_loop: &Eve
         .unwrap()
 }
 
-pub fn start_eventloop() {
-    let mut emulator = Emulator::new();
+pub fn start_eventloop(mut emulator: Emulator) {
+    let fps_limited = emulator.config().frame_pacing() == FramePacing::Limited;
     let mut frame_counter = Frames::new();
     let mut frame_limit = Frames::new();
 
@@ -52,8 +102,10 @@ pub fn start_eventloop() {
     let event_loop = EventLoop::new();
     let mut input = WinitInputHelper::new();
 
-    let window = create_window(WIDTH, HEIGHT, "rmg-001".to_string(), &event_loop);
-    let mut pixels = create_pixels(WIDTH, HEIGHT, &window);
+    let title = window_title(&emulator);
+    let window = create_window(WIDTH, HEIGHT, title.clone(), &event_loop);
+    let mut pixels = create_pixels(WIDTH, HEIGHT, fps_limited, &window);
+    let mut rewinding = false;
 
     event_loop.run(move |event, _, control_flow| {
         // *control_flow = ControlFlow::Wait;
@@ -67,7 +119,23 @@ pub fn start_eventloop() {
                 return;
             }
 
-            emulator.handle_input(&input);
+            if input.key_pressed(VirtualKeyCode::F5) {
+                match save_state_file(&emulator) {
+                    Ok(_) => println!("State saved"),
+                    Err(err) => eprintln!("Could not save state: {}", err),
+                };
+            }
+            if input.key_pressed(VirtualKeyCode::F7) {
+                match load_state_file(&mut emulator) {
+                    Ok(_) => println!("State loaded"),
+                    Err(err) => eprintln!("Could not load state: {}", err),
+                };
+            }
+
+            // Hold R to rewind
+            rewinding = input.key_held(VirtualKeyCode::R);
+
+            handle_input(&mut emulator, &input);
 
             // Resize the window
             if let Some(size) = input.window_resized() {
@@ -84,15 +152,18 @@ pub fn start_eventloop() {
                 *control_flow = ControlFlow::Exit
             },
             Event::MainEventsCleared => {
+                if rewinding {
+                    emulator.rewind(1);
+                }
                 emulator.run_frame(pixels.get_frame());
                 frame_counter.increment();
                 if frame_counter.elapsed_ms() >= 1000 {
-                    window.set_title(&format!("rmg-001 (FPS: {})", frame_counter.count()));
+                    window.set_title(&format!("{} (FPS: {})", title, frame_counter.count()));
                     frame_counter.reset_count();
                     frame_counter.reset_timer();
                 }
                 window.request_redraw();
-                if !is_fps_unlocked() {
+                if fps_limited {
                     frame_limit.limit();
                 }
                 frame_limit.reset_timer();
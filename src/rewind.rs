@@ -0,0 +1,217 @@
+use std::collections::VecDeque;
+
+// Snapshots are stored as a chain of deltas going backwards in time.
+// `latest` holds the most recent snapshot and every delta rebuilds the
+// previous snapshot from the one that follows it, so dropping the oldest
+// delta never invalidates the rest of the chain.
+pub struct Rewind {
+    interval: usize,
+    capacity: usize,
+    frame_counter: usize,
+    latest: Option<Vec<u8>>,
+    deltas: VecDeque<Vec<u8>>,
+}
+
+impl Rewind {
+    pub fn new(interval: usize, capacity: usize) -> Self {
+        Self {
+            interval: interval.max(1),
+            capacity: capacity.max(1),
+            frame_counter: 0,
+            latest: None,
+            deltas: VecDeque::new(),
+        }
+    }
+
+    // Amount of snapshots currently stored
+    pub fn len(&self) -> usize {
+        match self.latest {
+            Some(_) => self.deltas.len() + 1,
+            None => 0,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.latest.is_none()
+    }
+
+    // Returns true when a snapshot should be taken for the frame that just finished
+    pub fn frame_completed(&mut self) -> bool {
+        self.frame_counter += 1;
+        if self.frame_counter >= self.interval {
+            self.frame_counter = 0;
+            return true;
+        }
+        false
+    }
+
+    pub fn push(&mut self, snapshot: Vec<u8>) {
+        if let Some(latest) = self.latest.take() {
+            self.deltas.push_back(encode_delta(&snapshot, &latest));
+            if self.deltas.len() >= self.capacity {
+                self.deltas.pop_front();
+            }
+        }
+        self.latest = Some(snapshot);
+    }
+
+    // Removes the snapshots covering the last `frames` frames and returns the oldest of them
+    pub fn rewind(&mut self, frames: usize) -> Option<Vec<u8>> {
+        let steps = frames.div_ceil(self.interval).max(1);
+        let mut snapshot = None;
+        for _ in 0..steps {
+            let latest = match self.latest.take() {
+                Some(latest) => latest,
+                None => break,
+            };
+            self.latest = self.deltas.pop_back().map(|delta| decode_delta(&latest, &delta));
+            snapshot = Some(latest);
+        }
+        self.frame_counter = 0;
+        snapshot
+    }
+
+    pub fn clear(&mut self) {
+        self.frame_counter = 0;
+        self.latest = None;
+        self.deltas.clear();
+    }
+}
+
+fn write_varint(data: &mut Vec<u8>, value: usize) {
+    let mut value = value;
+    while value >= 0x80 {
+        data.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    data.push(value as u8);
+}
+
+fn read_varint(data: &[u8], index: &mut usize) -> usize {
+    let mut value = 0;
+    let mut shift = 0;
+    while let Some(byte) = data.get(*index) {
+        *index += 1;
+        value |= ((byte & 0x7F) as usize) << shift;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        shift += 7;
+    }
+    value
+}
+
+// The delta is the XOR of both snapshots, run length encoded as
+// pairs of (unchanged bytes, changed bytes) followed by the changed bytes
+pub fn encode_delta(base: &[u8], target: &[u8]) -> Vec<u8> {
+    let mut data = Vec::new();
+    write_varint(&mut data, target.len());
+    let xor = |index: usize| target[index] ^ base.get(index).copied().unwrap_or(0);
+    let mut index = 0;
+    while index < target.len() {
+        let start = index;
+        while index < target.len() && xor(index) == 0 {
+            index += 1;
+        }
+        let unchanged = index - start;
+        let start = index;
+        while index < target.len() && xor(index) != 0 {
+            index += 1;
+        }
+        // Trailing unchanged bytes don't need to be stored
+        if index == start {
+            break;
+        }
+        write_varint(&mut data, unchanged);
+        write_varint(&mut data, index - start);
+        for i in start..index {
+            data.push(xor(i));
+        }
+    }
+    data
+}
+
+pub fn decode_delta(base: &[u8], delta: &[u8]) -> Vec<u8> {
+    let mut index = 0;
+    let length = read_varint(delta, &mut index);
+    let mut target = base.to_vec();
+    target.resize(length, 0);
+    let mut position = 0;
+    while index < delta.len() {
+        position += read_varint(delta, &mut index);
+        let changed = read_varint(delta, &mut index);
+        for _ in 0..changed {
+            if let (Some(byte), Some(xor)) = (target.get_mut(position), delta.get(index)) {
+                *byte ^= xor;
+            }
+            position += 1;
+            index += 1;
+        }
+    }
+    target
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_delta_round_trip() {
+        let base = vec![1, 2, 3, 4, 5, 6, 7, 8];
+        let target = vec![1, 2, 9, 4, 5, 0, 0, 8, 10, 11];
+        let delta = encode_delta(&base, &target);
+        assert_eq!(decode_delta(&base, &delta), target);
+
+        let delta = encode_delta(&target, &base);
+        assert_eq!(decode_delta(&target, &delta), base);
+
+        let delta = encode_delta(&base, &base);
+        assert_eq!(delta.len(), 1);
+        assert_eq!(decode_delta(&base, &delta), base);
+    }
+
+    #[test]
+    fn test_delta_compression() {
+        let base = vec![0; 0x10000];
+        let mut target = base.clone();
+        target[0x1234] = 0xFF;
+        target[0x8000] = 0x01;
+        let delta = encode_delta(&base, &target);
+        assert!(delta.len() < 16);
+        assert_eq!(decode_delta(&base, &delta), target);
+    }
+
+    #[test]
+    fn test_rewind_order() {
+        let mut rewind = Rewind::new(2, 10);
+        for i in 0..5 {
+            rewind.push(vec![i; 4]);
+        }
+        assert_eq!(rewind.len(), 5);
+        assert_eq!(rewind.rewind(1), Some(vec![4; 4]));
+        assert_eq!(rewind.rewind(4), Some(vec![2; 4]));
+        assert_eq!(rewind.len(), 2);
+        assert_eq!(rewind.rewind(100), Some(vec![0; 4]));
+        assert!(rewind.is_empty());
+        assert_eq!(rewind.rewind(1), None);
+    }
+
+    #[test]
+    fn test_rewind_capacity() {
+        let mut rewind = Rewind::new(1, 3);
+        for i in 0..10 {
+            rewind.push(vec![i; 4]);
+        }
+        assert_eq!(rewind.len(), 3);
+        assert_eq!(rewind.rewind(3), Some(vec![7; 4]));
+    }
+
+    #[test]
+    fn test_frame_interval() {
+        let mut rewind = Rewind::new(3, 10);
+        assert!(!rewind.frame_completed());
+        assert!(!rewind.frame_completed());
+        assert!(rewind.frame_completed());
+        assert!(!rewind.frame_completed());
+    }
+}
@@ -1,109 +1,300 @@
-#[cfg(not(test))]
+use std::fmt;
 use std::fs::File;
-#[cfg(not(test))]
 use std::io::Read;
-#[cfg(not(test))]
 use std::io::Write;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use log::warn;
 
 use crate::bus::{
     BANK_ZERO,
     BANK_SWITCHABLE,
     EXTERNAL_RAM,
 };
-
+use crate::savestate::{SaveState, SaveStateError, StateReader, StateWriter};
+use crate::infrared::{InfraredPeer, InfraredPort};
+use crate::camera::{M64282FP, CAMERA_IMAGE_SIZE};
+use crate::licensee::{new_licensee_name, old_licensee_name, USE_NEW_LICENSEE_CODE};
+use crate::patch::{apply_patch, PatchError, PATCH_EXTENSIONS};
+
+pub const TITLE_ADDRESS: u16 = 0x0134;
+pub const MANUFACTURER_CODE_ADDRESS: u16 = 0x013F;
+pub const NEW_LICENSEE_CODE_ADDRESS: u16 = 0x0144;
 pub const CARTRIDGE_TYPE_ADDRESS: u16 = 0x0147;
 pub const CGB_FLAG_ADDRESS: u16 = 0x0143;
 pub const SGB_FLAG_ADDRESS: u16 = 0x0146;
 pub const RAM_SIZE_ADDRESS: u16 = 0x0149;
 pub const ROM_SIZE_ADDRESS: u16 = 0x0148;
 pub const DESTINATION_CODE_ADDRESS: u16 = 0x014A;
+pub const OLD_LICENSEE_CODE_ADDRESS: u16 = 0x014B;
+pub const VERSION_ADDRESS: u16 = 0x014C;
 pub const HEADER_CHECKSUM_ADDRESS: u16 = 0x014D;
+pub const GLOBAL_CHECKSUM_ADDRESS: u16 = 0x014E;
+pub const HEADER_END_ADDRESS: u16 = 0x014F;
+pub const NINTENDO_LOGO_ADDRESS: u16 = 0x0104;
+
+pub const NINTENDO_LOGO: [u8; 48] = [
+    0xCE, 0xED, 0x66, 0x66, 0xCC, 0x0D, 0x00, 0x0B, 0x03, 0x73, 0x00, 0x83, 0x00, 0x0C, 0x00, 0x0D,
+    0x00, 0x08, 0x11, 0x1F, 0x88, 0x89, 0x00, 0x0E, 0xDC, 0xCC, 0x6E, 0xE6, 0xDD, 0xDD, 0xD9, 0x99,
+    0xBB, 0xBB, 0x67, 0x63, 0x6E, 0x0E, 0xEC, 0xCC, 0xDD, 0xDC, 0x99, 0x9F, 0xBB, 0xB9, 0x33, 0x3E,
+];
+
+#[derive(Debug)]
+pub enum LoadError {
+    Io(std::io::Error),
+    // The image is smaller than the header
+    Truncated { expected: usize, actual: usize },
+    // A header field contains a value that no cartridge uses
+    BadHeader { field: &'static str, value: u8 },
+    ChecksumMismatch { expected: u8, actual: u8 },
+    Patch(PatchError),
+}
 
-#[cfg(not(test))]
-fn header_checksum(data: &Vec<u8>) -> bool {
-    if data.len() < HEADER_CHECKSUM_ADDRESS as usize {
-        return false;
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(err) => write!(f, "{}", err),
+            LoadError::Truncated { expected, actual } => write!(f, "ROM is truncated: expected {} bytes, found {}", expected, actual),
+            LoadError::BadHeader { field, value } => write!(f, "Invalid {} in the ROM header: 0x{:02X}", field, value),
+            LoadError::ChecksumMismatch { expected, actual } => write!(
+                f,
+                "Header checksum failed (expected 0x{:02X}, found 0x{:02X}). Is this a Gameboy ROM?",
+                expected,
+                actual,
+            ),
+            LoadError::Patch(err) => write!(f, "Could not apply the patch: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for LoadError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            LoadError::Io(err) => Some(err),
+            LoadError::Patch(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for LoadError {
+    fn from(err: std::io::Error) -> Self {
+        LoadError::Io(err)
     }
+}
 
+fn header_checksum(data: &[u8]) -> u8 {
     let mut checksum: u8 = 0;
     let mut index: u16 = 0x0134;
     while index < HEADER_CHECKSUM_ADDRESS {
         checksum = checksum.wrapping_sub(data[index as usize]).wrapping_sub(1);
         index += 1;
     }
-    checksum == data[HEADER_CHECKSUM_ADDRESS as usize]
+    checksum
+}
+
+// Sum of every byte of the ROM except the checksum itself. The boot ROM
+// doesn't check it, so a mismatch only means the image might be damaged
+fn global_checksum(data: &[u8]) -> u16 {
+    let checksum = GLOBAL_CHECKSUM_ADDRESS as usize;
+    data.iter()
+        .enumerate()
+        .filter(|(index, _)| *index != checksum && *index != checksum + 1)
+        .fold(0u16, |sum, (_, byte)| sum.wrapping_add(*byte as u16))
+}
+
+// The title takes 16 bytes on old cartridges, 15 once the CGB flag was added and only 11
+// on later ones that store a manufacturer code after it. Nothing marks the latter, so it
+// is assumed for CGB games using the new licensee code whose last 4 title bytes look like one
+fn parse_title(bytes: &[u8]) -> (String, String) {
+    let start = TITLE_ADDRESS as usize;
+    let manufacturer = &bytes[MANUFACTURER_CODE_ADDRESS as usize..CGB_FLAG_ADDRESS as usize];
+    let cgb = bytes[CGB_FLAG_ADDRESS as usize] & 0x80 != 0;
+    let has_manufacturer = cgb &&
+        bytes[OLD_LICENSEE_CODE_ADDRESS as usize] == USE_NEW_LICENSEE_CODE &&
+        manufacturer.iter().all(|byte| byte.is_ascii_uppercase() || byte.is_ascii_digit());
+    let end = match (cgb, has_manufacturer) {
+        (_, true) => MANUFACTURER_CODE_ADDRESS as usize,
+        (true, false) => CGB_FLAG_ADDRESS as usize,
+        (false, false) => NEW_LICENSEE_CODE_ADDRESS as usize,
+    };
+    let title: String = bytes[start..end].iter()
+        .take_while(|byte| **byte != 0)
+        .map(|byte| match byte.is_ascii_graphic() || *byte == b' ' {
+            true => *byte as char,
+            false => '?',
+        })
+        .collect();
+    let manufacturer = match has_manufacturer {
+        true => String::from_utf8_lossy(manufacturer).to_string(),
+        false => "".to_string(),
+    };
+    (title.trim_end().to_string(), manufacturer)
 }
 
 #[cfg(test)]
-pub fn load_rom(_filename: &str) -> std::io::Result<Box<dyn ROM>> {
-    Ok(Box::new(NoMBC::new(Vec::new(), ROMInfo {
+pub fn empty_rom() -> Box<dyn ROM> {
+    Box::new(NoMBC::new(Vec::new(), ROMInfo {
         mbc: MBC::NoMBC,
         filename: "".to_string(),
         publisher: "".to_string(),
+        licensee_code: "00".to_string(),
         title: "".to_string(),
+        manufacturer_code: "".to_string(),
+        version: 0,
         cgb_features: false,
         cgb_only: false,
         sgb_features: false,
         has_ram: false,
         has_battery: false,
         has_timer: false,
+        has_rumble: false,
         ram_banks: 0,
         rom_banks: 2,
         region: Region::NonJapanese,
-    })))
+        header_checksum: 0,
+        global_checksum: 0,
+        global_checksum_valid: true,
+    }))
+}
+
+pub fn load_rom(filename: &str) -> Result<Box<dyn ROM>, LoadError> {
+    load_rom_with_patch(filename, None)
+}
+
+// A patch sharing the name of the ROM is applied when none is given
+fn find_patch(filename: &str) -> Option<String> {
+    PATCH_EXTENSIONS.iter()
+        .map(|extension| Path::new(filename).with_extension(extension))
+        .find(|path| path.is_file())
+        .map(|path| path.to_string_lossy().to_string())
 }
 
-#[cfg(not(test))]
-pub fn load_rom(filename: &str) -> std::io::Result<Box<dyn ROM>> {
+// The patch is applied in memory, the files on disk are left untouched
+pub fn load_rom_with_patch(filename: &str, patch: Option<&str>) -> Result<Box<dyn ROM>, LoadError> {
     let mut file = File::open(filename)?;
     let mut data = vec![];
     file.read_to_end(&mut data)?;
-    if !header_checksum(&data) {
-        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "Header checksum failed. Is this a Gameboy ROM?"));
+
+    let patch = match patch {
+        Some(patch) => Some(patch.to_string()),
+        None => find_patch(filename),
+    };
+    if let Some(patch) = patch {
+        let mut file = File::open(&patch)?;
+        let mut patch_data = vec![];
+        file.read_to_end(&mut patch_data)?;
+        data = apply_patch(&data, &patch_data).map_err(LoadError::Patch)?;
+    }
+
+    let mut rom = build_rom(data, filename.to_string())?;
+
+    if let Err(err) = load_save(rom.as_mut()) {
+        eprintln!("Could not load save file: {}", err);
+    }
+
+    Ok(rom)
+}
+
+pub fn rom_from_bytes(data: Vec<u8>, save: Option<&[u8]>) -> Result<Box<dyn ROM>, LoadError> {
+    let mut rom = build_rom(data, "".to_string())?;
+    if let Some(save) = save {
+        rom.load_save_data(save);
     }
+    Ok(rom)
+}
 
-    let mut info = ROMInfo::from_bytes(&data);
-    info.set_filename(filename.to_string());
-    let info_copy = info.clone();
+fn build_rom(data: Vec<u8>, filename: String) -> Result<Box<dyn ROM>, LoadError> {
+    let mut info = match mmm01_menu_offset(&data) {
+        Some(offset) => {
+            let mut info = ROMInfo::from_bytes(&data[offset..])?;
+            info.global_checksum_valid = global_checksum(&data) == info.global_checksum;
+            info
+        },
+        None => ROMInfo::from_bytes(&data)?,
+    };
+    if !info.global_checksum_valid {
+        warn!("The global checksum doesn't match, the ROM might be damaged");
+    }
+    let data = mirror_rom(pad_rom(data));
+    if data.len() != info.rom_size() {
+        warn!("ROM size {} doesn't match the {} declared in the header", data.len(), info.rom_size());
+        info.rom_banks = (data.len() / 0x4000) as u16;
+    }
+    info.set_filename(filename);
 
-    let mut rom: Box<dyn ROM> = match info.mbc {
+    let rom: Box<dyn ROM> = match info.mbc {
         MBC::NoMBC => Box::new(NoMBC::new(data, info)),
         MBC::MBC1 => Box::new(MBC1::new(data, info)),
         MBC::MBC2 => Box::new(MBC2::new(data, info)),
+        MBC::MMM01 => Box::new(MMM01::new(data, info)),
         MBC::MBC3 => Box::new(MBC3::new(data, info)),
         MBC::MBC5 => Box::new(MBC5::new(data, info)),
-        _ => unimplemented!(),
-    };
-
-    #[cfg(not(test))]
-    match load_save(rom.ram_mut(), &info_copy) {
-        Err(err) => eprintln!("Could not load save file: {}", err),
-        _ => {},
+        MBC::MBC6 => Box::new(MBC6::new(data, info)),
+        MBC::MBC7 => Box::new(MBC7::new(data, info)),
+        MBC::HuC1 => Box::new(HuC1::new(data, info)),
+        MBC::HuC3 => Box::new(HuC3::new(data, info)),
+        MBC::PocketCamera => Box::new(PocketCamera::new(data, info)),
+        MBC::BandaiTIMA5 => Box::new(TAMA5::new(data, info)),
     };
 
     Ok(rom)
 }
 
-#[cfg(not(test))]
-pub fn save_file(ram: &Vec<u8>, info: &ROMInfo) -> std::io::Result<()> {
-    if !info.has_ram || !info.has_battery {
+// Partial banks are filled with 0xFF, as well as images smaller than the two fixed banks
+fn pad_rom(mut data: Vec<u8>) -> Vec<u8> {
+    let banks = data.len().div_ceil(0x4000).max(2);
+    data.resize(banks * 0x4000, 0xFF);
+    data
+}
+
+// Carts ignore the address lines their ROM doesn't use, so a size that isn't a power
+// of two is built from a big chip and a smaller one repeated over the rest of the space.
+// Once extended like that, every bank number can be masked against the image size
+fn mirror_rom(data: Vec<u8>) -> Vec<u8> {
+    if data.len().is_power_of_two() {
+        return data;
+    }
+    let half = data.len().next_power_of_two() / 2;
+    let upper = mirror_rom(data[half..].to_vec());
+    let mut mirrored = data;
+    mirrored.truncate(half);
+    while mirrored.len() < half * 2 {
+        mirrored.extend_from_slice(&upper);
+    }
+    mirrored
+}
+
+// Offset of `address` in ROM `bank`, wrapping around the image
+fn rom_address(data: &[u8], bank: usize, address: u16) -> usize {
+    ((bank * 0x4000) + (address as usize & 0x3FFF)) & data.len().saturating_sub(1)
+}
+
+pub fn save_file(rom: &dyn ROM) -> std::io::Result<()> {
+    let info = rom.info();
+    if !info.has_save() || info.filename.is_empty() {
         return Ok(());
     }
     let mut file = File::create(format!("{}.sav", info.filename))?;
-    file.write_all(ram)?;
+    file.write_all(&rom.save_data())?;
     Ok(())
 }
 
-#[cfg(not(test))]
-pub fn load_save(ram: &mut Vec<u8>, info: &ROMInfo) -> std::io::Result<()> {
-    if !info.has_ram || !info.has_battery {
+pub fn load_save(rom: &mut dyn ROM) -> std::io::Result<()> {
+    if !rom.info().has_save() {
         return Ok(());
     }
 
-    let mut file = File::open(format!("{}.sav", info.filename))?;
+    let mut file = File::open(format!("{}.sav", rom.info().filename))?;
     let mut data = vec![];
     file.read_to_end(&mut data)?;
+    rom.load_save_data(&data);
 
+    Ok(())
+}
+
+fn copy_save(ram: &mut [u8], data: &[u8]) {
     let mut index = 0;
     let size = match ram.len() < data.len() {
         true => ram.len(),
@@ -112,9 +303,7 @@ pub fn load_save(ram: &mut Vec<u8>, info: &ROMInfo) -> std::io::Result<()> {
     while index < size {
         ram[index] = data[index];
         index += 1;
-    } 
-
-    Ok(())
+    }
 }
 
 #[derive(Debug, Copy, Clone)]
@@ -150,19 +339,54 @@ pub struct ROMInfo {
     mbc: MBC,
     filename: String,
     publisher: String,
+    licensee_code: String,
     title: String,
+    manufacturer_code: String,
+    version: u8,
     cgb_features: bool,
     cgb_only: bool,
     sgb_features: bool,
     has_ram: bool,
     has_battery: bool,
     has_timer: bool,
+    has_rumble: bool,
     ram_banks: u8,
     rom_banks: u16,
     region: Region,
+    header_checksum: u8,
+    global_checksum: u16,
+    global_checksum_valid: bool,
 }
 
 impl ROMInfo {
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    // Empty when the licensee code isn't a known one
+    pub fn publisher(&self) -> &str {
+        &self.publisher
+    }
+
+    pub fn licensee_code(&self) -> &str {
+        &self.licensee_code
+    }
+
+    pub fn manufacturer_code(&self) -> Option<&str> {
+        match self.manufacturer_code.is_empty() {
+            true => None,
+            false => Some(&self.manufacturer_code),
+        }
+    }
+
+    pub fn version(&self) -> u8 {
+        self.version
+    }
+
+    pub fn sgb_features(&self) -> bool {
+        self.sgb_features
+    }
+
     pub fn cgb_features(&self) -> bool {
         self.cgb_features
     }
@@ -175,87 +399,153 @@ impl ROMInfo {
         self.filename = filename;
     }
 
-    pub fn from_bytes(bytes: &[u8]) -> Self {
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    pub fn header_checksum(&self) -> u8 {
+        self.header_checksum
+    }
+
+    pub fn global_checksum(&self) -> u16 {
+        self.global_checksum
+    }
+
+    pub fn global_checksum_valid(&self) -> bool {
+        self.global_checksum_valid
+    }
+
+    pub fn has_timer(&self) -> bool {
+        self.has_timer
+    }
+
+    pub fn has_rumble(&self) -> bool {
+        self.has_rumble
+    }
+
+    // Whether the cartridge keeps anything in a .sav file
+    pub fn has_save(&self) -> bool {
+        self.has_battery && (self.has_ram || self.has_timer)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, LoadError> {
+        if bytes.len() <= HEADER_END_ADDRESS as usize {
+            return Err(LoadError::Truncated { expected: HEADER_END_ADDRESS as usize + 1, actual: bytes.len() });
+        }
+        let checksum = header_checksum(bytes);
+        if checksum != bytes[HEADER_CHECKSUM_ADDRESS as usize] {
+            return Err(LoadError::ChecksumMismatch {
+                expected: checksum,
+                actual: bytes[HEADER_CHECKSUM_ADDRESS as usize],
+            });
+        }
+
         let rom_type = bytes[CARTRIDGE_TYPE_ADDRESS as usize];
-        Self {
-            mbc: match rom_type {
-                0x00 => MBC::NoMBC,
-                0x01 => MBC::MBC1,
-                0x02 => MBC::MBC1,
-                0x03 => MBC::MBC1,
-                0x05 => MBC::MBC2,
-                0x06 => MBC::MBC2,
-                0x08 => MBC::NoMBC,
-                0x09 => MBC::NoMBC,
-                0x0B => MBC::MMM01,
-                0x0C => MBC::MMM01,
-                0x0F => MBC::MBC3,
-                0x10 => MBC::MBC3,
-                0x11 => MBC::MBC3,
-                0x12 => MBC::MBC3,
-                0x13 => MBC::MBC3,
-                0x19 => MBC::MBC5,
-                0x1A => MBC::MBC5,
-                0x1B => MBC::MBC5,
-                0x1C => MBC::MBC5,
-                0x1D => MBC::MBC5,
-                0x1E => MBC::MBC5,
-                0x20 => MBC::MBC6,
-                0x22 => MBC::MBC7,
-                0xFC => MBC::PocketCamera,
-                0xFD => MBC::BandaiTIMA5,
-                0xFE => MBC::HuC3,
-                0xFF => MBC::HuC1,
-                _ => unreachable!(),
+        let mbc = match rom_type {
+            0x00 => MBC::NoMBC,
+            0x01 => MBC::MBC1,
+            0x02 => MBC::MBC1,
+            0x03 => MBC::MBC1,
+            0x05 => MBC::MBC2,
+            0x06 => MBC::MBC2,
+            0x08 => MBC::NoMBC,
+            0x09 => MBC::NoMBC,
+            0x0B => MBC::MMM01,
+            0x0C => MBC::MMM01,
+            0x0D => MBC::MMM01,
+            0x0F => MBC::MBC3,
+            0x10 => MBC::MBC3,
+            0x11 => MBC::MBC3,
+            0x12 => MBC::MBC3,
+            0x13 => MBC::MBC3,
+            0x19 => MBC::MBC5,
+            0x1A => MBC::MBC5,
+            0x1B => MBC::MBC5,
+            0x1C => MBC::MBC5,
+            0x1D => MBC::MBC5,
+            0x1E => MBC::MBC5,
+            0x20 => MBC::MBC6,
+            0x22 => MBC::MBC7,
+            0xFC => MBC::PocketCamera,
+            0xFD => MBC::BandaiTIMA5,
+            0xFE => MBC::HuC3,
+            0xFF => MBC::HuC1,
+            _ => return Err(LoadError::BadHeader { field: "cartridge type", value: rom_type }),
+        };
+        let ram_banks = match bytes[RAM_SIZE_ADDRESS as usize] {
+            0x00 | 0x01 => 0,
+            0x02 => 1,
+            0x03 => 4,
+            0x04 => 16,
+            0x05 => 8,
+            value => return Err(LoadError::BadHeader { field: "RAM size", value }),
+        };
+        let rom_banks = match bytes[ROM_SIZE_ADDRESS as usize] {
+            0x00 => 2,
+            0x01 => 4,
+            0x02 => 8,
+            0x03 => 16,
+            0x04 => 32,
+            0x05 => 64,
+            0x06 => 128,
+            0x07 => 256,
+            0x08 => 512,
+            0x52 => 72,
+            0x53 => 80,
+            0x54 => 96,
+            value => return Err(LoadError::BadHeader { field: "ROM size", value }),
+        };
+
+        let (title, manufacturer_code) = parse_title(bytes);
+        let old_licensee_code = bytes[OLD_LICENSEE_CODE_ADDRESS as usize];
+        let (licensee_code, publisher) = match old_licensee_code {
+            USE_NEW_LICENSEE_CODE => {
+                let code = &bytes[NEW_LICENSEE_CODE_ADDRESS as usize..SGB_FLAG_ADDRESS as usize];
+                let code = String::from_utf8_lossy(code).to_string();
+                let publisher = new_licensee_name(&code);
+                (code, publisher)
             },
+            code => (format!("{:02X}", code), old_licensee_name(code)),
+        };
+        let checksum_address = GLOBAL_CHECKSUM_ADDRESS as usize;
+        let expected_checksum = u16::from_be_bytes([bytes[checksum_address], bytes[checksum_address + 1]]);
+
+        Ok(Self {
+            mbc,
             filename: "".to_string(),
             region: match bytes[DESTINATION_CODE_ADDRESS as usize] {
                 0x00 => Region::Japanese,
                 _ => Region::NonJapanese,
             },
-            publisher: "".to_string(), // TODO: Extract publisher
-            title: "".to_string(), // TODO: Extract the game title
+            publisher: publisher.unwrap_or("").to_string(),
+            licensee_code,
+            title,
+            manufacturer_code,
+            version: bytes[VERSION_ADDRESS as usize],
             cgb_features: bytes[CGB_FLAG_ADDRESS as usize] == 0x80,
             cgb_only: bytes[CGB_FLAG_ADDRESS as usize] == 0xC0,
             sgb_features: bytes[SGB_FLAG_ADDRESS as usize] == 0x03,
             has_ram: match rom_type {
-                0x02 | 0x03 | 0x08 | 0x09 | 0x0C | 0x0D | 0x10 | 0x12 |
-                0x13 | 0x1A | 0x1B | 0x1D | 0x1E | 0x22 | 0xFF => true,
+                0x02 | 0x03 | 0x05 | 0x06 | 0x08 | 0x09 | 0x0C | 0x0D | 0x10 | 0x12 |
+                0x13 | 0x1A | 0x1B | 0x1D | 0x1E | 0x20 | 0x22 | 0xFC | 0xFD | 0xFE | 0xFF => true,
                 _ => false,
             },
             has_battery: match rom_type {
                 0x03 | 0x06 | 0x09 | 0x0D | 0x0F | 0x10 |
-                0x13 | 0x1B | 0x1E | 0x22 | 0xFF => true,
+                0x13 | 0x1B | 0x1E | 0x20 | 0x22 | 0xFC | 0xFD | 0xFE | 0xFF => true,
                 _ => false,
             },
             has_timer: match rom_type {
-                0x0F | 0x10 => true,
+                0x0F | 0x10 | 0xFD | 0xFE => true,
                 _ => false,
             },
-            ram_banks: match bytes[RAM_SIZE_ADDRESS as usize] {
-                0x00 | 0x01 => 0,
-                0x02 => 1,
-                0x03 => 4,
-                0x04 => 16,
-                0x05 => 8,
-                _ => unreachable!(),
-            },
-            rom_banks: match bytes[ROM_SIZE_ADDRESS as usize] {
-                0x00 => 2,
-                0x01 => 4,
-                0x02 => 8,
-                0x03 => 16,
-                0x04 => 32,
-                0x05 => 64,
-                0x06 => 128,
-                0x07 => 256,
-                0x08 => 512,
-                0x52 => 72,
-                0x53 => 80,
-                0x54 => 96,
-                _ => unreachable!(),
-            },
-        }
+            has_rumble: (0x1C..=0x1E).contains(&rom_type),
+            ram_banks,
+            rom_banks,
+            header_checksum: bytes[HEADER_CHECKSUM_ADDRESS as usize],
+            global_checksum: expected_checksum,
+            global_checksum_valid: global_checksum(bytes) == expected_checksum,
+        })
     }
 
     pub fn rom_size(&self) -> usize {
@@ -267,12 +557,43 @@ impl ROMInfo {
     }
 }
 
-pub trait ROM {
+pub trait ROM: SaveState {
     fn read(&self, address: u16) -> u8;
     fn write(&mut self, address: u16, data: u8);
     fn ram_mut(&mut self) -> &mut Vec<u8>;
     fn ram(&self) -> &Vec<u8>;
     fn info(&self) -> &ROMInfo;
+
+    // Contents of the .sav file
+    fn save_data(&self) -> Vec<u8> {
+        self.ram().clone()
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        copy_save(self.ram_mut(), data);
+    }
+
+    // Whether the rumble motor is currently on
+    fn rumble(&self) -> bool {
+        false
+    }
+
+    // Tone the cartridge speaker is playing, if any
+    fn tone(&self) -> Option<u8> {
+        None
+    }
+
+    // Feeds the accelerometer of cartridges that have one. Both axes go from -1.0 to 1.0
+    fn set_tilt(&mut self, _x: f32, _y: f32) {}
+
+    // Connects the infrared port of cartridges that have one
+    fn set_infrared_peer(&mut self, _peer: Option<Box<dyn InfraredPeer>>) {}
+
+    // Brings hardware on the cartridge that keeps its own time up to `now`
+    fn catch_up(&mut self, _now: u64) {}
+
+    // Picture seen by cartridges with a camera, see camera::M64282FP::set_image
+    fn set_camera_image(&mut self, _image: &[u8]) {}
 }
 
 pub struct NoMBC {
@@ -295,6 +616,14 @@ impl NoMBC {
     }
 }
 
+impl SaveState for NoMBC {
+    fn save_state(&self, _state: &mut StateWriter) {}
+
+    fn load_state(&mut self, _state: &mut StateReader) -> Result<(), SaveStateError> {
+        Ok(())
+    }
+}
+
 impl ROM for NoMBC {
     fn read(&self, address: u16) -> u8 {
         match self.data.get(address as usize) {
@@ -327,6 +656,18 @@ pub struct MBC1 {
     ram_enable: bool,
     bitmask: u8,
     banking_mode: BankingMode,
+    multicart: bool,
+}
+
+// MBC1M compilation carts only wire 4 bits of the ROM bank register, so every game
+// lives in its own 256 KiB block selected by the upper bank bits. They can be told
+// apart by the Nintendo logo of the games being repeated at the start of each block
+fn is_mbc1_multicart(data: &[u8]) -> bool {
+    if data.len() != 0x100000 {
+        return false;
+    }
+    let logo = NINTENDO_LOGO_ADDRESS as usize;
+    (1..4).any(|block| data[(block * 0x40000) + logo..(block * 0x40000) + logo + NINTENDO_LOGO.len()] == NINTENDO_LOGO)
 }
 
 impl MBC1 {
@@ -338,16 +679,11 @@ impl MBC1 {
         println!("ROM banks {}", info.rom_banks);
         println!("RAM banks {}", info.ram_banks);
         let ram = vec![0; info.ram_size() as usize];
-        let mut bitmask = 0b11111;
-        if info.rom_banks <= 2 {
-            bitmask = 0b1;
-        } else if info.rom_banks <= 4 {
-            bitmask = 0b11;
-        } else if info.rom_banks <= 8 {
-            bitmask = 0b111;
-        } else if info.rom_banks <= 16 {
-            bitmask = 0b1111;
-        }
+        let multicart = is_mbc1_multicart(&data);
+        let bitmask = match multicart {
+            true => 0b1111,
+            false => 0b11111,
+        };
         Self {
             data,
             info,
@@ -357,61 +693,76 @@ impl MBC1 {
             ram_enable: false,
             bitmask,
             banking_mode: BankingMode::Simple,
+            multicart,
         }
     }
 
     fn switch_rom_bank(&mut self, bank: u8) {
+        // The bank 0 check is done on the whole register, before the unused bits are dropped
+        let bank = match bank & 0b11111 {
+            0 => 1,
+            bank => bank,
+        };
         self.rom_bank = bank as u16 & self.bitmask as u16;
-        if self.rom_bank == 0 {
-            self.rom_bank = 1;
-        }
     }
 
     fn switch_ram_bank(&mut self, bank: u8) {
         self.ram_bank = bank & 0b11;
     }
 
+    // The secondary bank register provides the upper bits of the ROM bank number
+    fn upper_rom_bank(&self) -> usize {
+        match self.multicart {
+            true => (self.ram_bank as usize) << 4,
+            false => (self.ram_bank as usize) << 5,
+        }
+    }
+
+    // Bank numbers wrap around the ROM size since the extra lines aren't connected
+    fn get_rom_address(&self, bank: usize, address: u16) -> usize {
+        let bank = bank & (self.info.rom_banks as usize).saturating_sub(1);
+        (0x4000 * bank) + (address as usize & 0x3FFF)
+    }
+
     fn get_bank_zero_address(&self, address: u16) -> usize {
         match self.banking_mode {
-            BankingMode::Simple => address as usize,
-            BankingMode::Advanced => {
-                if self.info.rom_banks <= 32 {
-                    return address as usize;
-                } else if self.info.rom_banks <= 64 {
-                    let bank = (self.ram_bank & 0b1) << 5;
-                    return (bank as usize) * (address as usize);
-                } else if self.info.rom_banks >= 128 {
-                    let bank = self.ram_bank << 5;
-                    return (bank as usize) * (address as usize);
-                }
-                address as usize
-            },
+            BankingMode::Simple => self.get_rom_address(0, address),
+            BankingMode::Advanced => self.get_rom_address(self.upper_rom_bank(), address),
         }
     }
 
     fn get_bank_switchable_address(&self, address: u16) -> usize {
-        if self.info.rom_banks <= 32 {
-            return (0x4000 * self.rom_bank as usize) + (address as usize - 0x4000);
-        } else if self.info.rom_banks <= 64 {
-            let bank = (((self.ram_bank as u16) & 0b1) << 5) | self.rom_bank;
-            return (0x4000 * bank as usize) + (address as usize - 0x4000);
-        } else if self.info.rom_banks <= 128 {
-            let bank = ((self.ram_bank as u16) << 5) | self.rom_bank;
-            return (0x4000 * bank as usize) + (address as usize - 0x4000);
-        }
-        (0x4000 * self.rom_bank as usize) + (address as usize - 0x4000)
+        self.get_rom_address(self.upper_rom_bank() | self.rom_bank as usize, address)
     }
 
     fn get_ram_address(&self, address: u16) -> usize {
-        match self.banking_mode {
-            BankingMode::Simple => address as usize - 0xA000,
-            BankingMode::Advanced => {
-                if self.info.ram_banks <= 1 {
-                    return address as usize - 0xA000;
-                }
-                (0x2000 * self.ram_bank as usize) + (address as usize - 0xA000)
-            },
-        }
+        let bank = match self.banking_mode {
+            BankingMode::Simple => 0,
+            BankingMode::Advanced => self.ram_bank as usize & (self.info.ram_banks as usize).saturating_sub(1),
+        };
+        (0x2000 * bank) + (address as usize - 0xA000)
+    }
+}
+
+impl SaveState for MBC1 {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u16(self.rom_bank);
+        state.write_u8(self.ram_bank);
+        state.write_bool(self.ram_enable);
+        state.write_bool(self.banking_mode == BankingMode::Advanced);
+        state.write_bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        // The stored bank already went through the bank 0 check, and can be 0 on multicarts
+        self.rom_bank = state.read_u16()? & self.bitmask as u16;
+        self.switch_ram_bank(state.read_u8()?);
+        self.ram_enable = state.read_bool()?;
+        self.banking_mode = match state.read_bool()? {
+            true => BankingMode::Advanced,
+            false => BankingMode::Simple,
+        };
+        state.read_bytes_into(&mut self.ram)
     }
 }
 
@@ -482,6 +833,9 @@ impl ROM for MBC1 {
     }
 }
 
+// 512 half bytes built into the MBC2 chip, stored one cell per byte
+const MBC2_RAM_SIZE: usize = 0x200;
+
 pub struct MBC2 {
     data: Vec<u8>,
     info: ROMInfo,
@@ -498,7 +852,7 @@ impl MBC2 {
         println!("Has battery {}", info.has_battery);
         println!("ROM banks {}", info.rom_banks);
         println!("RAM banks {}", info.ram_banks);
-        let ram = vec![0; 0x200];
+        let ram = vec![0; MBC2_RAM_SIZE];
         Self {
             data,
             info,
@@ -509,29 +863,46 @@ impl MBC2 {
     }
 
     fn switch_rom_bank(&mut self, bank: u16) {
-        self.rom_bank = bank & 0b1111;
-        if self.rom_bank > self.info.rom_banks.saturating_sub(1) {
-            self.rom_bank = self.info.rom_banks.saturating_sub(1);
-        }
-        if self.rom_bank == 0 {
-            self.rom_bank = 1;
-        }
+        self.rom_bank = match bank & 0b1111 {
+            0 => 1,
+            bank => bank & self.info.rom_banks.saturating_sub(1),
+        };
+    }
+}
+
+impl SaveState for MBC2 {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u16(self.rom_bank);
+        state.write_bool(self.ram_enable);
+        state.write_bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.switch_rom_bank(state.read_u16()?);
+        self.ram_enable = state.read_bool()?;
+        state.read_bytes_into(&mut self.ram)
     }
 }
 
 impl ROM for MBC2 {
     fn read(&self, address: u16) -> u8 {
         if BANK_ZERO.contains(&address) {
-            return self.data[address as usize];
+            return match self.data.get(address as usize) {
+                Some(byte) => *byte,
+                None => 0xFF,
+            };
         } else if BANK_SWITCHABLE.contains(&address) {
-            return self.data[((self.rom_bank as usize * 0x4000) + (address as usize - 0x4000)) as usize];
+            return match self.data.get(rom_address(&self.data, self.rom_bank as usize, address)) {
+                Some(byte) => *byte,
+                None => 0xFF,
+            };
         } else if address >= 0xA000 {
-            let address = (address as usize) & 0x1FF;
             if !self.ram_enable {
                 return 0xFF;
             }
-            return match self.ram.get(address as usize) {
-                Some(data) => *data,
+            // Only the lower 4 bits exist, the rest of the data lines read as 1s
+            return match self.ram.get(address as usize & (MBC2_RAM_SIZE - 1)) {
+                Some(data) => *data | 0xF0,
                 None => 0xFF,
             };
         }
@@ -540,11 +911,9 @@ impl ROM for MBC2 {
 
     fn write(&mut self, address: u16, data: u8) {
         if BANK_ZERO.contains(&address) {
-            if address.to_be_bytes()[0] & 1 == 0 {
-                match data {
-                    0x0A => self.ram_enable = true,
-                    _ => self.ram_enable = false,
-                }
+            // Bit 8 of the address selects between the RAM enable and the ROM bank registers
+            if address & 0x0100 == 0 {
+                self.ram_enable = data & 0x0F == 0x0A;
             } else {
                 self.switch_rom_bank(data as u16);
             }
@@ -552,9 +921,9 @@ impl ROM for MBC2 {
             if !self.ram_enable {
                 return;
             }
-            let address = address & 0x1FF;
-            if let Some(elem) = self.ram.get_mut(address as usize) {
-                *elem = data;
+            // The 512 cells are repeated all over the external RAM area
+            if let Some(elem) = self.ram.get_mut(address as usize & (MBC2_RAM_SIZE - 1)) {
+                *elem = data & 0x0F;
             }
         }
     }
@@ -570,6 +939,145 @@ impl ROM for MBC2 {
     fn info(&self) -> &ROMInfo {
         &self.info
     }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        copy_save(&mut self.ram, data);
+        for cell in self.ram.iter_mut() {
+            *cell &= 0x0F;
+        }
+    }
+}
+
+// Seconds since the unix epoch on the host, the RTC counts real time even while the emulator is closed
+fn unix_time() -> u64 {
+    match SystemTime::now().duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs(),
+        Err(_) => 0,
+    }
+}
+
+const RTC_FOOTER_SIZE: usize = 48;
+const RTC_DAY_HIGH_BIT: u8 = 0b0000_0001;
+const RTC_HALT_BIT: u8 = 0b0100_0000;
+const RTC_DAY_CARRY_BIT: u8 = 0b1000_0000;
+
+// Registers 0x08 to 0x0C: seconds, minutes, hours, lower 8 bits of the day counter
+// and the upper day bit along with the halt and day carry flags
+#[derive(Debug, Copy, Clone, PartialEq)]
+struct RealTimeClock {
+    registers: [u8; 5],
+    latched: [u8; 5],
+    // Host time the registers were last brought up to date
+    timestamp: u64,
+}
+
+impl RealTimeClock {
+    fn new(now: u64) -> Self {
+        Self {
+            registers: [0; 5],
+            latched: [0; 5],
+            timestamp: now,
+        }
+    }
+
+    fn is_halted(&self) -> bool {
+        self.registers[4] & RTC_HALT_BIT != 0
+    }
+
+    fn days(&self) -> u64 {
+        (((self.registers[4] & RTC_DAY_HIGH_BIT) as u64) << 8) | self.registers[3] as u64
+    }
+
+    fn update(&mut self, now: u64) {
+        let elapsed = now.saturating_sub(self.timestamp);
+        self.timestamp = now;
+        if self.is_halted() || elapsed == 0 {
+            return;
+        }
+        let mut total = elapsed + self.registers[0] as u64;
+        self.registers[0] = (total % 60) as u8;
+        total = (total / 60) + self.registers[1] as u64;
+        self.registers[1] = (total % 60) as u8;
+        total = (total / 60) + self.registers[2] as u64;
+        self.registers[2] = (total % 24) as u8;
+        let days = (total / 24) + self.days();
+        if days > 0x1FF {
+            self.registers[4] |= RTC_DAY_CARRY_BIT;
+        }
+        self.registers[3] = days as u8;
+        self.registers[4] = (self.registers[4] & !RTC_DAY_HIGH_BIT) | ((days >> 8) as u8 & RTC_DAY_HIGH_BIT);
+    }
+
+    fn latch(&mut self, now: u64) {
+        self.update(now);
+        self.latched = self.registers;
+    }
+
+    fn read(&self, register: u8) -> u8 {
+        match self.latched.get(register.wrapping_sub(0x08) as usize) {
+            Some(value) => *value,
+            None => 0xFF,
+        }
+    }
+
+    fn write(&mut self, register: u8, data: u8, now: u64) {
+        self.update(now);
+        let mask = match register {
+            0x08 | 0x09 => 0b0011_1111,
+            0x0A => 0b0001_1111,
+            0x0B => 0b1111_1111,
+            0x0C => RTC_DAY_HIGH_BIT | RTC_HALT_BIT | RTC_DAY_CARRY_BIT,
+            _ => return,
+        };
+        self.registers[(register - 0x08) as usize] = data & mask;
+    }
+
+    // Same layout as VBA-M and BGB: every register as a 32 bit integer,
+    // first the current ones, then the latched ones and finally a 64 bit unix timestamp
+    fn to_footer(self) -> Vec<u8> {
+        let mut footer = Vec::with_capacity(RTC_FOOTER_SIZE);
+        for value in self.registers.iter().chain(self.latched.iter()) {
+            footer.extend_from_slice(&(*value as u32).to_le_bytes());
+        }
+        footer.extend_from_slice(&self.timestamp.to_le_bytes());
+        footer
+    }
+
+    // Older emulators store the timestamp as 32 bits, resulting in a 44 byte footer
+    fn from_footer(footer: &[u8]) -> Option<Self> {
+        if footer.len() != RTC_FOOTER_SIZE && footer.len() != RTC_FOOTER_SIZE - 4 {
+            return None;
+        }
+        let value = |index: usize| footer[index * 4];
+        let mut timestamp = [0; 8];
+        timestamp[..footer.len() - 40].copy_from_slice(&footer[40..]);
+        let mut rtc = Self {
+            registers: [value(0), value(1), value(2), value(3), value(4)],
+            latched: [value(5), value(6), value(7), value(8), value(9)],
+            timestamp: u64::from_le_bytes(timestamp),
+        };
+        // Keep out of range values from the file from turning into impossible register states
+        for register in 0x08..=0x0C {
+            let value = rtc.registers[register as usize - 0x08];
+            rtc.write(register, value, rtc.timestamp);
+        }
+        Some(rtc)
+    }
+}
+
+impl SaveState for RealTimeClock {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.registers);
+        state.write_bytes(&self.latched);
+        state.write_u64(self.timestamp);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        state.read_bytes_into(&mut self.registers)?;
+        state.read_bytes_into(&mut self.latched)?;
+        self.timestamp = state.read_u64()?;
+        Ok(())
+    }
 }
 
 pub struct MBC3 {
@@ -582,6 +1090,7 @@ pub struct MBC3 {
     map_rtc: bool,
     prev_rtc_latch: u8,
     rtc_register: u8,
+    rtc: RealTimeClock,
 }
 
 impl MBC3 {
@@ -590,6 +1099,7 @@ impl MBC3 {
         println!("Region {:?}", info.region);
         println!("Has RAM {}", info.has_ram);
         println!("Has battery {}", info.has_battery);
+        println!("Has timer {}", info.has_timer);
         println!("ROM banks {}", info.rom_banks);
         println!("RAM banks {}", info.ram_banks);
         let ram = vec![0; info.ram_size() as usize];
@@ -603,16 +1113,15 @@ impl MBC3 {
             map_rtc: false,
             prev_rtc_latch: 0,
             rtc_register: 0x08,
+            rtc: RealTimeClock::new(unix_time()),
         }
     }
 
     fn switch_rom_bank(&mut self, bank: u16) {
-        self.rom_bank = bank & 0b01111111;
-        if self.rom_bank > self.info.rom_banks.saturating_sub(1) {
-            self.rom_bank = self.info.rom_banks.saturating_sub(1);
-        } else if self.rom_bank == 0 {
-            self.rom_bank = 1;
-        }
+        self.rom_bank = match bank & 0b01111111 {
+            0 => 1,
+            bank => bank,
+        };
     }
 
     fn get_ram_address(&self, address: u16) -> usize {
@@ -620,28 +1129,57 @@ impl MBC3 {
     }
 }
 
+impl SaveState for MBC3 {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u16(self.rom_bank);
+        state.write_u8(self.ram_bank);
+        state.write_bool(self.ram_timer_enable);
+        state.write_bool(self.map_rtc);
+        state.write_u8(self.prev_rtc_latch);
+        state.write_u8(self.rtc_register);
+        state.write_bytes(&self.ram);
+        self.rtc.save_state(state);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.switch_rom_bank(state.read_u16()?);
+        self.ram_bank = state.read_u8()?;
+        self.ram_timer_enable = state.read_bool()?;
+        self.map_rtc = state.read_bool()?;
+        self.prev_rtc_latch = state.read_u8()?;
+        self.rtc_register = state.read_u8()?;
+        state.read_bytes_into(&mut self.ram)?;
+        self.rtc.load_state(state)
+    }
+}
+
 impl ROM for MBC3 {
     fn read(&self, address: u16) -> u8 {
         if BANK_ZERO.contains(&address) {
-            return self.data[address as usize];
+            return match self.data.get(address as usize) {
+                Some(byte) => *byte,
+                None => 0xFF,
+            };
         } else if BANK_SWITCHABLE.contains(&address) {
-            return self.data[((self.rom_bank as usize * 0x4000) + (address as usize - 0x4000)) as usize];
+            return match self.data.get(rom_address(&self.data, self.rom_bank as usize, address)) {
+                Some(byte) => *byte,
+                None => 0xFF,
+            };
         } else if EXTERNAL_RAM.contains(&address) {
-            if self.map_rtc {
-                if !self.ram_timer_enable {
-                    return 0xFF;
-                }
+            if !self.ram_timer_enable {
                 return 0xFF;
-            } else {
-                if !self.ram_timer_enable {
-                    return 0xFF;
-                }
-                let address = self.get_ram_address(address);
-                return match self.ram.get(address) {
-                    Some(data) => *data,
-                    None => 0xFF,
+            }
+            if self.map_rtc {
+                return match self.info.has_timer {
+                    true => self.rtc.read(self.rtc_register),
+                    false => 0xFF,
                 };
             }
+            let address = self.get_ram_address(address);
+            return match self.ram.get(address) {
+                Some(data) => *data,
+                None => 0xFF,
+            };
         }
         unreachable!("Invalid ROM read: {}", address);
     }
@@ -665,7 +1203,7 @@ impl ROM for MBC3 {
             }
         } else if address >= 0x6000 && address <= 0x7FFF {
             if self.prev_rtc_latch == 0 && data == 1 {
-                // latch
+                self.rtc.latch(unix_time());
             }
             self.prev_rtc_latch = data;
         } else if address >= 0xA000 && address <= 0xBFFF {
@@ -674,6 +1212,7 @@ impl ROM for MBC3 {
             }
 
             if self.map_rtc {
+                self.rtc.write(self.rtc_register, data, unix_time());
             } else {
                 let address = self.get_ram_address(address);
                 if let Some(elem) = self.ram.get_mut(address) {
@@ -694,6 +1233,25 @@ impl ROM for MBC3 {
     fn info(&self) -> &ROMInfo {
         &self.info
     }
+
+    fn save_data(&self) -> Vec<u8> {
+        let mut data = self.ram.clone();
+        if self.info.has_timer {
+            data.extend(self.rtc.to_footer());
+        }
+        data
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        copy_save(&mut self.ram, data);
+        if !self.info.has_timer {
+            return;
+        }
+        if let Some(rtc) = data.get(self.ram.len()..).and_then(RealTimeClock::from_footer) {
+            self.rtc = rtc;
+            self.rtc.update(unix_time());
+        }
+    }
 }
 
 pub struct MBC5 {
@@ -703,6 +1261,7 @@ pub struct MBC5 {
     rom_bank: u16,
     ram_bank: u8,
     ram_enable: bool,
+    rumble: bool,
 }
 
 impl MBC5 {
@@ -711,6 +1270,7 @@ impl MBC5 {
         println!("Region {:?}", info.region);
         println!("Has RAM {}", info.has_ram);
         println!("Has battery {}", info.has_battery);
+        println!("Has rumble {}", info.has_rumble);
         println!("ROM banks {}", info.rom_banks);
         println!("RAM banks {}", info.ram_banks);
         let ram = vec![0; info.ram_size() as usize];
@@ -721,6 +1281,7 @@ impl MBC5 {
             rom_bank: 1,
             ram_bank: 0,
             ram_enable: false,
+            rumble: false,
         }
     }
 
@@ -729,12 +1290,33 @@ impl MBC5 {
     }
 }
 
+impl SaveState for MBC5 {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u16(self.rom_bank);
+        state.write_u8(self.ram_bank);
+        state.write_bool(self.rumble);
+        state.write_bool(self.ram_enable);
+        state.write_bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.rom_bank = state.read_u16()?;
+        self.ram_bank = state.read_u8()?;
+        self.rumble = state.read_bool()?;
+        self.ram_enable = state.read_bool()?;
+        state.read_bytes_into(&mut self.ram)
+    }
+}
+
 impl ROM for MBC5 {
     fn read(&self, address: u16) -> u8 {
         if BANK_ZERO.contains(&address) {
-            return self.data[address as usize];
+            return match self.data.get(address as usize) {
+                Some(byte) => *byte,
+                None => 0xFF,
+            };
         } else if BANK_SWITCHABLE.contains(&address) {
-            return match self.data.get(((self.rom_bank as usize * 0x4000) + (address as usize - 0x4000)) as usize) {
+            return match self.data.get(rom_address(&self.data, self.rom_bank as usize, address)) {
                 Some(byte) => *byte,
                 None => 0xFF,
             };
@@ -758,7 +1340,14 @@ impl ROM for MBC5 {
         } else if address >= 0x3000 && address <= 0x3FFF {
             self.rom_bank = (((data & 1) as u16) << 8) | (self.rom_bank & 0xFF);
         } else if address >= 0x4000 && address <= 0x5FFF {
-            self.ram_bank = data & 0b1111;
+            // Rumble carts wire the motor to bit 3 instead of the RAM bank
+            match self.info.has_rumble {
+                true => {
+                    self.ram_bank = data & 0b0111;
+                    self.rumble = data & 0b1000 != 0;
+                },
+                false => self.ram_bank = data & 0b1111,
+            };
         } else if EXTERNAL_RAM.contains(&address) {
             if !self.ram_enable || !self.info.has_ram {
                 return;
@@ -781,4 +1370,2566 @@ impl ROM for MBC5 {
     fn info(&self) -> &ROMInfo {
         &self.info
     }
+
+    fn rumble(&self) -> bool {
+        self.rumble
+    }
+}
+
+// MX29F008 flash chip, 1 MiB shown through the same 8 KiB windows as the ROM.
+// Commands are sent with the usual 0xAA, 0x55 unlock sequence
+const FLASH_SIZE: usize = 0x100000;
+const FLASH_SECTOR_SIZE: usize = 0x20000;
+const FLASH_MANUFACTURER_ID: u8 = 0xC2;
+const FLASH_DEVICE_ID: u8 = 0x81;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum FlashState {
+    Read,
+    Unlocked,
+    Command,
+    Id,
+    Program,
+    // The second unlock sequence of an erase
+    Erase,
+    EraseUnlocked,
+    EraseCommand,
+}
+
+struct Flash {
+    data: Vec<u8>,
+    state: FlashState,
+}
+
+impl Flash {
+    fn new() -> Self {
+        Self {
+            data: vec![0xFF; FLASH_SIZE],
+            state: FlashState::Read,
+        }
+    }
+
+    fn read(&self, address: usize) -> u8 {
+        match self.state {
+            FlashState::Id => match address & 1 {
+                0 => FLASH_MANUFACTURER_ID,
+                _ => FLASH_DEVICE_ID,
+            },
+            _ => self.data[address % FLASH_SIZE],
+        }
+    }
+
+    fn write(&mut self, address: usize, data: u8) {
+        let address = address % FLASH_SIZE;
+        // The unlock addresses are decoded from the lower 15 bits only
+        let command_address = address & 0x7FFF;
+        self.state = match (self.state, command_address, data) {
+            // Programming can only clear bits
+            (FlashState::Program, _, _) => {
+                self.data[address] &= data;
+                FlashState::Read
+            },
+            (_, _, 0xF0) => FlashState::Read,
+            (FlashState::Read | FlashState::Id, 0x5555, 0xAA) => FlashState::Unlocked,
+            (FlashState::Unlocked, 0x2AAA, 0x55) => FlashState::Command,
+            (FlashState::Command, 0x5555, 0x90) => FlashState::Id,
+            (FlashState::Command, 0x5555, 0xA0) => FlashState::Program,
+            (FlashState::Command, 0x5555, 0x80) => FlashState::Erase,
+            (FlashState::Erase, 0x5555, 0xAA) => FlashState::EraseUnlocked,
+            (FlashState::EraseUnlocked, 0x2AAA, 0x55) => FlashState::EraseCommand,
+            (FlashState::EraseCommand, 0x5555, 0x10) => {
+                self.data.fill(0xFF);
+                FlashState::Read
+            },
+            (FlashState::EraseCommand, _, 0x30) => {
+                let sector = address - (address % FLASH_SECTOR_SIZE);
+                self.data[sector..sector + FLASH_SECTOR_SIZE].fill(0xFF);
+                FlashState::Read
+            },
+            (FlashState::Id, _, _) => FlashState::Id,
+            _ => FlashState::Read,
+        };
+    }
+}
+
+impl SaveState for Flash {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(match self.state {
+            FlashState::Read => 0,
+            FlashState::Unlocked => 1,
+            FlashState::Command => 2,
+            FlashState::Id => 3,
+            FlashState::Program => 4,
+            FlashState::Erase => 5,
+            FlashState::EraseUnlocked => 6,
+            FlashState::EraseCommand => 7,
+        });
+        state.write_bytes(&self.data);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.state = match state.read_u8()? {
+            0 => FlashState::Read,
+            1 => FlashState::Unlocked,
+            2 => FlashState::Command,
+            3 => FlashState::Id,
+            4 => FlashState::Program,
+            5 => FlashState::Erase,
+            6 => FlashState::EraseUnlocked,
+            7 => FlashState::EraseCommand,
+            _ => return Err(SaveStateError::InvalidData("flash state")),
+        };
+        state.read_bytes_into(&mut self.data)
+    }
+}
+
+// One of the two halves of the switchable ROM area, or of the external RAM
+#[derive(Copy, Clone)]
+struct MBC6Window {
+    rom_bank: u8,
+    flash: bool,
+    ram_bank: u8,
+}
+
+pub struct MBC6 {
+    data: Vec<u8>,
+    info: ROMInfo,
+    ram: Vec<u8>,
+    flash: Flash,
+    windows: [MBC6Window; 2],
+    ram_enable: bool,
+    flash_enable: bool,
+    flash_write_enable: bool,
+}
+
+impl MBC6 {
+    fn new(data: Vec<u8>, info: ROMInfo) -> Self {
+        println!("MBC {:?}", info.mbc);
+        println!("Region {:?}", info.region);
+        println!("Has battery {}", info.has_battery);
+        println!("ROM banks {}", info.rom_banks);
+        println!("RAM banks {}", info.ram_banks);
+        let ram = vec![0; info.ram_size()];
+        let window = MBC6Window {
+            rom_bank: 0,
+            flash: false,
+            ram_bank: 0,
+        };
+        Self {
+            data,
+            info,
+            ram,
+            flash: Flash::new(),
+            windows: [window; 2],
+            ram_enable: false,
+            flash_enable: false,
+            flash_write_enable: false,
+        }
+    }
+
+    // Each window is 8 KiB wide, 0x4000-0x5FFF is the first one
+    fn window(address: u16) -> usize {
+        ((address as usize - 0x4000) / 0x2000) & 1
+    }
+
+    fn get_rom_address(&self, window: usize, address: u16) -> usize {
+        let banks = (self.info.rom_banks as usize * 2).saturating_sub(1);
+        let bank = self.windows[window].rom_bank as usize & banks;
+        (0x2000 * bank) + (address as usize & 0x1FFF)
+    }
+
+    fn get_flash_address(&self, window: usize, address: u16) -> usize {
+        let bank = self.windows[window].rom_bank as usize & 0x7F;
+        (0x2000 * bank) + (address as usize & 0x1FFF)
+    }
+
+    // RAM is banked in 4 KiB halves, 0xA000-0xAFFF and 0xB000-0xBFFF
+    fn get_ram_address(&self, address: u16) -> usize {
+        let window = ((address as usize - 0xA000) / 0x1000) & 1;
+        let banks = (self.ram.len() / 0x1000).saturating_sub(1);
+        let bank = self.windows[window].ram_bank as usize & banks;
+        (0x1000 * bank) + (address as usize & 0x0FFF)
+    }
+}
+
+impl SaveState for MBC6 {
+    fn save_state(&self, state: &mut StateWriter) {
+        for window in &self.windows {
+            state.write_u8(window.rom_bank);
+            state.write_bool(window.flash);
+            state.write_u8(window.ram_bank);
+        }
+        state.write_bool(self.ram_enable);
+        state.write_bool(self.flash_enable);
+        state.write_bool(self.flash_write_enable);
+        self.flash.save_state(state);
+        state.write_bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        for window in self.windows.iter_mut() {
+            window.rom_bank = state.read_u8()? & 0x7F;
+            window.flash = state.read_bool()?;
+            window.ram_bank = state.read_u8()? & 0b111;
+        }
+        self.ram_enable = state.read_bool()?;
+        self.flash_enable = state.read_bool()?;
+        self.flash_write_enable = state.read_bool()?;
+        self.flash.load_state(state)?;
+        state.read_bytes_into(&mut self.ram)
+    }
+}
+
+impl ROM for MBC6 {
+    fn read(&self, address: u16) -> u8 {
+        if BANK_ZERO.contains(&address) {
+            return match self.data.get(address as usize) {
+                Some(byte) => *byte,
+                None => 0xFF,
+            };
+        } else if BANK_SWITCHABLE.contains(&address) {
+            let window = MBC6::window(address);
+            return match (self.windows[window].flash, self.flash_enable) {
+                (true, true) => self.flash.read(self.get_flash_address(window, address)),
+                (true, false) => 0xFF,
+                (false, _) => match self.data.get(self.get_rom_address(window, address)) {
+                    Some(byte) => *byte,
+                    None => 0xFF,
+                },
+            };
+        } else if EXTERNAL_RAM.contains(&address) && self.ram_enable {
+            return match self.ram.get(self.get_ram_address(address)) {
+                Some(data) => *data,
+                None => 0xFF,
+            };
+        }
+        0xFF
+    }
+
+    fn write(&mut self, address: u16, data: u8) {
+        match address {
+            0x0000..=0x03FF => self.ram_enable = data & 0x0F == 0x0A,
+            0x0400..=0x07FF => self.windows[0].ram_bank = data & 0b111,
+            0x0800..=0x0BFF => self.windows[1].ram_bank = data & 0b111,
+            // The flash can only be turned on while writing to it is allowed
+            0x0C00..=0x0FFF if self.flash_write_enable => self.flash_enable = data & 1 != 0,
+            0x1000 => self.flash_write_enable = data & 1 != 0,
+            0x2000..=0x27FF => self.windows[0].rom_bank = data & 0x7F,
+            0x2800..=0x2FFF => self.windows[0].flash = data == 0x08,
+            0x3000..=0x37FF => self.windows[1].rom_bank = data & 0x7F,
+            0x3800..=0x3FFF => self.windows[1].flash = data == 0x08,
+            0x4000..=0x7FFF => {
+                let window = MBC6::window(address);
+                if self.windows[window].flash && self.flash_enable && self.flash_write_enable {
+                    let address = self.get_flash_address(window, address);
+                    self.flash.write(address, data);
+                }
+            },
+            0xA000..=0xBFFF if self.ram_enable => {
+                let address = self.get_ram_address(address);
+                if let Some(elem) = self.ram.get_mut(address) {
+                    *elem = data;
+                }
+            },
+            _ => {},
+        };
+    }
+
+    fn ram_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.ram
+    }
+
+    fn ram(&self) -> &Vec<u8> {
+        &self.ram
+    }
+
+    fn info(&self) -> &ROMInfo {
+        &self.info
+    }
+
+    // The flash contents are stored after the RAM
+    fn save_data(&self) -> Vec<u8> {
+        let mut data = self.ram.clone();
+        data.extend_from_slice(&self.flash.data);
+        data
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        copy_save(&mut self.ram, data);
+        if let Some(flash) = data.get(self.ram.len()..) {
+            copy_save(&mut self.flash.data, flash);
+        }
+    }
+}
+
+// 93LC56 serial EEPROM organized as 128 words of 16 bits. Games bit bang its
+// chip select, clock and data lines through the MBC7 register at 0xA080
+const EEPROM_SIZE: usize = 0x100;
+const EEPROM_CS_BIT: u8 = 0b1000_0000;
+const EEPROM_CLK_BIT: u8 = 0b0100_0000;
+const EEPROM_DI_BIT: u8 = 0b0000_0010;
+// Start bit, 2 bits of opcode and 8 bits of address
+const EEPROM_COMMAND_BITS: u8 = 11;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+enum EepromState {
+    // Waiting for the start bit
+    Idle,
+    Command,
+    Read,
+    // Receiving the data of a WRITE, or of a WRAL when there is no address
+    Write(Option<u8>),
+}
+
+struct Eeprom {
+    state: EepromState,
+    lines: u8,
+    data_out: bool,
+    shift: u16,
+    bits: u8,
+    address: u8,
+    write_enable: bool,
+}
+
+impl Eeprom {
+    fn new() -> Self {
+        Self {
+            state: EepromState::Idle,
+            lines: 0,
+            data_out: true,
+            shift: 0,
+            bits: 0,
+            address: 0,
+            write_enable: false,
+        }
+    }
+
+    fn read(&self) -> u8 {
+        (self.lines & (EEPROM_CS_BIT | EEPROM_CLK_BIT | EEPROM_DI_BIT)) | self.data_out as u8
+    }
+
+    fn read_word(memory: &[u8], address: u8) -> u16 {
+        let index = (address as usize & 0x7F) * 2;
+        u16::from_le_bytes([memory[index], memory[index + 1]])
+    }
+
+    fn write_word(&self, memory: &mut [u8], address: u8, value: u16) {
+        if !self.write_enable {
+            return;
+        }
+        let index = (address as usize & 0x7F) * 2;
+        memory[index..index + 2].copy_from_slice(&value.to_le_bytes());
+    }
+
+    fn write(&mut self, memory: &mut [u8], data: u8) {
+        let previous = self.lines;
+        self.lines = data;
+        if data & EEPROM_CS_BIT == 0 {
+            self.state = EepromState::Idle;
+            return;
+        }
+        if previous & EEPROM_CS_BIT == 0 {
+            // Selecting the chip shows whether the last write is done, which is always the case here
+            self.data_out = true;
+        }
+        // Everything happens on the rising edge of the clock
+        if previous & EEPROM_CLK_BIT != 0 || data & EEPROM_CLK_BIT == 0 {
+            return;
+        }
+
+        let bit = (data & EEPROM_DI_BIT != 0) as u16;
+        match self.state {
+            EepromState::Idle => {
+                if bit == 1 {
+                    self.state = EepromState::Command;
+                    self.shift = 1;
+                    self.bits = 1;
+                }
+            },
+            EepromState::Command => {
+                self.shift = (self.shift << 1) | bit;
+                self.bits += 1;
+                if self.bits == EEPROM_COMMAND_BITS {
+                    self.run_command(memory);
+                }
+            },
+            EepromState::Read => {
+                self.data_out = self.shift & 0x8000 != 0;
+                self.shift <<= 1;
+                self.bits += 1;
+                // Reading goes on with the next word for as long as the clock keeps running
+                if self.bits == 16 {
+                    self.address = (self.address + 1) & 0x7F;
+                    self.shift = Eeprom::read_word(memory, self.address);
+                    self.bits = 0;
+                }
+            },
+            EepromState::Write(address) => {
+                self.shift = (self.shift << 1) | bit;
+                self.bits += 1;
+                if self.bits == 16 {
+                    match address {
+                        Some(address) => self.write_word(memory, address, self.shift),
+                        None => for address in 0..0x80 {
+                            self.write_word(memory, address, self.shift);
+                        },
+                    };
+                    self.state = EepromState::Idle;
+                }
+            },
+        };
+    }
+
+    fn run_command(&mut self, memory: &mut [u8]) {
+        let opcode = (self.shift >> 8) & 0b11;
+        let address = self.shift as u8;
+        self.state = EepromState::Idle;
+        self.shift = 0;
+        self.bits = 0;
+        match (opcode, address >> 6) {
+            // READ, the data comes after a dummy 0
+            (0b10, _) => {
+                self.address = address & 0x7F;
+                self.shift = Eeprom::read_word(memory, self.address);
+                self.data_out = false;
+                self.state = EepromState::Read;
+            },
+            // WRITE
+            (0b01, _) => self.state = EepromState::Write(Some(address)),
+            // ERASE
+            (0b11, _) => self.write_word(memory, address, 0xFFFF),
+            // EWDS
+            (_, 0b00) => self.write_enable = false,
+            // WRAL
+            (_, 0b01) => self.state = EepromState::Write(None),
+            // ERAL
+            (_, 0b10) => for address in 0..0x80 {
+                self.write_word(memory, address, 0xFFFF);
+            },
+            // EWEN
+            _ => self.write_enable = true,
+        };
+    }
+}
+
+impl SaveState for Eeprom {
+    fn save_state(&self, state: &mut StateWriter) {
+        let (tag, address) = match self.state {
+            EepromState::Idle => (0, 0),
+            EepromState::Command => (1, 0),
+            EepromState::Read => (2, 0),
+            EepromState::Write(Some(address)) => (3, address),
+            EepromState::Write(None) => (4, 0),
+        };
+        state.write_u8(tag);
+        state.write_u8(address);
+        state.write_u8(self.lines);
+        state.write_bool(self.data_out);
+        state.write_u16(self.shift);
+        state.write_u8(self.bits);
+        state.write_u8(self.address);
+        state.write_bool(self.write_enable);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        let tag = state.read_u8()?;
+        let address = state.read_u8()?;
+        self.state = match tag {
+            0 => EepromState::Idle,
+            1 => EepromState::Command,
+            2 => EepromState::Read,
+            3 => EepromState::Write(Some(address)),
+            4 => EepromState::Write(None),
+            _ => return Err(SaveStateError::InvalidData("EEPROM state")),
+        };
+        self.lines = state.read_u8()?;
+        self.data_out = state.read_bool()?;
+        self.shift = state.read_u16()?;
+        self.bits = state.read_u8()?;
+        self.address = state.read_u8()? & 0x7F;
+        self.write_enable = state.read_bool()?;
+        Ok(())
+    }
+}
+
+// ADXL202 readings at rest and the change caused by a tilt of 1 g
+const ACCELEROMETER_CENTER: f32 = 0x81D0 as f32;
+const ACCELEROMETER_GRAVITY: f32 = 0x70 as f32;
+
+pub struct MBC7 {
+    data: Vec<u8>,
+    info: ROMInfo,
+    // Contents of the EEPROM
+    ram: Vec<u8>,
+    rom_bank: u16,
+    ram_enable: bool,
+    ram_enable_2: bool,
+    tilt_x: f32,
+    tilt_y: f32,
+    accelerometer_x: u16,
+    accelerometer_y: u16,
+    latch_ready: bool,
+    eeprom: Eeprom,
+}
+
+impl MBC7 {
+    fn new(data: Vec<u8>, info: ROMInfo) -> Self {
+        println!("MBC {:?}", info.mbc);
+        println!("Region {:?}", info.region);
+        println!("Has battery {}", info.has_battery);
+        println!("ROM banks {}", info.rom_banks);
+        Self {
+            data,
+            info,
+            ram: vec![0xFF; EEPROM_SIZE],
+            rom_bank: 1,
+            ram_enable: false,
+            ram_enable_2: false,
+            tilt_x: 0.0,
+            tilt_y: 0.0,
+            accelerometer_x: 0x8000,
+            accelerometer_y: 0x8000,
+            latch_ready: false,
+            eeprom: Eeprom::new(),
+        }
+    }
+
+    fn switch_rom_bank(&mut self, bank: u16) {
+        self.rom_bank = bank & 0x7F & self.info.rom_banks.saturating_sub(1);
+    }
+
+    fn registers_enabled(&self) -> bool {
+        self.ram_enable && self.ram_enable_2
+    }
+
+    fn accelerometer_value(tilt: f32) -> u16 {
+        (ACCELEROMETER_CENTER + (tilt.clamp(-1.0, 1.0) * ACCELEROMETER_GRAVITY)) as u16
+    }
+}
+
+impl SaveState for MBC7 {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u16(self.rom_bank);
+        state.write_bool(self.ram_enable);
+        state.write_bool(self.ram_enable_2);
+        state.write_u16(self.accelerometer_x);
+        state.write_u16(self.accelerometer_y);
+        state.write_bool(self.latch_ready);
+        self.eeprom.save_state(state);
+        state.write_bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.switch_rom_bank(state.read_u16()?);
+        self.ram_enable = state.read_bool()?;
+        self.ram_enable_2 = state.read_bool()?;
+        self.accelerometer_x = state.read_u16()?;
+        self.accelerometer_y = state.read_u16()?;
+        self.latch_ready = state.read_bool()?;
+        self.eeprom.load_state(state)?;
+        state.read_bytes_into(&mut self.ram)
+    }
+}
+
+impl ROM for MBC7 {
+    fn read(&self, address: u16) -> u8 {
+        if BANK_ZERO.contains(&address) {
+            return match self.data.get(address as usize) {
+                Some(byte) => *byte,
+                None => 0xFF,
+            };
+        } else if BANK_SWITCHABLE.contains(&address) {
+            return match self.data.get(rom_address(&self.data, self.rom_bank as usize, address)) {
+                Some(byte) => *byte,
+                None => 0xFF,
+            };
+        } else if (0xA000..=0xAFFF).contains(&address) && self.registers_enabled() {
+            let [x_high, x_low] = self.accelerometer_x.to_be_bytes();
+            let [y_high, y_low] = self.accelerometer_y.to_be_bytes();
+            return match (address >> 4) & 0x0F {
+                0x2 => x_low,
+                0x3 => x_high,
+                0x4 => y_low,
+                0x5 => y_high,
+                0x6 => 0x00,
+                0x8 => self.eeprom.read(),
+                _ => 0xFF,
+            };
+        }
+        0xFF
+    }
+
+    fn write(&mut self, address: u16, data: u8) {
+        if address <= 0x1FFF {
+            self.ram_enable = data == 0x0A;
+        } else if (0x2000..=0x3FFF).contains(&address) {
+            self.switch_rom_bank(data as u16);
+        } else if (0x4000..=0x5FFF).contains(&address) {
+            self.ram_enable_2 = data == 0x40;
+        } else if (0xA000..=0xAFFF).contains(&address) && self.registers_enabled() {
+            match (address >> 4) & 0x0F {
+                // Erase the latched values, then latch the current tilt
+                0x0 if data == 0x55 => {
+                    self.accelerometer_x = 0x8000;
+                    self.accelerometer_y = 0x8000;
+                    self.latch_ready = true;
+                },
+                0x1 if data == 0xAA && self.latch_ready => {
+                    self.accelerometer_x = MBC7::accelerometer_value(self.tilt_x);
+                    self.accelerometer_y = MBC7::accelerometer_value(self.tilt_y);
+                    self.latch_ready = false;
+                },
+                0x8 => self.eeprom.write(&mut self.ram, data),
+                _ => {},
+            };
+        }
+    }
+
+    fn ram_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.ram
+    }
+
+    fn ram(&self) -> &Vec<u8> {
+        &self.ram
+    }
+
+    fn info(&self) -> &ROMInfo {
+        &self.info
+    }
+
+    fn set_tilt(&mut self, x: f32, y: f32) {
+        self.tilt_x = x;
+        self.tilt_y = y;
+    }
+}
+
+pub struct HuC1 {
+    data: Vec<u8>,
+    info: ROMInfo,
+    ram: Vec<u8>,
+    rom_bank: u16,
+    ram_bank: u8,
+    // External RAM area is mapped to the infrared port instead of the RAM
+    ir_mode: bool,
+    infrared: InfraredPort,
+}
+
+impl HuC1 {
+    fn new(data: Vec<u8>, info: ROMInfo) -> Self {
+        println!("MBC {:?}", info.mbc);
+        println!("Region {:?}", info.region);
+        println!("Has battery {}", info.has_battery);
+        println!("ROM banks {}", info.rom_banks);
+        println!("RAM banks {}", info.ram_banks);
+        let ram = vec![0; info.ram_size()];
+        Self {
+            data,
+            info,
+            ram,
+            rom_bank: 1,
+            ram_bank: 0,
+            ir_mode: false,
+            infrared: InfraredPort::default(),
+        }
+    }
+
+    fn switch_rom_bank(&mut self, bank: u16) {
+        self.rom_bank = bank & 0b111111 & self.info.rom_banks.saturating_sub(1);
+    }
+
+    fn get_ram_address(&self, address: u16) -> usize {
+        let bank = self.ram_bank as usize & (self.info.ram_banks as usize).saturating_sub(1);
+        (0x2000 * bank) + (address as usize - 0xA000)
+    }
+}
+
+impl SaveState for HuC1 {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u16(self.rom_bank);
+        state.write_u8(self.ram_bank);
+        state.write_bool(self.ir_mode);
+        self.infrared.save_state(state);
+        state.write_bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.switch_rom_bank(state.read_u16()?);
+        self.ram_bank = state.read_u8()? & 0b11;
+        self.ir_mode = state.read_bool()?;
+        self.infrared.load_state(state)?;
+        state.read_bytes_into(&mut self.ram)
+    }
+}
+
+impl ROM for HuC1 {
+    fn read(&self, address: u16) -> u8 {
+        if BANK_ZERO.contains(&address) {
+            return match self.data.get(address as usize) {
+                Some(byte) => *byte,
+                None => 0xFF,
+            };
+        } else if BANK_SWITCHABLE.contains(&address) {
+            return match self.data.get(rom_address(&self.data, self.rom_bank as usize, address)) {
+                Some(byte) => *byte,
+                None => 0xFF,
+            };
+        } else if EXTERNAL_RAM.contains(&address) {
+            if self.ir_mode {
+                return self.infrared.read();
+            }
+            return match self.ram.get(self.get_ram_address(address)) {
+                Some(data) => *data,
+                None => 0xFF,
+            };
+        }
+        0xFF
+    }
+
+    fn write(&mut self, address: u16, data: u8) {
+        if address <= 0x1FFF {
+            self.ir_mode = data == 0x0E;
+        } else if (0x2000..=0x3FFF).contains(&address) {
+            self.switch_rom_bank(data as u16);
+        } else if (0x4000..=0x5FFF).contains(&address) {
+            self.ram_bank = data & 0b11;
+        } else if EXTERNAL_RAM.contains(&address) {
+            if self.ir_mode {
+                self.infrared.write(data);
+                return;
+            }
+            let address = self.get_ram_address(address);
+            if let Some(elem) = self.ram.get_mut(address) {
+                *elem = data;
+            }
+        }
+    }
+
+    fn ram_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.ram
+    }
+
+    fn ram(&self) -> &Vec<u8> {
+        &self.ram
+    }
+
+    fn info(&self) -> &ROMInfo {
+        &self.info
+    }
+
+    fn set_infrared_peer(&mut self, peer: Option<Box<dyn InfraredPeer>>) {
+        self.infrared.set_peer(peer);
+    }
+}
+
+// MMM01 carts boot into a menu stored in the last 32 KiB of the ROM, so the header describing
+// the cartridge is found there while the one at the start belongs to the first game
+fn mmm01_menu_offset(data: &[u8]) -> Option<usize> {
+    let offset = data.len().checked_sub(0x8000)?;
+    if offset == 0 {
+        return None;
+    }
+    let header = &data[offset..];
+    let is_mmm01 = (0x0B..=0x0D).contains(&header[CARTRIDGE_TYPE_ADDRESS as usize]);
+    match is_mmm01 && header_checksum(header) == header[HEADER_CHECKSUM_ADDRESS as usize] {
+        true => Some(offset),
+        false => None,
+    }
+}
+
+pub struct MMM01 {
+    data: Vec<u8>,
+    info: ROMInfo,
+    ram: Vec<u8>,
+    // Until the menu maps a game, the upper ROM bank bits are forced to 1
+    mapped: bool,
+    ram_enable: bool,
+    rom_bank_low: u8,
+    rom_bank_mid: u8,
+    rom_bank_high: u8,
+    ram_bank_low: u8,
+    ram_bank_high: u8,
+    // Bits of the low bank registers that can no longer be written by the game
+    rom_bank_mask: u8,
+    ram_bank_mask: u8,
+    banking_mode: BankingMode,
+    banking_mode_locked: bool,
+    // Swaps the middle ROM bank bits with the low RAM bank bits, like a MBC1
+    multiplex: bool,
+}
+
+impl MMM01 {
+    fn new(data: Vec<u8>, info: ROMInfo) -> Self {
+        println!("MBC {:?}", info.mbc);
+        println!("Region {:?}", info.region);
+        println!("Has RAM {}", info.has_ram);
+        println!("Has battery {}", info.has_battery);
+        println!("ROM banks {}", info.rom_banks);
+        println!("RAM banks {}", info.ram_banks);
+        let ram = vec![0; info.ram_size()];
+        Self {
+            data,
+            info,
+            ram,
+            mapped: false,
+            ram_enable: false,
+            rom_bank_low: 0,
+            rom_bank_mid: 0,
+            rom_bank_high: 0,
+            ram_bank_low: 0,
+            ram_bank_high: 0,
+            rom_bank_mask: 0,
+            ram_bank_mask: 0,
+            banking_mode: BankingMode::Simple,
+            banking_mode_locked: false,
+            multiplex: false,
+        }
+    }
+
+    fn write_rom_bank_low(&mut self, data: u8) {
+        let mask = self.rom_bank_mask << 1;
+        self.rom_bank_low = (self.rom_bank_low & mask) | (data & 0b11111 & !mask);
+    }
+
+    fn write_ram_bank_low(&mut self, data: u8) {
+        let mask = self.ram_bank_mask;
+        self.ram_bank_low = (self.ram_bank_low & mask) | (data & 0b11 & !mask);
+    }
+
+    fn get_rom_address(&self, low: u8, mid: u8, address: u16) -> usize {
+        let mut bank = ((self.rom_bank_high as usize) << 7) | ((mid as usize) << 5) | low as usize;
+        if !self.mapped {
+            bank |= 0x1FE;
+        }
+        let bank = bank & (self.info.rom_banks as usize).saturating_sub(1);
+        (0x4000 * bank) + (address as usize & 0x3FFF)
+    }
+
+    fn get_bank_zero_address(&self, address: u16) -> usize {
+        // Only the locked bits select the first bank of the mapped game
+        let low = self.rom_bank_low & (self.rom_bank_mask << 1);
+        let mid = match (self.multiplex, self.banking_mode) {
+            (false, _) => self.rom_bank_mid,
+            (true, BankingMode::Simple) => 0,
+            (true, BankingMode::Advanced) => self.ram_bank_low,
+        };
+        self.get_rom_address(low, mid, address)
+    }
+
+    fn get_bank_switchable_address(&self, address: u16) -> usize {
+        // The bank 0 check only looks at the bits the game can still write
+        let low = match self.rom_bank_low & !(self.rom_bank_mask << 1) {
+            0 => self.rom_bank_low | 1,
+            _ => self.rom_bank_low,
+        };
+        let mid = match self.multiplex {
+            true => self.ram_bank_low,
+            false => self.rom_bank_mid,
+        };
+        self.get_rom_address(low, mid, address)
+    }
+
+    fn get_ram_address(&self, address: u16) -> usize {
+        let low = match (self.multiplex, self.banking_mode) {
+            (false, _) => self.ram_bank_low,
+            (true, BankingMode::Simple) => 0,
+            (true, BankingMode::Advanced) => self.rom_bank_mid,
+        };
+        let bank = ((self.ram_bank_high as usize) << 2) | low as usize;
+        let bank = bank & (self.info.ram_banks as usize).saturating_sub(1);
+        (0x2000 * bank) + (address as usize - 0xA000)
+    }
+}
+
+impl SaveState for MMM01 {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bool(self.mapped);
+        state.write_bool(self.ram_enable);
+        state.write_u8(self.rom_bank_low);
+        state.write_u8(self.rom_bank_mid);
+        state.write_u8(self.rom_bank_high);
+        state.write_u8(self.ram_bank_low);
+        state.write_u8(self.ram_bank_high);
+        state.write_u8(self.rom_bank_mask);
+        state.write_u8(self.ram_bank_mask);
+        state.write_bool(self.banking_mode == BankingMode::Advanced);
+        state.write_bool(self.banking_mode_locked);
+        state.write_bool(self.multiplex);
+        state.write_bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.mapped = state.read_bool()?;
+        self.ram_enable = state.read_bool()?;
+        self.rom_bank_low = state.read_u8()? & 0b11111;
+        self.rom_bank_mid = state.read_u8()? & 0b11;
+        self.rom_bank_high = state.read_u8()? & 0b11;
+        self.ram_bank_low = state.read_u8()? & 0b11;
+        self.ram_bank_high = state.read_u8()? & 0b11;
+        self.rom_bank_mask = state.read_u8()? & 0b1111;
+        self.ram_bank_mask = state.read_u8()? & 0b11;
+        self.banking_mode = match state.read_bool()? {
+            true => BankingMode::Advanced,
+            false => BankingMode::Simple,
+        };
+        self.banking_mode_locked = state.read_bool()?;
+        self.multiplex = state.read_bool()?;
+        state.read_bytes_into(&mut self.ram)
+    }
+}
+
+impl ROM for MMM01 {
+    fn read(&self, address: u16) -> u8 {
+        let address = if BANK_ZERO.contains(&address) {
+            self.get_bank_zero_address(address)
+        } else if BANK_SWITCHABLE.contains(&address) {
+            self.get_bank_switchable_address(address)
+        } else if EXTERNAL_RAM.contains(&address) && self.ram_enable {
+            return match self.ram.get(self.get_ram_address(address)) {
+                Some(data) => *data,
+                None => 0xFF,
+            };
+        } else {
+            return 0xFF;
+        };
+        match self.data.get(address) {
+            Some(byte) => *byte,
+            None => 0xFF,
+        }
+    }
+
+    // The registers behave like a MBC1, plus extra bits that only the menu can write
+    fn write(&mut self, address: u16, data: u8) {
+        if address <= 0x1FFF {
+            self.ram_enable = data & 0x0F == 0x0A;
+            if !self.mapped {
+                self.ram_bank_mask = (data >> 4) & 0b11;
+                self.mapped = data & 0x40 != 0;
+            }
+        } else if (0x2000..=0x3FFF).contains(&address) {
+            self.write_rom_bank_low(data);
+            if !self.mapped {
+                self.rom_bank_mid = (data >> 5) & 0b11;
+            }
+        } else if (0x4000..=0x5FFF).contains(&address) {
+            self.write_ram_bank_low(data);
+            if !self.mapped {
+                self.ram_bank_high = (data >> 2) & 0b11;
+                self.rom_bank_high = (data >> 4) & 0b11;
+                self.banking_mode_locked = data & 0x40 != 0;
+            }
+        } else if (0x6000..=0x7FFF).contains(&address) {
+            if !self.banking_mode_locked {
+                self.banking_mode = match data & 1 {
+                    0 => BankingMode::Simple,
+                    _ => BankingMode::Advanced,
+                };
+            }
+            if !self.mapped {
+                self.rom_bank_mask = (data >> 2) & 0b1111;
+                self.multiplex = data & 0x40 != 0;
+            }
+        } else if EXTERNAL_RAM.contains(&address) && self.ram_enable {
+            let address = self.get_ram_address(address);
+            if let Some(elem) = self.ram.get_mut(address) {
+                *elem = data;
+            }
+        }
+    }
+
+    fn ram_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.ram
+    }
+
+    fn ram(&self) -> &Vec<u8> {
+        &self.ram
+    }
+
+    fn info(&self) -> &ROMInfo {
+        &self.info
+    }
+}
+
+// The captured picture is written to the first RAM bank, right after this offset
+const CAMERA_IMAGE_ADDRESS: usize = 0x100;
+const CAMERA_REGISTERS_BIT: u8 = 0b0001_0000;
+
+pub struct PocketCamera {
+    data: Vec<u8>,
+    info: ROMInfo,
+    ram: Vec<u8>,
+    rom_bank: u16,
+    ram_bank: u8,
+    ram_enable: bool,
+    // Bit 4 of the RAM bank register maps the sensor registers instead of RAM
+    registers_selected: bool,
+    sensor: M64282FP,
+    now: u64,
+}
+
+impl PocketCamera {
+    fn new(data: Vec<u8>, info: ROMInfo) -> Self {
+        println!("MBC {:?}", info.mbc);
+        println!("Region {:?}", info.region);
+        println!("Has battery {}", info.has_battery);
+        println!("ROM banks {}", info.rom_banks);
+        println!("RAM banks {}", info.ram_banks);
+        let ram = vec![0; info.ram_size()];
+        Self {
+            data,
+            info,
+            ram,
+            rom_bank: 1,
+            ram_bank: 0,
+            ram_enable: false,
+            registers_selected: false,
+            sensor: M64282FP::new(),
+            now: 0,
+        }
+    }
+
+    fn switch_rom_bank(&mut self, bank: u16) {
+        self.rom_bank = bank & 0x3F & self.info.rom_banks.saturating_sub(1);
+    }
+
+    fn get_ram_address(&self, address: u16) -> usize {
+        let bank = self.ram_bank as usize & (self.info.ram_banks as usize).saturating_sub(1);
+        (0x2000 * bank) + (address as usize - 0xA000)
+    }
+}
+
+impl SaveState for PocketCamera {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u16(self.rom_bank);
+        state.write_u8(self.ram_bank);
+        state.write_bool(self.ram_enable);
+        state.write_bool(self.registers_selected);
+        self.sensor.save_state(state);
+        state.write_bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.switch_rom_bank(state.read_u16()?);
+        self.ram_bank = state.read_u8()? & 0x0F;
+        self.ram_enable = state.read_bool()?;
+        self.registers_selected = state.read_bool()?;
+        self.sensor.load_state(state)?;
+        state.read_bytes_into(&mut self.ram)
+    }
+}
+
+impl ROM for PocketCamera {
+    fn read(&self, address: u16) -> u8 {
+        if BANK_ZERO.contains(&address) {
+            return match self.data.get(address as usize) {
+                Some(byte) => *byte,
+                None => 0xFF,
+            };
+        } else if BANK_SWITCHABLE.contains(&address) {
+            return match self.data.get(rom_address(&self.data, self.rom_bank as usize, address)) {
+                Some(byte) => *byte,
+                None => 0xFF,
+            };
+        } else if EXTERNAL_RAM.contains(&address) {
+            // RAM can be read even when it's disabled, but not while the sensor is busy
+            return match (self.registers_selected, self.sensor.is_capturing()) {
+                (true, _) => self.sensor.read_register(address as usize & 0x7F),
+                (false, true) => 0x00,
+                (false, false) => match self.ram.get(self.get_ram_address(address)) {
+                    Some(data) => *data,
+                    None => 0xFF,
+                },
+            };
+        }
+        0xFF
+    }
+
+    fn write(&mut self, address: u16, data: u8) {
+        if address <= 0x1FFF {
+            self.ram_enable = data & 0x0F == 0x0A;
+        } else if (0x2000..=0x3FFF).contains(&address) {
+            self.switch_rom_bank(data as u16);
+        } else if (0x4000..=0x5FFF).contains(&address) {
+            self.registers_selected = data & CAMERA_REGISTERS_BIT != 0;
+            self.ram_bank = data & 0x0F;
+        } else if EXTERNAL_RAM.contains(&address) {
+            if self.registers_selected {
+                self.sensor.write_register(address as usize & 0x7F, data, self.now);
+            } else if self.ram_enable {
+                let address = self.get_ram_address(address);
+                if let Some(elem) = self.ram.get_mut(address) {
+                    *elem = data;
+                }
+            }
+        }
+    }
+
+    fn ram_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.ram
+    }
+
+    fn ram(&self) -> &Vec<u8> {
+        &self.ram
+    }
+
+    fn info(&self) -> &ROMInfo {
+        &self.info
+    }
+
+    fn catch_up(&mut self, now: u64) {
+        self.now = now;
+        // Images with a smaller RAM than the header of the real cartridge just lose the picture
+        if let Some(image) = self.sensor.update(now) {
+            if let Some(ram) = self.ram.get_mut(CAMERA_IMAGE_ADDRESS..CAMERA_IMAGE_ADDRESS + CAMERA_IMAGE_SIZE) {
+                ram.copy_from_slice(&image);
+            }
+        }
+    }
+
+    fn set_camera_image(&mut self, image: &[u8]) {
+        self.sensor.set_image(image);
+    }
+}
+
+const HUC3_RTC_FOOTER_SIZE: usize = 17;
+const MINUTES_PER_DAY: u64 = 24 * 60;
+
+// The HuC3 clock only counts minutes and days, plus an alarm used to wake the cartridge speaker.
+// Its values are accessed one nibble at a time through the command interface
+struct HuC3Clock {
+    minutes: u16,
+    days: u16,
+    alarm_minutes: u16,
+    alarm_days: u16,
+    alarm_enabled: bool,
+    // Host time of the last whole minute that was counted
+    timestamp: u64,
+}
+
+impl HuC3Clock {
+    fn new(now: u64) -> Self {
+        Self {
+            minutes: 0,
+            days: 0,
+            alarm_minutes: 0,
+            alarm_days: 0,
+            alarm_enabled: false,
+            timestamp: now,
+        }
+    }
+
+    fn update(&mut self, now: u64) {
+        let elapsed = now.saturating_sub(self.timestamp) / 60;
+        self.timestamp += elapsed * 60;
+        let minutes = self.minutes as u64 + elapsed;
+        self.minutes = (minutes % MINUTES_PER_DAY) as u16;
+        self.days = self.days.wrapping_add((minutes / MINUTES_PER_DAY) as u16);
+    }
+
+    fn nibble(value: u16, index: u8) -> u8 {
+        ((value >> (index * 4)) & 0x0F) as u8
+    }
+
+    fn set_nibble(value: &mut u16, index: u8, data: u8) {
+        let shift = index * 4;
+        *value = (*value & !(0x0F << shift)) | (((data & 0x0F) as u16) << shift);
+    }
+
+    fn read(&self, address: u8) -> u8 {
+        match address {
+            0x00..=0x02 => HuC3Clock::nibble(self.minutes, address),
+            0x03..=0x06 => HuC3Clock::nibble(self.days, address - 0x03),
+            0x58..=0x5A => HuC3Clock::nibble(self.alarm_minutes, address - 0x58),
+            0x5B..=0x5E => HuC3Clock::nibble(self.alarm_days, address - 0x5B),
+            0x5F => self.alarm_enabled as u8,
+            _ => 0,
+        }
+    }
+
+    fn write(&mut self, address: u8, data: u8) {
+        match address {
+            0x00..=0x02 => HuC3Clock::set_nibble(&mut self.minutes, address, data),
+            0x03..=0x06 => HuC3Clock::set_nibble(&mut self.days, address - 0x03, data),
+            0x58..=0x5A => HuC3Clock::set_nibble(&mut self.alarm_minutes, address - 0x58, data),
+            0x5B..=0x5E => HuC3Clock::set_nibble(&mut self.alarm_days, address - 0x5B, data),
+            0x5F => self.alarm_enabled = data & 1 != 0,
+            _ => {},
+        };
+    }
+
+    // Same layout SameBoy uses: the timestamp followed by every counter, all little endian
+    fn to_footer(&self) -> Vec<u8> {
+        let mut footer = Vec::with_capacity(HUC3_RTC_FOOTER_SIZE);
+        footer.extend_from_slice(&self.timestamp.to_le_bytes());
+        footer.extend_from_slice(&self.minutes.to_le_bytes());
+        footer.extend_from_slice(&self.days.to_le_bytes());
+        footer.extend_from_slice(&self.alarm_minutes.to_le_bytes());
+        footer.extend_from_slice(&self.alarm_days.to_le_bytes());
+        footer.push(self.alarm_enabled as u8);
+        footer
+    }
+
+    fn from_footer(footer: &[u8]) -> Option<Self> {
+        if footer.len() != HUC3_RTC_FOOTER_SIZE {
+            return None;
+        }
+        let word = |index: usize| u16::from_le_bytes([footer[index], footer[index + 1]]);
+        let mut timestamp = [0; 8];
+        timestamp.copy_from_slice(&footer[0..8]);
+        Some(Self {
+            minutes: word(8) % MINUTES_PER_DAY as u16,
+            days: word(10),
+            alarm_minutes: word(12),
+            alarm_days: word(14),
+            alarm_enabled: footer[16] & 1 != 0,
+            timestamp: u64::from_le_bytes(timestamp),
+        })
+    }
+}
+
+impl SaveState for HuC3Clock {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.to_footer());
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        let mut footer = [0; HUC3_RTC_FOOTER_SIZE];
+        state.read_bytes_into(&mut footer)?;
+        *self = HuC3Clock::from_footer(&footer).unwrap();
+        Ok(())
+    }
+}
+
+pub struct HuC3 {
+    data: Vec<u8>,
+    info: ROMInfo,
+    ram: Vec<u8>,
+    rom_bank: u16,
+    ram_bank: u8,
+    // Selects what the external RAM area is mapped to
+    mode: u8,
+    // Upper nibble holds the last command and the lower one its result
+    result: u8,
+    access_address: u8,
+    access_flags: u8,
+    clock: HuC3Clock,
+    infrared: InfraredPort,
+    // Tone requested from the speaker, the sound itself isn't generated
+    tone: Option<u8>,
+}
+
+impl HuC3 {
+    fn new(data: Vec<u8>, info: ROMInfo) -> Self {
+        println!("MBC {:?}", info.mbc);
+        println!("Region {:?}", info.region);
+        println!("Has battery {}", info.has_battery);
+        println!("ROM banks {}", info.rom_banks);
+        println!("RAM banks {}", info.ram_banks);
+        let ram = vec![0; info.ram_size()];
+        Self {
+            data,
+            info,
+            ram,
+            rom_bank: 1,
+            ram_bank: 0,
+            mode: 0,
+            result: 0,
+            access_address: 0,
+            access_flags: 0,
+            clock: HuC3Clock::new(unix_time()),
+            infrared: InfraredPort::default(),
+            tone: None,
+        }
+    }
+
+    fn switch_rom_bank(&mut self, bank: u16) {
+        self.rom_bank = bank & 0x7F & self.info.rom_banks.saturating_sub(1);
+    }
+
+    fn get_ram_address(&self, address: u16) -> usize {
+        let bank = self.ram_bank as usize & (self.info.ram_banks as usize).saturating_sub(1);
+        (0x2000 * bank) + (address as usize - 0xA000)
+    }
+
+    fn run_command(&mut self, data: u8) {
+        let command = (data >> 4) & 0x07;
+        let argument = data & 0x0F;
+        self.clock.update(unix_time());
+        let mut result = 0;
+        match command {
+            // Read and increment the address
+            0x1 => {
+                result = self.clock.read(self.access_address);
+                self.access_address = self.access_address.wrapping_add(1);
+            },
+            // Write, 0x3 also increments the address
+            0x2 | 0x3 => {
+                self.clock.write(self.access_address, argument);
+                if command == 0x3 {
+                    self.access_address = self.access_address.wrapping_add(1);
+                }
+            },
+            0x4 => self.access_address = (self.access_address & 0xF0) | argument,
+            0x5 => self.access_address = (self.access_address & 0x0F) | (argument << 4),
+            // Extended commands, 0x62 asks whether the clock is ready. The rest drive
+            // the speaker: 0x60 silences it and any other argument selects a tone
+            0x6 => {
+                self.access_flags = argument;
+                match argument {
+                    0x0 => self.tone = None,
+                    0x2 => result = 1,
+                    tone => self.tone = Some(tone),
+                };
+            },
+            _ => {},
+        };
+        self.result = (command << 4) | result;
+    }
+}
+
+impl SaveState for HuC3 {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u16(self.rom_bank);
+        state.write_u8(self.ram_bank);
+        state.write_u8(self.mode);
+        state.write_u8(self.result);
+        state.write_u8(self.access_address);
+        state.write_u8(self.access_flags);
+        state.write_u8(self.tone.unwrap_or(0));
+        self.infrared.save_state(state);
+        self.clock.save_state(state);
+        state.write_bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.switch_rom_bank(state.read_u16()?);
+        self.ram_bank = state.read_u8()? & 0b11;
+        self.mode = state.read_u8()? & 0x0F;
+        self.result = state.read_u8()?;
+        self.access_address = state.read_u8()?;
+        self.access_flags = state.read_u8()?;
+        self.tone = match state.read_u8()? & 0x0F {
+            0 => None,
+            tone => Some(tone),
+        };
+        self.infrared.load_state(state)?;
+        self.clock.load_state(state)?;
+        state.read_bytes_into(&mut self.ram)
+    }
+}
+
+impl ROM for HuC3 {
+    fn read(&self, address: u16) -> u8 {
+        if BANK_ZERO.contains(&address) {
+            return match self.data.get(address as usize) {
+                Some(byte) => *byte,
+                None => 0xFF,
+            };
+        } else if BANK_SWITCHABLE.contains(&address) {
+            return match self.data.get(rom_address(&self.data, self.rom_bank as usize, address)) {
+                Some(byte) => *byte,
+                None => 0xFF,
+            };
+        } else if EXTERNAL_RAM.contains(&address) {
+            return match self.mode {
+                0x0 | 0xA => match self.ram.get(self.get_ram_address(address)) {
+                    Some(data) => *data,
+                    None => 0xFF,
+                },
+                0xC => self.result,
+                // Commands run instantly, so the clock is always ready
+                0xD => 0x01,
+                0xE => self.infrared.read(),
+                _ => 0xFF,
+            };
+        }
+        0xFF
+    }
+
+    fn write(&mut self, address: u16, data: u8) {
+        if address <= 0x1FFF {
+            self.mode = data & 0x0F;
+        } else if (0x2000..=0x3FFF).contains(&address) {
+            self.switch_rom_bank(data as u16);
+        } else if (0x4000..=0x5FFF).contains(&address) {
+            self.ram_bank = data & 0b11;
+        } else if EXTERNAL_RAM.contains(&address) {
+            match self.mode {
+                0xA => {
+                    let address = self.get_ram_address(address);
+                    if let Some(elem) = self.ram.get_mut(address) {
+                        *elem = data;
+                    }
+                },
+                0xB => self.run_command(data),
+                0xE => self.infrared.write(data),
+                _ => {},
+            };
+        }
+    }
+
+    fn ram_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.ram
+    }
+
+    fn ram(&self) -> &Vec<u8> {
+        &self.ram
+    }
+
+    fn info(&self) -> &ROMInfo {
+        &self.info
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        let mut data = self.ram.clone();
+        data.extend(self.clock.to_footer());
+        data
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        copy_save(&mut self.ram, data);
+        if let Some(clock) = data.get(self.ram.len()..).and_then(HuC3Clock::from_footer) {
+            self.clock = clock;
+            self.clock.update(unix_time());
+        }
+    }
+
+    fn set_infrared_peer(&mut self, peer: Option<Box<dyn InfraredPeer>>) {
+        self.infrared.set_peer(peer);
+    }
+
+    fn tone(&self) -> Option<u8> {
+        self.tone
+    }
+}
+
+// The TAMA6 microcontroller keeps 32 bytes of RAM and a calendar clock
+const TAMA5_RAM_SIZE: usize = 0x20;
+const TAMA5_CLOCK_FOOTER_SIZE: usize = 17;
+
+const TAMA5_ROM_BANK_LOW: u8 = 0x0;
+const TAMA5_ROM_BANK_HIGH: u8 = 0x1;
+const TAMA5_DATA_LOW: u8 = 0x4;
+const TAMA5_DATA_HIGH: u8 = 0x5;
+const TAMA5_ADDRESS_HIGH: u8 = 0x6;
+const TAMA5_ADDRESS_LOW: u8 = 0x7;
+const TAMA5_READY: u8 = 0xA;
+const TAMA5_RESULT_LOW: u8 = 0xC;
+const TAMA5_RESULT_HIGH: u8 = 0xD;
+
+fn days_in_month(month: u8, year: u8) -> u8 {
+    match month {
+        2 if year & 0b11 == 0 => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Calendar clock counting in host time. Its registers are BCD digits laid out like
+// a RP5C01, with the alarm in the second page
+struct Tama5Clock {
+    seconds: u8,
+    minutes: u8,
+    hours: u8,
+    weekday: u8,
+    day: u8,
+    month: u8,
+    year: u8,
+    alarm_minutes: u8,
+    alarm_hours: u8,
+    timestamp: u64,
+}
+
+impl Tama5Clock {
+    fn new(now: u64) -> Self {
+        Self {
+            seconds: 0,
+            minutes: 0,
+            hours: 0,
+            weekday: 0,
+            day: 1,
+            month: 1,
+            year: 0,
+            alarm_minutes: 0,
+            alarm_hours: 0,
+            timestamp: now,
+        }
+    }
+
+    fn next_day(&mut self) {
+        self.weekday = (self.weekday + 1) % 7;
+        self.day += 1;
+        if self.day > days_in_month(self.month, self.year) {
+            self.day = 1;
+            self.month += 1;
+            if self.month > 12 {
+                self.month = 1;
+                self.year = (self.year + 1) % 100;
+            }
+        }
+    }
+
+    fn update(&mut self, now: u64) {
+        let elapsed = now.saturating_sub(self.timestamp);
+        self.timestamp += elapsed;
+        let seconds = self.seconds as u64 + elapsed;
+        let minutes = self.minutes as u64 + (seconds / 60);
+        let hours = self.hours as u64 + (minutes / 60);
+        self.seconds = (seconds % 60) as u8;
+        self.minutes = (minutes % 60) as u8;
+        self.hours = (hours % 24) as u8;
+        for _ in 0..hours / 24 {
+            self.next_day();
+        }
+    }
+
+    // Keeps the counters in range after a write, the way the hardware wraps them
+    fn normalize(&mut self) {
+        self.seconds %= 60;
+        self.minutes %= 60;
+        self.hours %= 24;
+        self.weekday %= 7;
+        self.month = self.month.clamp(1, 12);
+        self.day = self.day.clamp(1, days_in_month(self.month, self.year));
+        self.year %= 100;
+        self.alarm_minutes %= 60;
+        self.alarm_hours %= 24;
+    }
+
+    fn counter(&mut self, register: u8) -> Option<&mut u8> {
+        match register {
+            0x00 | 0x01 => Some(&mut self.seconds),
+            0x02 | 0x03 => Some(&mut self.minutes),
+            0x04 | 0x05 => Some(&mut self.hours),
+            0x06 => Some(&mut self.weekday),
+            0x07 | 0x08 => Some(&mut self.day),
+            0x09 | 0x0A => Some(&mut self.month),
+            0x0B | 0x0C => Some(&mut self.year),
+            0x12 | 0x13 => Some(&mut self.alarm_minutes),
+            0x14 | 0x15 => Some(&mut self.alarm_hours),
+            _ => None,
+        }
+    }
+
+    // Every counter takes a register for the ones and the next one for the tens,
+    // except for the weekday that only has a single digit
+    fn is_tens(register: u8) -> bool {
+        matches!(register, 0x01 | 0x03 | 0x05 | 0x08 | 0x0A | 0x0C | 0x13 | 0x15)
+    }
+
+    fn read(&mut self, register: u8) -> u8 {
+        let tens = Tama5Clock::is_tens(register);
+        match self.counter(register) {
+            Some(value) if tens => *value / 10,
+            Some(value) if register == 0x06 => *value,
+            Some(value) => *value % 10,
+            None => 0,
+        }
+    }
+
+    fn write(&mut self, register: u8, data: u8) {
+        let digit = data & 0x0F;
+        let tens = Tama5Clock::is_tens(register);
+        match self.counter(register) {
+            Some(value) if tens => *value = (digit * 10) + (*value % 10),
+            Some(value) if register == 0x06 => *value = digit,
+            Some(value) => *value = ((*value / 10) * 10) + digit,
+            None => {},
+        };
+        self.normalize();
+    }
+
+    fn to_footer(&self) -> Vec<u8> {
+        let mut footer = Vec::with_capacity(TAMA5_CLOCK_FOOTER_SIZE);
+        footer.extend_from_slice(&self.timestamp.to_le_bytes());
+        footer.extend_from_slice(&[
+            self.seconds,
+            self.minutes,
+            self.hours,
+            self.weekday,
+            self.day,
+            self.month,
+            self.year,
+            self.alarm_minutes,
+            self.alarm_hours,
+        ]);
+        footer
+    }
+
+    fn from_footer(footer: &[u8]) -> Option<Self> {
+        if footer.len() != TAMA5_CLOCK_FOOTER_SIZE {
+            return None;
+        }
+        let mut timestamp = [0; 8];
+        timestamp.copy_from_slice(&footer[0..8]);
+        let mut clock = Self {
+            seconds: footer[8],
+            minutes: footer[9],
+            hours: footer[10],
+            weekday: footer[11],
+            day: footer[12],
+            month: footer[13],
+            year: footer[14],
+            alarm_minutes: footer[15],
+            alarm_hours: footer[16],
+            timestamp: u64::from_le_bytes(timestamp),
+        };
+        clock.normalize();
+        Some(clock)
+    }
+}
+
+impl SaveState for Tama5Clock {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.to_footer());
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        let mut footer = [0; TAMA5_CLOCK_FOOTER_SIZE];
+        state.read_bytes_into(&mut footer)?;
+        *self = Tama5Clock::from_footer(&footer).unwrap();
+        Ok(())
+    }
+}
+
+// Bandai TAMA5. Everything goes through 0xA001, which selects a register, and 0xA000,
+// which reads or writes 4 bits of it. Writing the low address runs the command in
+// the upper address register: 0 writes RAM, 1 reads RAM, 2 writes the clock and 3 reads it
+pub struct TAMA5 {
+    data: Vec<u8>,
+    info: ROMInfo,
+    ram: Vec<u8>,
+    rom_bank: u8,
+    register: u8,
+    registers: [u8; 0x10],
+    result: u8,
+    clock: Tama5Clock,
+}
+
+impl TAMA5 {
+    fn new(data: Vec<u8>, info: ROMInfo) -> Self {
+        println!("MBC {:?}", info.mbc);
+        println!("Region {:?}", info.region);
+        println!("Has battery {}", info.has_battery);
+        println!("ROM banks {}", info.rom_banks);
+        Self {
+            data,
+            info,
+            ram: vec![0; TAMA5_RAM_SIZE],
+            rom_bank: 1,
+            register: 0,
+            registers: [0; 0x10],
+            result: 0,
+            clock: Tama5Clock::new(unix_time()),
+        }
+    }
+
+    fn switch_rom_bank(&mut self) {
+        let bank = ((self.registers[TAMA5_ROM_BANK_HIGH as usize] & 1) << 4) | self.registers[TAMA5_ROM_BANK_LOW as usize];
+        self.rom_bank = bank & (self.info.rom_banks as u8).wrapping_sub(1);
+    }
+
+    fn run_command(&mut self) {
+        let address_high = self.registers[TAMA5_ADDRESS_HIGH as usize];
+        let address = ((address_high & 1) << 4) | self.registers[TAMA5_ADDRESS_LOW as usize];
+        let data = (self.registers[TAMA5_DATA_HIGH as usize] << 4) | self.registers[TAMA5_DATA_LOW as usize];
+        match address_high >> 1 {
+            0 => self.ram[address as usize] = data,
+            1 => self.result = self.ram[address as usize],
+            2 => {
+                self.clock.update(unix_time());
+                self.clock.write(address, data);
+            },
+            3 => {
+                self.clock.update(unix_time());
+                self.result = self.clock.read(address);
+            },
+            _ => {},
+        };
+    }
+}
+
+impl SaveState for TAMA5 {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.rom_bank);
+        state.write_u8(self.register);
+        state.write_bytes(&self.registers);
+        state.write_u8(self.result);
+        self.clock.save_state(state);
+        state.write_bytes(&self.ram);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.rom_bank = state.read_u8()?;
+        self.register = state.read_u8()? & 0x0F;
+        state.read_bytes_into(&mut self.registers)?;
+        for register in self.registers.iter_mut() {
+            *register &= 0x0F;
+        }
+        self.switch_rom_bank();
+        self.result = state.read_u8()?;
+        self.clock.load_state(state)?;
+        state.read_bytes_into(&mut self.ram)
+    }
+}
+
+impl ROM for TAMA5 {
+    fn read(&self, address: u16) -> u8 {
+        if BANK_ZERO.contains(&address) {
+            return match self.data.get(address as usize) {
+                Some(byte) => *byte,
+                None => 0xFF,
+            };
+        } else if BANK_SWITCHABLE.contains(&address) {
+            return match self.data.get(rom_address(&self.data, self.rom_bank as usize, address)) {
+                Some(byte) => *byte,
+                None => 0xFF,
+            };
+        } else if EXTERNAL_RAM.contains(&address) && address & 1 == 0 {
+            // Commands finish instantly, so the chip always reports being ready
+            return match self.register {
+                TAMA5_READY => 0xF1,
+                TAMA5_RESULT_LOW => 0xF0 | (self.result & 0x0F),
+                TAMA5_RESULT_HIGH => 0xF0 | (self.result >> 4),
+                _ => 0xFF,
+            };
+        }
+        0xFF
+    }
+
+    fn write(&mut self, address: u16, data: u8) {
+        if !EXTERNAL_RAM.contains(&address) {
+            return;
+        }
+        if address & 1 == 1 {
+            self.register = data & 0x0F;
+            return;
+        }
+        self.registers[self.register as usize] = data & 0x0F;
+        match self.register {
+            TAMA5_ROM_BANK_LOW | TAMA5_ROM_BANK_HIGH => self.switch_rom_bank(),
+            TAMA5_ADDRESS_LOW => self.run_command(),
+            _ => {},
+        };
+    }
+
+    fn ram_mut(&mut self) -> &mut Vec<u8> {
+        &mut self.ram
+    }
+
+    fn ram(&self) -> &Vec<u8> {
+        &self.ram
+    }
+
+    fn info(&self) -> &ROMInfo {
+        &self.info
+    }
+
+    fn save_data(&self) -> Vec<u8> {
+        let mut data = self.ram.clone();
+        data.extend(self.clock.to_footer());
+        data
+    }
+
+    fn load_save_data(&mut self, data: &[u8]) {
+        copy_save(&mut self.ram, data);
+        if let Some(clock) = data.get(self.ram.len()..).and_then(Tama5Clock::from_footer) {
+            self.clock = clock;
+            self.clock.update(unix_time());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rom_image(cartridge_type: u8, rom_size: u8, ram_size: u8, length: usize) -> Vec<u8> {
+        let mut data = vec![0; length];
+        data[CARTRIDGE_TYPE_ADDRESS as usize] = cartridge_type;
+        data[ROM_SIZE_ADDRESS as usize] = rom_size;
+        data[RAM_SIZE_ADDRESS as usize] = ram_size;
+        data[HEADER_CHECKSUM_ADDRESS as usize] = header_checksum(&data);
+        data
+    }
+
+    // Stores the bank number in the first byte of every bank
+    fn tag_banks(data: &mut [u8]) {
+        for bank in 0..data.len() / 0x4000 {
+            data[bank * 0x4000] = bank as u8;
+        }
+    }
+
+    // Writes the fields after the logo and fixes both checksums. The CGB flag is also the last title byte
+    fn header_image(title: &[u8], cgb_flag: u8, new_licensee: &[u8; 2], old_licensee: u8, version: u8) -> Vec<u8> {
+        let mut data = rom_image(0x00, 0x00, 0x00, 0x8000);
+        data[TITLE_ADDRESS as usize..TITLE_ADDRESS as usize + title.len()].copy_from_slice(title);
+        data[CGB_FLAG_ADDRESS as usize] = cgb_flag;
+        data[NEW_LICENSEE_CODE_ADDRESS as usize..NEW_LICENSEE_CODE_ADDRESS as usize + 2].copy_from_slice(new_licensee);
+        data[OLD_LICENSEE_CODE_ADDRESS as usize] = old_licensee;
+        data[VERSION_ADDRESS as usize] = version;
+        data[HEADER_CHECKSUM_ADDRESS as usize] = header_checksum(&data);
+        let checksum = global_checksum(&data).to_be_bytes();
+        data[GLOBAL_CHECKSUM_ADDRESS as usize..GLOBAL_CHECKSUM_ADDRESS as usize + 2].copy_from_slice(&checksum);
+        data
+    }
+
+    #[test]
+    fn test_header_fields() {
+        let info = ROMInfo::from_bytes(&header_image(b"SUPER GAME BOY 2", b'2', b"00", 0x01, 0)).unwrap();
+        assert_eq!(info.title(), "SUPER GAME BOY 2");
+        assert_eq!(info.publisher(), "Nintendo");
+        assert_eq!(info.licensee_code(), "01");
+        assert_eq!(info.manufacturer_code(), None);
+        assert!(info.global_checksum_valid());
+
+        let info = ROMInfo::from_bytes(&header_image(b"POKEMON_SLVAAXE", 0x80, b"01", 0x33, 1)).unwrap();
+        assert_eq!(info.title(), "POKEMON_SLV");
+        assert_eq!(info.manufacturer_code(), Some("AAXE"));
+        assert_eq!(info.publisher(), "Nintendo Research & Development 1");
+        assert_eq!(info.licensee_code(), "01");
+        assert_eq!(info.version(), 1);
+
+        // Old licensee codes can't have a manufacturer code
+        let info = ROMInfo::from_bytes(&header_image(b"TETRIS DX\0\0\0\0\0\0", 0x80, b"00", 0x01, 0)).unwrap();
+        assert_eq!(info.title(), "TETRIS DX");
+        assert_eq!(info.manufacturer_code(), None);
+
+        let mut data = header_image(b"GAME", 0x00, b"00", 0x01, 0);
+        data[0x4000] ^= 0xFF;
+        assert!(!ROMInfo::from_bytes(&data).unwrap().global_checksum_valid());
+    }
+
+    #[test]
+    fn test_load_valid_rom() {
+        let rom = rom_from_bytes(rom_image(0x03, 0x01, 0x02, 0x10000), None).unwrap();
+        assert_eq!(rom.info().rom_size(), 0x10000);
+        assert_eq!(rom.ram().len(), 0x2000);
+    }
+
+    #[test]
+    fn test_load_bad_header() {
+        assert!(matches!(
+            rom_from_bytes(rom_image(0x04, 0x00, 0x00, 0x8000), None),
+            Err(LoadError::BadHeader { field: "cartridge type", value: 0x04 }),
+        ));
+        assert!(matches!(
+            rom_from_bytes(rom_image(0x00, 0x09, 0x00, 0x8000), None),
+            Err(LoadError::BadHeader { field: "ROM size", value: 0x09 }),
+        ));
+        assert!(matches!(
+            rom_from_bytes(rom_image(0x00, 0x00, 0x06, 0x8000), None),
+            Err(LoadError::BadHeader { field: "RAM size", value: 0x06 }),
+        ));
+    }
+
+    #[test]
+    fn test_load_checksum_mismatch() {
+        let mut data = rom_image(0x00, 0x00, 0x00, 0x8000);
+        data[HEADER_CHECKSUM_ADDRESS as usize] ^= 0xFF;
+        assert!(matches!(rom_from_bytes(data, None), Err(LoadError::ChecksumMismatch { .. })));
+    }
+
+    #[test]
+    fn test_load_truncated() {
+        assert!(matches!(
+            rom_from_bytes(vec![0; 0x100], None),
+            Err(LoadError::Truncated { expected: 0x150, actual: 0x100 }),
+        ));
+    }
+
+    #[test]
+    fn test_load_short_rom() {
+        // 3 banks where the header declares 8, the last one cut in half
+        let mut data = rom_image(0x19, 0x02, 0x00, 0xA000);
+        tag_banks(&mut data);
+        data[0x8000] = 2;
+        let mut rom = rom_from_bytes(data, None).unwrap();
+        assert_eq!(rom.info().rom_banks, 4);
+        rom.write(0x2000, 2);
+        assert_eq!(rom.read(0x4000), 2);
+        assert_eq!(rom.read(0x6000), 0xFF);
+        // Banks past the end wrap around, 3 mirrors 2 and 6 mirrors 2 as well
+        rom.write(0x2000, 3);
+        assert_eq!(rom.read(0x4000), 2);
+        rom.write(0x2000, 6);
+        assert_eq!(rom.read(0x4000), 2);
+        rom.write(0x2000, 5);
+        assert_eq!(rom.read(0x4000), 1);
+    }
+
+    #[test]
+    fn test_mirror_rom() {
+        // 6 banks are a 4 bank chip followed by a 2 bank one
+        let mut data = vec![0; 0x4000 * 6];
+        tag_banks(&mut data);
+        let data = mirror_rom(data);
+        assert_eq!(data.len(), 0x4000 * 8);
+        let banks: Vec<u8> = (0..8).map(|bank| data[bank * 0x4000]).collect();
+        assert_eq!(banks, vec![0, 1, 2, 3, 4, 5, 4, 5]);
+
+        // 7 banks: 4, then 2, then 1 mirrored twice
+        let mut data = vec![0; 0x4000 * 7];
+        tag_banks(&mut data);
+        let data = mirror_rom(data);
+        let banks: Vec<u8> = (0..8).map(|bank| data[bank * 0x4000]).collect();
+        assert_eq!(banks, vec![0, 1, 2, 3, 4, 5, 6, 6]);
+    }
+
+    #[test]
+    fn test_load_overdump() {
+        // Twice the declared size, with the second half repeating the first
+        let mut data = rom_image(0x01, 0x00, 0x00, 0x10000);
+        tag_banks(&mut data);
+        let mut rom = rom_from_bytes(data, None).unwrap();
+        assert_eq!(rom.info().rom_banks, 4);
+        rom.write(0x2000, 7);
+        assert_eq!(rom.read(0x4000), 3);
+    }
+
+    // IPS patch writing a single byte
+    fn ips_patch(address: usize, value: u8) -> Vec<u8> {
+        let mut patch = b"PATCH".to_vec();
+        patch.extend_from_slice(&(address as u32).to_be_bytes()[1..]);
+        patch.extend_from_slice(&[0x00, 0x01, value]);
+        patch.extend_from_slice(b"EOF");
+        patch
+    }
+
+    #[test]
+    fn test_load_rom_with_patch() {
+        let dir = std::env::temp_dir().join(format!("rmg-001-patch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let rom_path = dir.join("game.gb");
+        let explicit_path = dir.join("other.ips");
+        std::fs::write(&rom_path, rom_image(0x00, 0x00, 0x00, 0x8000)).unwrap();
+        std::fs::write(dir.join("game.ips"), ips_patch(0x4000, 0x42)).unwrap();
+        std::fs::write(&explicit_path, ips_patch(0x4000, 0x24)).unwrap();
+        let filename = rom_path.to_str().unwrap();
+
+        let sidecar = load_rom(filename).map(|rom| rom.read(0x4000));
+        let explicit = load_rom_with_patch(filename, explicit_path.to_str()).map(|rom| rom.read(0x4000));
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(sidecar.unwrap(), 0x42);
+        assert_eq!(explicit.unwrap(), 0x24);
+    }
+
+    #[test]
+    fn test_rtc_counts_host_time() {
+        let mut rtc = RealTimeClock::new(1000);
+        rtc.write(0x08, 59, 1000);
+        rtc.write(0x09, 59, 1000);
+        rtc.write(0x0A, 23, 1000);
+        rtc.write(0x0B, 0xFF, 1000);
+        rtc.write(0x0C, RTC_DAY_HIGH_BIT, 1000);
+        rtc.latch(1001);
+        assert_eq!(rtc.read(0x08), 0);
+        assert_eq!(rtc.read(0x09), 0);
+        assert_eq!(rtc.read(0x0A), 0);
+        assert_eq!(rtc.read(0x0B), 0);
+        assert_eq!(rtc.read(0x0C), RTC_DAY_CARRY_BIT);
+
+        // Latched values don't change until the next latch
+        rtc.update(1000 + 3600 * 25 + 61);
+        assert_eq!(rtc.read(0x08), 0);
+        rtc.latch(1000 + 3600 * 25 + 61);
+        assert_eq!(rtc.read(0x08), 0);
+        assert_eq!(rtc.read(0x09), 1);
+        assert_eq!(rtc.read(0x0A), 1);
+        assert_eq!(rtc.read(0x0B), 1);
+    }
+
+    #[test]
+    fn test_rtc_halt() {
+        let mut rtc = RealTimeClock::new(0);
+        rtc.write(0x0C, RTC_HALT_BIT, 10);
+        rtc.latch(500);
+        assert_eq!(rtc.read(0x08), 10);
+        rtc.write(0x0C, 0, 600);
+        rtc.latch(605);
+        assert_eq!(rtc.read(0x08), 15);
+    }
+
+    #[test]
+    fn test_mbc3_rtc_save_footer() {
+        let mut rom = rom_from_bytes(rom_image(0x10, 0x00, 0x02, 0x8000), None).unwrap();
+        rom.write(0x0000, 0x0A);
+        rom.write(0x4000, 0x09);
+        rom.write(0xA000, 42);
+        rom.write(0x6000, 0x00);
+        rom.write(0x6000, 0x01);
+        assert_eq!(rom.read(0xA000), 42);
+
+        let save = rom.save_data();
+        assert_eq!(save.len(), 0x2000 + RTC_FOOTER_SIZE);
+        assert_eq!(save[0x2000 + 4], 42);
+        let mut loaded = rom_from_bytes(rom_image(0x10, 0x00, 0x02, 0x8000), Some(&save)).unwrap();
+        loaded.write(0x0000, 0x0A);
+        loaded.write(0x4000, 0x09);
+        assert_eq!(loaded.read(0xA000), 42);
+
+        let rtc = RealTimeClock::from_footer(&save[0x2000..0x2000 + 44]).unwrap();
+        assert_eq!(rtc.read(0x09), 42);
+    }
+
+    #[test]
+    fn test_mbc3_without_timer() {
+        let rom = rom_from_bytes(rom_image(0x13, 0x00, 0x02, 0x8000), None).unwrap();
+        assert_eq!(rom.save_data().len(), 0x2000);
+    }
+
+    #[test]
+    fn test_mbc1_multicart() {
+        let mut data = rom_image(0x01, 0x05, 0x00, 0x100000);
+        tag_banks(&mut data);
+        for block in 0..4 {
+            let logo = (block * 0x40000) + NINTENDO_LOGO_ADDRESS as usize;
+            data[logo..logo + NINTENDO_LOGO.len()].copy_from_slice(&NINTENDO_LOGO);
+        }
+        let mut rom = rom_from_bytes(data, None).unwrap();
+        assert_eq!(rom.read(0x4000), 0x01);
+
+        // Select the second game
+        rom.write(0x4000, 0x01);
+        rom.write(0x6000, 0x01);
+        assert_eq!(rom.read(0x0000), 0x10);
+        assert_eq!(rom.read(0x4000), 0x11);
+        rom.write(0x2000, 0x02);
+        assert_eq!(rom.read(0x4000), 0x12);
+        // Only 4 bits are wired, but the bank 0 check still sees the fifth one
+        rom.write(0x2000, 0x10);
+        assert_eq!(rom.read(0x4000), 0x10);
+        let mut state = StateWriter::new();
+        rom.save_state(&mut state);
+        let data = state.into_bytes();
+        rom.write(0x2000, 0x00);
+        assert_eq!(rom.read(0x4000), 0x11);
+        rom.load_state(&mut StateReader::new(&data)).unwrap();
+        assert_eq!(rom.read(0x4000), 0x10);
+    }
+
+    #[test]
+    fn test_mbc1_not_multicart() {
+        let mut data = rom_image(0x01, 0x05, 0x00, 0x100000);
+        tag_banks(&mut data);
+        let mut rom = rom_from_bytes(data, None).unwrap();
+        rom.write(0x2000, 0x10);
+        assert_eq!(rom.read(0x4000), 0x10);
+    }
+
+    #[test]
+    fn test_mbc1_1mb_banking() {
+        let mut data = rom_image(0x01, 0x05, 0x00, 0x100000);
+        tag_banks(&mut data);
+        let mut rom = rom_from_bytes(data, None).unwrap();
+        rom.write(0x2000, 0x05);
+        rom.write(0x4000, 0x01);
+        assert_eq!(rom.read(0x4000), 0x25);
+        assert_eq!(rom.read(0x0000), 0x00);
+        rom.write(0x6000, 0x01);
+        assert_eq!(rom.read(0x0000), 0x20);
+        assert_eq!(rom.read(0x4000), 0x25);
+        // 64 banks only use the lower bit of the secondary register
+        rom.write(0x4000, 0x02);
+        assert_eq!(rom.read(0x0000), 0x00);
+        assert_eq!(rom.read(0x4000), 0x05);
+        rom.write(0x4000, 0x01);
+        rom.write(0x2000, 0x20);
+        assert_eq!(rom.read(0x4000), 0x21);
+    }
+
+    #[test]
+    fn test_mbc1_2mb_banking() {
+        let mut data = rom_image(0x01, 0x06, 0x00, 0x200000);
+        tag_banks(&mut data);
+        let mut rom = rom_from_bytes(data, None).unwrap();
+        rom.write(0x4000, 0x03);
+        rom.write(0x2000, 0x1F);
+        assert_eq!(rom.read(0x4000), 0x7F);
+        assert_eq!(rom.read(0x0000), 0x00);
+        rom.write(0x6000, 0x01);
+        assert_eq!(rom.read(0x0000), 0x60);
+        rom.write(0x2000, 0x00);
+        assert_eq!(rom.read(0x4000), 0x61);
+        rom.write(0x4000, 0x02);
+        assert_eq!(rom.read(0x0000), 0x40);
+        assert_eq!(rom.read(0x4000), 0x41);
+        rom.write(0x6000, 0x00);
+        assert_eq!(rom.read(0x0000), 0x00);
+        assert_eq!(rom.read(0x4000), 0x41);
+    }
+
+    #[test]
+    fn test_mbc1_ram_banking() {
+        let mut rom = rom_from_bytes(rom_image(0x03, 0x00, 0x03, 0x8000), None).unwrap();
+        rom.write(0x0000, 0x0A);
+        rom.write(0x4000, 0x02);
+        // Simple mode always maps the first RAM bank
+        rom.write(0xA000, 0xAA);
+        rom.write(0x6000, 0x01);
+        rom.write(0xA000, 0xBB);
+        assert_eq!(rom.ram()[0x0000], 0xAA);
+        assert_eq!(rom.ram()[0x4000], 0xBB);
+        assert_eq!(rom.read(0xA000), 0xBB);
+        rom.write(0x4000, 0x00);
+        assert_eq!(rom.read(0xA000), 0xAA);
+    }
+
+    #[test]
+    fn test_mbc2_ram() {
+        let mut rom = rom_from_bytes(rom_image(0x06, 0x01, 0x00, 0x10000), None).unwrap();
+        rom.write(0xA000, 0x05);
+        assert_eq!(rom.read(0xA000), 0xFF);
+        // Bit 8 set selects the ROM bank register instead
+        rom.write(0x0100, 0x0A);
+        assert_eq!(rom.read(0xA000), 0xFF);
+        rom.write(0x0000, 0x0A);
+        rom.write(0xA000, 0xAB);
+        rom.write(0xA1FF, 0x12);
+        assert_eq!(rom.read(0xA000), 0xFB);
+        assert_eq!(rom.read(0xA200), 0xFB);
+        assert_eq!(rom.read(0xBE00), 0xFB);
+        assert_eq!(rom.read(0xBFFF), 0xF2);
+
+        let save = rom.save_data();
+        assert_eq!(save.len(), 0x200);
+        assert_eq!(save[0], 0x0B);
+        let mut save = save;
+        save[1] = 0xF7;
+        let mut rom = rom_from_bytes(rom_image(0x06, 0x01, 0x00, 0x10000), Some(&save)).unwrap();
+        rom.write(0x0000, 0x0A);
+        assert_eq!(rom.read(0xA000), 0xFB);
+        assert_eq!(rom.save_data()[1], 0x07);
+    }
+
+    #[test]
+    fn test_mbc2_rom_bank_select() {
+        let mut data = rom_image(0x05, 0x02, 0x00, 0x20000);
+        tag_banks(&mut data);
+        let mut rom = rom_from_bytes(data, None).unwrap();
+        rom.write(0x2100, 0x05);
+        assert_eq!(rom.read(0x4000), 0x05);
+        // Bit 8 clear writes the RAM enable register
+        rom.write(0x2000, 0x03);
+        assert_eq!(rom.read(0x4000), 0x05);
+        rom.write(0x0100, 0x00);
+        assert_eq!(rom.read(0x4000), 0x01);
+    }
+
+    #[test]
+    fn test_mbc5_rumble() {
+        let mut rom = rom_from_bytes(rom_image(0x1E, 0x00, 0x03, 0x8000), None).unwrap();
+        rom.write(0x0000, 0x0A);
+        rom.write(0x4000, 0x0B);
+        assert!(rom.rumble());
+        rom.write(0xA000, 0x42);
+        assert_eq!(rom.ram()[0x6000], 0x42);
+        let mut state = StateWriter::new();
+        rom.save_state(&mut state);
+        let data = state.into_bytes();
+        rom.write(0x4000, 0x03);
+        assert!(!rom.rumble());
+        rom.load_state(&mut StateReader::new(&data)).unwrap();
+        assert!(rom.rumble());
+
+        let mut rom = rom_from_bytes(rom_image(0x1B, 0x00, 0x04, 0x8000), None).unwrap();
+        rom.write(0x0000, 0x0A);
+        rom.write(0x4000, 0x0B);
+        assert!(!rom.rumble());
+        rom.write(0xA000, 0x42);
+        assert_eq!(rom.ram()[0x16000], 0x42);
+    }
+
+    fn eeprom_send(rom: &mut Box<dyn ROM>, value: u32, bits: u8) {
+        for index in (0..bits).rev() {
+            let bit = (((value >> index) & 1) as u8) << 1;
+            rom.write(0xA080, EEPROM_CS_BIT | bit);
+            rom.write(0xA080, EEPROM_CS_BIT | EEPROM_CLK_BIT | bit);
+        }
+    }
+
+    fn eeprom_receive(rom: &mut Box<dyn ROM>) -> u16 {
+        let mut value = 0;
+        for _ in 0..16 {
+            rom.write(0xA080, EEPROM_CS_BIT);
+            rom.write(0xA080, EEPROM_CS_BIT | EEPROM_CLK_BIT);
+            value = (value << 1) | (rom.read(0xA080) & 1) as u16;
+        }
+        value
+    }
+
+    fn eeprom_deselect(rom: &mut Box<dyn ROM>) {
+        rom.write(0xA080, 0x00);
+    }
+
+    #[test]
+    fn test_mbc7_eeprom() {
+        let mut rom = rom_from_bytes(rom_image(0x22, 0x00, 0x00, 0x8000), None).unwrap();
+        rom.write(0x0000, 0x0A);
+        rom.write(0x4000, 0x40);
+
+        // Writes are ignored until EWEN
+        eeprom_send(&mut rom, (0b101 << 24) | (0x05 << 16) | 0xBEEF, 27);
+        eeprom_deselect(&mut rom);
+        assert_eq!(rom.ram()[10], 0xFF);
+        eeprom_send(&mut rom, (0b100 << 8) | 0xC0, 11);
+        eeprom_deselect(&mut rom);
+        eeprom_send(&mut rom, (0b101 << 24) | (0x05 << 16) | 0xBEEF, 27);
+        eeprom_deselect(&mut rom);
+        assert_eq!(&rom.ram()[10..12], &[0xEF, 0xBE]);
+
+        eeprom_send(&mut rom, (0b110 << 8) | 0x05, 11);
+        assert_eq!(rom.read(0xA080) & 1, 0);
+        assert_eq!(eeprom_receive(&mut rom), 0xBEEF);
+        assert_eq!(eeprom_receive(&mut rom), 0xFFFF);
+        eeprom_deselect(&mut rom);
+
+        let save = rom.save_data();
+        assert_eq!(save.len(), EEPROM_SIZE);
+        let mut rom = rom_from_bytes(rom_image(0x22, 0x00, 0x00, 0x8000), Some(&save)).unwrap();
+        rom.write(0x0000, 0x0A);
+        rom.write(0x4000, 0x40);
+        eeprom_send(&mut rom, (0b110 << 8) | 0x05, 11);
+        assert_eq!(eeprom_receive(&mut rom), 0xBEEF);
+    }
+
+    #[test]
+    fn test_mbc7_accelerometer() {
+        let mut rom = rom_from_bytes(rom_image(0x22, 0x00, 0x00, 0x8000), None).unwrap();
+        rom.set_tilt(0.5, -1.0);
+        rom.write(0xA000, 0x55);
+        rom.write(0xA010, 0xAA);
+        assert_eq!(rom.read(0xA020), 0xFF);
+        rom.write(0x0000, 0x0A);
+        rom.write(0x4000, 0x40);
+        rom.write(0xA000, 0x55);
+        assert_eq!(rom.read(0xA030), 0x80);
+        rom.write(0xA010, 0xAA);
+        rom.set_tilt(0.0, 0.0);
+        assert_eq!(u16::from_le_bytes([rom.read(0xA020), rom.read(0xA030)]), 0x81D0 + 0x38);
+        assert_eq!(u16::from_le_bytes([rom.read(0xA040), rom.read(0xA050)]), 0x81D0 - 0x70);
+        // Latching again requires erasing first
+        rom.write(0xA010, 0xAA);
+        assert_eq!(u16::from_le_bytes([rom.read(0xA020), rom.read(0xA030)]), 0x81D0 + 0x38);
+    }
+
+    struct TestPeer {
+        led: std::rc::Rc<std::cell::Cell<bool>>,
+    }
+
+    impl InfraredPeer for TestPeer {
+        fn set_led(&mut self, on: bool) {
+            self.led.set(on);
+        }
+
+        fn light(&self) -> bool {
+            // Reflects our own light back, like a mirror
+            self.led.get()
+        }
+    }
+
+    #[test]
+    fn test_huc1() {
+        let mut data = rom_image(0xFF, 0x02, 0x03, 0x20000);
+        tag_banks(&mut data);
+        let mut rom = rom_from_bytes(data, None).unwrap();
+        rom.write(0x2000, 0x05);
+        assert_eq!(rom.read(0x4000), 0x05);
+        rom.write(0x4000, 0x02);
+        rom.write(0x0000, 0x0A);
+        rom.write(0xA000, 0x42);
+        assert_eq!(rom.ram()[0x4000], 0x42);
+
+        // Infrared mode reads no light without a peer
+        rom.write(0x0000, 0x0E);
+        assert_eq!(rom.read(0xA000), 0xC0);
+        rom.write(0xA000, 0x01);
+        assert_eq!(rom.ram()[0x4000], 0x42);
+        assert_eq!(rom.read(0xA000), 0xC0);
+
+        let led = std::rc::Rc::new(std::cell::Cell::new(false));
+        rom.set_infrared_peer(Some(Box::new(TestPeer { led: led.clone() })));
+        rom.write(0xA000, 0x01);
+        assert!(led.get());
+        assert_eq!(rom.read(0xA000), 0xC1);
+        rom.write(0xA000, 0x00);
+        assert_eq!(rom.read(0xA000), 0xC0);
+
+        rom.write(0x0000, 0x00);
+        assert_eq!(rom.read(0xA000), 0x42);
+    }
+
+    #[test]
+    fn test_huc3_clock_counts_minutes() {
+        let mut clock = HuC3Clock::new(0);
+        clock.write(0x00, 0xF);
+        clock.write(0x01, 0x9);
+        clock.write(0x02, 0x5);
+        assert_eq!(clock.minutes, 0x59F);
+        clock.update(59);
+        assert_eq!(clock.minutes, 0x59F);
+        clock.update(60);
+        assert_eq!(clock.minutes, 0);
+        assert_eq!(clock.days, 1);
+        // The seconds left over are kept for the next update
+        clock.update(150);
+        clock.update(180);
+        assert_eq!(clock.minutes, 2);
+    }
+
+    #[test]
+    fn test_huc3_commands() {
+        let mut rom = rom_from_bytes(rom_image(0xFE, 0x00, 0x03, 0x8000), None).unwrap();
+        // Write 0x123 minutes and 2 days starting at address 0
+        rom.write(0x0000, 0x0B);
+        rom.write(0xA000, 0x40);
+        rom.write(0xA000, 0x50);
+        for nibble in [0x3, 0x2, 0x1, 0x2, 0x0, 0x0, 0x0] {
+            rom.write(0xA000, 0x30 | nibble);
+        }
+        rom.write(0x0000, 0x0D);
+        assert_eq!(rom.read(0xA000) & 1, 1);
+
+        rom.write(0x0000, 0x0B);
+        rom.write(0xA000, 0x40);
+        let mut values = Vec::new();
+        for _ in 0..4 {
+            rom.write(0x0000, 0x0B);
+            rom.write(0xA000, 0x10);
+            rom.write(0x0000, 0x0C);
+            values.push(rom.read(0xA000));
+        }
+        assert_eq!(values, vec![0x13, 0x12, 0x11, 0x12]);
+
+        rom.write(0x0000, 0x0A);
+        rom.write(0xA000, 0x42);
+        rom.write(0x0000, 0x00);
+        assert_eq!(rom.read(0xA000), 0x42);
+        rom.write(0xA000, 0x43);
+        assert_eq!(rom.read(0xA000), 0x42);
+        rom.write(0x0000, 0x0E);
+        assert_eq!(rom.read(0xA000), 0xC0);
+
+        rom.write(0x0000, 0x0B);
+        rom.write(0xA000, 0x65);
+        assert_eq!(rom.tone(), Some(0x5));
+        // The ready probe leaves the speaker alone
+        rom.write(0xA000, 0x62);
+        assert_eq!(rom.tone(), Some(0x5));
+        let mut state = StateWriter::new();
+        rom.save_state(&mut state);
+        let data = state.into_bytes();
+        rom.write(0xA000, 0x60);
+        assert_eq!(rom.tone(), None);
+        rom.load_state(&mut StateReader::new(&data)).unwrap();
+        assert_eq!(rom.tone(), Some(0x5));
+
+        let save = rom.save_data();
+        assert_eq!(save.len(), 0x8000 + HUC3_RTC_FOOTER_SIZE);
+        let clock = HuC3Clock::from_footer(&save[0x8000..]).unwrap();
+        assert_eq!(clock.days, 2);
+        let mut rom = rom_from_bytes(rom_image(0xFE, 0x00, 0x03, 0x8000), Some(&save)).unwrap();
+        rom.write(0x0000, 0x0B);
+        rom.write(0xA000, 0x43);
+        rom.write(0xA000, 0x10);
+        rom.write(0x0000, 0x0C);
+        assert_eq!(rom.read(0xA000), 0x12);
+    }
+
+    #[test]
+    fn test_mmm01() {
+        // 256 KiB image whose menu, and the header describing the cart, sits in the last 32 KiB
+        let mut data = vec![0; 0x40000];
+        let header = rom_image(0x0D, 0x03, 0x03, 0x8000);
+        data[0x38000..].copy_from_slice(&header);
+        tag_banks(&mut data);
+        let mut rom = rom_from_bytes(data, None).unwrap();
+        assert!(matches!(rom.info().mbc, MBC::MMM01));
+        assert_eq!(rom.info().rom_banks, 16);
+
+        // Unmapped, the menu is visible at both ROM areas
+        assert_eq!(rom.read(0x0000), 14);
+        assert_eq!(rom.read(0x4000), 15);
+
+        // The menu maps a 4 bank game starting at bank 4 and locks the upper bank bits
+        rom.write(0x2000, 0x04);
+        rom.write(0x6000, 0b1110 << 2);
+        rom.write(0x0000, 0x40);
+        assert_eq!(rom.read(0x0000), 4);
+        assert_eq!(rom.read(0x4000), 5);
+        rom.write(0x2000, 0x02);
+        assert_eq!(rom.read(0x4000), 6);
+        rom.write(0x2000, 0x1F);
+        assert_eq!(rom.read(0x4000), 7);
+        // The mask can't be changed anymore
+        rom.write(0x6000, 0);
+        rom.write(0x2000, 0x08);
+        assert_eq!(rom.read(0x4000), 5);
+
+        rom.write(0x0000, 0x0A);
+        rom.write(0x4000, 0x02);
+        rom.write(0xA000, 0x42);
+        assert_eq!(rom.ram()[0x4000], 0x42);
+    }
+
+    #[test]
+    fn test_mbc6_windows() {
+        let mut data = rom_image(0x20, 0x05, 0x03, 0x100000);
+        for bank in 0..data.len() / 0x2000 {
+            data[bank * 0x2000] = bank as u8;
+        }
+        let mut rom = rom_from_bytes(data, None).unwrap();
+        rom.write(0x2000, 5);
+        rom.write(0x3000, 9);
+        assert_eq!(rom.read(0x4000), 5);
+        assert_eq!(rom.read(0x6000), 9);
+
+        rom.write(0x0000, 0x0A);
+        rom.write(0x0400, 1);
+        rom.write(0x0800, 6);
+        rom.write(0xA000, 0x12);
+        rom.write(0xB000, 0x34);
+        assert_eq!(rom.ram()[0x1000], 0x12);
+        assert_eq!(rom.ram()[0x6000], 0x34);
+    }
+
+    #[test]
+    fn test_mbc6_flash() {
+        let mut rom = rom_from_bytes(rom_image(0x20, 0x05, 0x03, 0x100000), None).unwrap();
+        // Bank 2 in the first window and bank 1 in the second one reach the unlock addresses
+        rom.write(0x1000, 1);
+        rom.write(0x0C00, 1);
+        rom.write(0x2000, 2);
+        rom.write(0x2800, 0x08);
+        rom.write(0x3000, 1);
+        rom.write(0x3800, 0x08);
+        let command = |rom: &mut Box<dyn ROM>, data: u8| {
+            rom.write(0x5555, 0xAA);
+            rom.write(0x6AAA, 0x55);
+            rom.write(0x5555, data);
+        };
+
+        command(&mut rom, 0x90);
+        assert_eq!(rom.read(0x4000), FLASH_MANUFACTURER_ID);
+        assert_eq!(rom.read(0x4001), FLASH_DEVICE_ID);
+        rom.write(0x4000, 0xF0);
+        assert_eq!(rom.read(0x4000), 0xFF);
+
+        command(&mut rom, 0xA0);
+        rom.write(0x6010, 0x5A);
+        assert_eq!(rom.read(0x6010), 0x5A);
+        // Without an erase bits can't be set again
+        command(&mut rom, 0xA0);
+        rom.write(0x6010, 0xF0);
+        assert_eq!(rom.read(0x6010), 0x50);
+
+        let save = rom.save_data();
+        assert_eq!(save.len(), 0x8000 + FLASH_SIZE);
+        assert_eq!(save[0x8000 + 0x2010], 0x50);
+
+        command(&mut rom, 0x80);
+        rom.write(0x5555, 0xAA);
+        rom.write(0x6AAA, 0x55);
+        rom.write(0x6000, 0x30);
+        assert_eq!(rom.read(0x6010), 0xFF);
+
+        let mut rom = rom_from_bytes(rom_image(0x20, 0x05, 0x03, 0x100000), Some(&save)).unwrap();
+        rom.write(0x1000, 1);
+        rom.write(0x0C00, 1);
+        rom.write(0x3000, 1);
+        rom.write(0x3800, 0x08);
+        assert_eq!(rom.read(0x6010), 0x50);
+    }
+
+    #[test]
+    fn test_pocket_camera() {
+        let mut rom = rom_from_bytes(rom_image(0xFC, 0x05, 0x04, 0x100000), None).unwrap();
+        assert_eq!(rom.ram().len(), 0x20000);
+        rom.write(0x0000, 0x0A);
+        rom.write(0x4000, 0x00);
+        rom.write(0xA100, 0x55);
+
+        // Exposure and a dithering matrix that turns the whole picture black
+        rom.write(0x4000, 0x10);
+        rom.write(0xA001, 0x04);
+        rom.write(0xA002, 0x10);
+        for register in 0x06..0x36 {
+            rom.write(0xA000 + register, 0xFF);
+        }
+        rom.catch_up(1000);
+        rom.write(0xA000, 0x01);
+        assert_eq!(rom.read(0xA000) & 1, 1);
+        rom.write(0x4000, 0x00);
+        assert_eq!(rom.read(0xA100), 0x00);
+
+        rom.catch_up(1_000_000);
+        assert_eq!(rom.read(0xA100), 0xFF);
+        assert_eq!(rom.read(0xAEFF), 0xFF);
+        rom.write(0x4000, 0x10);
+        assert_eq!(rom.read(0xA000) & 1, 0);
+    }
+
+    #[test]
+    fn test_pocket_camera_without_ram() {
+        let mut rom = rom_from_bytes(rom_image(0xFC, 0x05, 0x00, 0x100000), None).unwrap();
+        assert!(rom.ram().is_empty());
+        rom.write(0x4000, 0x10);
+        rom.catch_up(1000);
+        rom.write(0xA000, 0x01);
+        rom.catch_up(1_000_000);
+        assert_eq!(rom.read(0xA000) & 1, 0);
+        rom.write(0x4000, 0x00);
+        assert_eq!(rom.read(0xA100), 0xFF);
+    }
+
+    fn tama5_write(rom: &mut Box<dyn ROM>, register: u8, data: u8) {
+        rom.write(0xA001, register);
+        rom.write(0xA000, data);
+    }
+
+    fn tama5_command(rom: &mut Box<dyn ROM>, command: u8, address: u8, data: u8) -> u8 {
+        tama5_write(rom, TAMA5_DATA_LOW, data & 0x0F);
+        tama5_write(rom, TAMA5_DATA_HIGH, data >> 4);
+        tama5_write(rom, TAMA5_ADDRESS_HIGH, (command << 1) | (address >> 4));
+        tama5_write(rom, TAMA5_ADDRESS_LOW, address & 0x0F);
+        rom.write(0xA001, TAMA5_RESULT_LOW);
+        let low = rom.read(0xA000) & 0x0F;
+        rom.write(0xA001, TAMA5_RESULT_HIGH);
+        let high = rom.read(0xA000) & 0x0F;
+        (high << 4) | low
+    }
+
+    #[test]
+    fn test_tama5() {
+        let mut data = rom_image(0xFD, 0x04, 0x00, 0x80000);
+        tag_banks(&mut data);
+        let mut rom = rom_from_bytes(data, None).unwrap();
+        tama5_write(&mut rom, TAMA5_ROM_BANK_LOW, 0x03);
+        tama5_write(&mut rom, TAMA5_ROM_BANK_HIGH, 0x01);
+        assert_eq!(rom.read(0x4000), 0x13);
+
+        rom.write(0xA001, TAMA5_READY);
+        assert_eq!(rom.read(0xA000) & 1, 1);
+        tama5_command(&mut rom, 0, 0x1A, 0x5C);
+        assert_eq!(tama5_command(&mut rom, 1, 0x1A, 0), 0x5C);
+
+        // 23:59 on the 28th of February of a leap year
+        for (register, digit) in [(0x2, 9), (0x3, 5), (0x4, 3), (0x5, 2), (0x7, 8), (0x8, 2), (0x9, 2), (0xB, 4)] {
+            tama5_command(&mut rom, 2, register, digit);
+        }
+        assert_eq!(tama5_command(&mut rom, 3, 0x3, 0), 5);
+        assert_eq!(tama5_command(&mut rom, 3, 0x8, 0), 2);
+
+        let save = rom.save_data();
+        assert_eq!(save.len(), TAMA5_RAM_SIZE + TAMA5_CLOCK_FOOTER_SIZE);
+        let mut clock = Tama5Clock::from_footer(&save[TAMA5_RAM_SIZE..]).unwrap();
+        assert_eq!((clock.hours, clock.minutes, clock.day, clock.month), (23, 59, 28, 2));
+        clock.update(clock.timestamp + 60);
+        assert_eq!((clock.hours, clock.minutes, clock.day, clock.month), (0, 0, 29, 2));
+        clock.update(clock.timestamp + (24 * 60 * 60));
+        assert_eq!((clock.day, clock.month), (1, 3));
+
+        let mut rom = rom_from_bytes(rom_image(0xFD, 0x04, 0x00, 0x80000), Some(&save)).unwrap();
+        assert_eq!(tama5_command(&mut rom, 1, 0x1A, 0), 0x5C);
+    }
 }
@@ -0,0 +1,158 @@
+use crate::cpu::TCycles;
+use crate::savestate::{SaveState, SaveStateError, StateReader, StateWriter};
+
+const EVENT_COUNT: usize = 4;
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Event {
+    TimerOverflow,
+    PPUModeTransition,
+    FrameSequencer,
+    SerialBit,
+}
+
+impl Event {
+    const ALL: [Event; EVENT_COUNT] = [
+        Event::TimerOverflow,
+        Event::PPUModeTransition,
+        Event::FrameSequencer,
+        Event::SerialBit,
+    ];
+}
+
+// Keeps the master clock and the time at which every pending event fires.
+// Each event can only be queued once, scheduling it again replaces the previous time
+pub struct Scheduler {
+    now: u64,
+    events: [Option<u64>; EVENT_COUNT],
+    next_event: u64,
+}
+
+impl Scheduler {
+    pub fn new() -> Self {
+        Self {
+            now: 0,
+            events: [None; EVENT_COUNT],
+            next_event: u64::MAX,
+        }
+    }
+
+    // T-cycles elapsed since power on
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    pub fn advance(&mut self, cycles: TCycles) {
+        self.now += cycles.0 as u64;
+    }
+
+    pub fn schedule(&mut self, event: Event, time: u64) {
+        self.events[event as usize] = Some(time);
+        self.update_next_event();
+    }
+
+    pub fn schedule_in(&mut self, event: Event, cycles: u64) {
+        self.schedule(event, self.now + cycles);
+    }
+
+    pub fn cancel(&mut self, event: Event) {
+        self.events[event as usize] = None;
+        self.update_next_event();
+    }
+
+    pub fn next_event(&self) -> u64 {
+        self.next_event
+    }
+
+    // Removes and returns the earliest event that should have fired by now, along with its time
+    pub fn pop_due(&mut self) -> Option<(Event, u64)> {
+        if self.next_event > self.now {
+            return None;
+        }
+        let mut due: Option<(Event, u64)> = None;
+        for event in Event::ALL {
+            if let Some(time) = self.events[event as usize] {
+                let earlier = match due {
+                    Some((_, due_time)) => time < due_time,
+                    None => true,
+                };
+                if time <= self.now && earlier {
+                    due = Some((event, time));
+                }
+            }
+        }
+        let (event, time) = due?;
+        self.cancel(event);
+        Some((event, time))
+    }
+
+    fn update_next_event(&mut self) {
+        self.next_event = self.events.iter().flatten().copied().min().unwrap_or(u64::MAX);
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SaveState for Scheduler {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u64(self.now);
+        for time in self.events {
+            match time {
+                Some(time) => {
+                    state.write_bool(true);
+                    state.write_u64(time);
+                },
+                None => state.write_bool(false),
+            };
+        }
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.now = state.read_u64()?;
+        for time in self.events.iter_mut() {
+            *time = match state.read_bool()? {
+                true => Some(state.read_u64()?),
+                false => None,
+            };
+        }
+        self.update_next_event();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events_in_order() {
+        let mut scheduler = Scheduler::new();
+        scheduler.schedule(Event::SerialBit, 30);
+        scheduler.schedule(Event::TimerOverflow, 10);
+        scheduler.schedule(Event::PPUModeTransition, 20);
+        assert_eq!(scheduler.next_event(), 10);
+        assert_eq!(scheduler.pop_due(), None);
+
+        scheduler.advance(TCycles(25));
+        assert_eq!(scheduler.pop_due(), Some((Event::TimerOverflow, 10)));
+        assert_eq!(scheduler.pop_due(), Some((Event::PPUModeTransition, 20)));
+        assert_eq!(scheduler.pop_due(), None);
+        assert_eq!(scheduler.next_event(), 30);
+    }
+
+    #[test]
+    fn test_reschedule_and_cancel() {
+        let mut scheduler = Scheduler::new();
+        scheduler.schedule(Event::TimerOverflow, 10);
+        scheduler.schedule_in(Event::TimerOverflow, 50);
+        assert_eq!(scheduler.next_event(), 50);
+        scheduler.cancel(Event::TimerOverflow);
+        assert_eq!(scheduler.next_event(), u64::MAX);
+        scheduler.advance(TCycles(100));
+        assert_eq!(scheduler.pop_due(), None);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::utils::{BitIndex, get_bit};
+use crate::interrupts::{Interrupt, Interrupts};
+use crate::savestate::{SaveState, SaveStateError, StateReader, StateWriter};
+
+pub const SERIAL_DATA_ADDRESS: u16    = 0xFF01;
+pub const SERIAL_CONTROL_ADDRESS: u16 = 0xFF02;
+
+// Serial port without a link cable connected, every bit shifted in reads as 1
+pub struct Serial {
+    data: u8,
+    control: u8,
+    bits_left: u8,
+}
+
+impl Serial {
+    pub fn new() -> Self {
+        Self {
+            data: 0,
+            control: 0,
+            bits_left: 0,
+        }
+    }
+
+    pub fn is_io_register(address: u16) -> bool {
+        address == SERIAL_DATA_ADDRESS || address == SERIAL_CONTROL_ADDRESS
+    }
+
+    pub fn get_register(&self, address: u16) -> u8 {
+        match address {
+            SERIAL_DATA_ADDRESS => self.data,
+            _ => self.control,
+        }
+    }
+
+    pub fn set_register(&mut self, address: u16, data: u8) {
+        match address {
+            SERIAL_DATA_ADDRESS => self.data = data,
+            _ => {
+                self.control = data;
+                // Only transfers using the internal clock make progress without a peer
+                self.bits_left = match get_bit(data, BitIndex::I7) && get_bit(data, BitIndex::I0) {
+                    true => 8,
+                    false => 0,
+                };
+            },
+        };
+    }
+
+    pub fn is_transferring(&self) -> bool {
+        self.bits_left > 0
+    }
+
+    // T-cycles between two bit shifts
+    pub fn bit_period(&self, cgb_mode: bool, double_speed: bool) -> u64 {
+        let period = match cgb_mode && get_bit(self.control, BitIndex::I1) {
+            true => 16,
+            false => 512,
+        };
+        match double_speed {
+            true => period / 2,
+            false => period,
+        }
+    }
+
+    pub fn shift_bit(&mut self, interrupts: &mut Interrupts) {
+        if self.bits_left == 0 {
+            return;
+        }
+        self.data = (self.data << 1) | 1;
+        self.bits_left -= 1;
+        if self.bits_left == 0 {
+            self.control &= 0b0111_1111;
+            interrupts.request(Interrupt::Serial);
+        }
+    }
+}
+
+impl Default for Serial {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SaveState for Serial {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u8(self.data);
+        state.write_u8(self.control);
+        state.write_u8(self.bits_left);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.data = state.read_u8()?;
+        self.control = state.read_u8()?;
+        self.bits_left = state.read_u8()?;
+        if self.bits_left > 8 {
+            return Err(SaveStateError::InvalidData("serial transfer"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_internal_clock_transfer() {
+        let mut serial = Serial::new();
+        let mut interrupts = Interrupts::new();
+        serial.set_register(SERIAL_DATA_ADDRESS, 0b1010_0000);
+        serial.set_register(SERIAL_CONTROL_ADDRESS, 0x81);
+        assert!(serial.is_transferring());
+        assert_eq!(serial.bit_period(false, false), 512);
+        for _ in 0..8 {
+            serial.shift_bit(&mut interrupts);
+        }
+        assert!(!serial.is_transferring());
+        assert_eq!(serial.get_register(SERIAL_DATA_ADDRESS), 0xFF);
+        assert_eq!(serial.get_register(SERIAL_CONTROL_ADDRESS), 0x01);
+        assert!(interrupts.get(Interrupt::Serial));
+    }
+
+    #[test]
+    fn test_external_clock_waits() {
+        let mut serial = Serial::new();
+        serial.set_register(SERIAL_CONTROL_ADDRESS, 0x80);
+        assert!(!serial.is_transferring());
+    }
+}
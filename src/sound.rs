@@ -1,10 +1,13 @@
-use std::env;
 use std::ops::RangeInclusive;
+#[cfg(feature = "audio-cpal")]
 use std::sync::{Arc, Mutex};
+#[cfg(feature = "audio-cpal")]
 use cpal::{Stream, StreamConfig, Device, Sample, SampleRate};
+#[cfg(feature = "audio-cpal")]
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use crate::cpu::Cycles;
+use crate::cpu::TCycles;
 use crate::utils::join_bytes;
+use crate::savestate::{SaveState, SaveStateError, StateReader, StateWriter};
 
 pub const NR10_ADDRESS: u16 = 0xFF10;
 pub const NR11_ADDRESS: u16 = 0xFF11;
@@ -36,6 +39,10 @@ pub const WAVE_PATTERN_RAM: RangeInclusive<u16> = 0xFF30..=0xFF3F;
 
 pub const SAMPLE_RATE: u32 = 48000;
 
+// The frame sequencer runs at 512 Hz
+pub const FRAME_SEQUENCER_PERIOD: u64 = 8192;
+
+#[cfg(feature = "audio-cpal")]
 const WAVE_DUTY_PATTERNS: [[u8; 8]; 4] = [
     [0, 0, 0, 0, 0, 0, 0, 1],
     [0, 0, 0, 0, 0, 0, 1, 1],
@@ -43,6 +50,7 @@ const WAVE_DUTY_PATTERNS: [[u8; 8]; 4] = [
     [1, 1, 1, 1, 1, 1, 0, 0],
 ];
 
+#[cfg(feature = "audio-cpal")]
 struct ChannelTwo {
     #[allow(dead_code)]
     stream: Stream,
@@ -53,6 +61,7 @@ struct ChannelTwo {
     buffer_pos: usize,
 }
 
+#[cfg(feature = "audio-cpal")]
 impl ChannelTwo {
     pub fn new(device: &Device, config: &StreamConfig) -> Self {
         let mut count: usize = 0;
@@ -115,6 +124,9 @@ impl ChannelTwo {
 
 pub struct Sound {
     io_registers: [u8; 48],
+    frame_sequencer_step: u8,
+    last_sync: u64,
+    #[cfg(feature = "audio-cpal")]
     channel_two: Option<ChannelTwo>,
 }
 
@@ -131,8 +143,21 @@ channel_two.play().unwrap();
 sound.channel_two = Some(channel_two); */
 
 impl Sound {
-    pub fn new() -> Self {
-        if !env::var("SOUND_ENABLE").is_err() {
+    #[cfg(not(feature = "audio-cpal"))]
+    pub fn new(enabled: bool) -> Self {
+        if enabled {
+            eprintln!("Audio output is not available, rebuild with the audio-cpal feature");
+        }
+        Self {
+            io_registers: [0; 48],
+            frame_sequencer_step: 0,
+            last_sync: 0,
+        }
+    }
+
+    #[cfg(feature = "audio-cpal")]
+    pub fn new(enabled: bool) -> Self {
+        if enabled {
             let host = cpal::default_host();
             let device = host.default_output_device().expect("no output device available");
             let mut supported_configs_range = device.supported_output_configs()
@@ -144,12 +169,16 @@ impl Sound {
             let config: StreamConfig = supported_config.into();
             return Self {
                 io_registers: [0; 48],
+                frame_sequencer_step: 0,
+                last_sync: 0,
                 channel_two: Some(ChannelTwo::new(&device, &config)),
             };
         }
 
         Self {
             io_registers: [0; 48],
+            frame_sequencer_step: 0,
+            last_sync: 0,
             channel_two: None,
         }
     }
@@ -174,14 +203,48 @@ impl Sound {
         self.io_registers[(address - 0xFF10) as usize] = data;
     }
 
-    pub fn do_cycles(&mut self, cycles: Cycles) {
-        let mut count = 0.0;
-        while count < cycles.0 {
+    pub fn frame_sequencer_step(&self) -> u8 {
+        self.frame_sequencer_step
+    }
+
+    pub fn step_frame_sequencer(&mut self) {
+        self.frame_sequencer_step = (self.frame_sequencer_step + 1) & 0b111;
+    }
+
+    // Runs the channels up to the master clock time `now`. Nothing needs to be
+    // generated cycle by cycle when there is no audio output
+    pub fn catch_up(&mut self, now: u64) {
+        if self.has_output() && now > self.last_sync {
+            let mut remaining = now - self.last_sync;
+            while remaining > 0 {
+                let cycles = remaining.min(u32::MAX as u64);
+                self.do_cycles(TCycles(cycles as u32));
+                remaining -= cycles;
+            }
+        }
+        self.last_sync = now;
+    }
+
+    #[cfg(not(feature = "audio-cpal"))]
+    fn has_output(&self) -> bool {
+        false
+    }
+
+    #[cfg(feature = "audio-cpal")]
+    fn has_output(&self) -> bool {
+        self.channel_two.is_some()
+    }
+
+    pub fn do_cycles(&mut self, cycles: TCycles) {
+        for _ in 0..cycles.0 {
             self.cycle();
-            count += 1.0;
         }
     }
 
+    #[cfg(not(feature = "audio-cpal"))]
+    fn cycle(&mut self) {}
+
+    #[cfg(feature = "audio-cpal")]
     fn cycle(&mut self) {
         if self.channel_two.is_some() {
             let duty = self.channel_two_duty();
@@ -190,3 +253,18 @@ impl Sound {
         }
     }
 }
+
+impl SaveState for Sound {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_bytes(&self.io_registers);
+        state.write_u8(self.frame_sequencer_step);
+        state.write_u64(self.last_sync);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        state.read_bytes_into(&mut self.io_registers)?;
+        self.frame_sequencer_step = state.read_u8()? & 0b111;
+        self.last_sync = state.read_u64()?;
+        Ok(())
+    }
+}
@@ -1,9 +1,10 @@
-use crate::cpu::Cycles;
+use crate::cpu::TCycles;
 use crate::interrupts::{Interrupt, Interrupts};
 use crate::utils::{
     BitIndex,
     get_bit,
 };
+use crate::savestate::{SaveState, SaveStateError, StateReader, StateWriter};
 
 pub const TIMER_DIVIDER_REGISTER_ADDRESS: u16 = 0xFF04;
 pub const TIMER_COUNTER_ADDRESS: u16          = 0xFF05;
@@ -16,6 +17,7 @@ pub struct Timer {
     is_enabled: bool,
     control: u8,
     io_registers: [u8; 4],
+    last_sync: u64,
 }
 
 impl Timer {
@@ -27,6 +29,7 @@ impl Timer {
             prev_result: false,
             is_enabled: false,
             io_registers: [0; 4],
+            last_sync: 0,
         }
     }
 
@@ -65,48 +68,139 @@ impl Timer {
         self.divider.to_be_bytes()[0]
     }
     
-    pub fn do_cycles(&mut self, interrupts: &mut Interrupts, cycles: Cycles) {
+    // The timer is clocked once per T-cycle, or 4 times per T-cycle in double speed mode
+    fn speed_factor(double_speed: bool) -> u64 {
+        match double_speed {
+            true => 4,
+            false => 1,
+        }
+    }
+
+    // Runs the timer up to the master clock time `now`
+    pub fn catch_up(&mut self, interrupts: &mut Interrupts, now: u64, double_speed: bool) {
         self.is_enabled = self.is_timer_enabled();
         self.control = self.get_register(TIMER_CONTROL_ADDRESS);
-        let mut count = 0.0;
-        while count < cycles.0 {
-            self.cycle(interrupts);
-            count += 1.0;
+        if now > self.last_sync {
+            self.do_ticks(interrupts, (now - self.last_sync) * Timer::speed_factor(double_speed));
         }
+        self.last_sync = now;
     }
 
-    fn cycle(&mut self, interrupts: &mut Interrupts) {
-        self.divider = self.divider.wrapping_add(1);
+    pub fn do_cycles(&mut self, interrupts: &mut Interrupts, cycles: TCycles) {
+        self.is_enabled = self.is_timer_enabled();
+        self.control = self.get_register(TIMER_CONTROL_ADDRESS);
+        self.do_ticks(interrupts, cycles.0 as u64);
+    }
 
-        let result = self.is_enabled && self.get_tima_rate();
+    // Same result as incrementing DIV one tick at a time and checking the
+    // selected bit for a falling edge, but without looping over every tick
+    fn do_ticks(&mut self, interrupts: &mut Interrupts, ticks: u64) {
+        if ticks == 0 {
+            return;
+        }
+        let divider = self.divider as u64;
+        // Resetting DIV or disabling the timer can cause a falling edge on the first tick
+        let first_result = self.is_enabled && Timer::get_tima_rate(self.control, divider + 1);
+        let mut increments = (self.prev_result && !first_result) as u64;
+        if self.is_enabled {
+            let period = Timer::get_tima_period(self.control);
+            increments += (divider + ticks) / period - (divider + 1) / period;
+        }
+        self.divider = (divider + ticks) as u16;
+        self.prev_result = self.is_enabled && Timer::get_tima_rate(self.control, divider + ticks);
+        self.increment_tima(interrupts, increments);
+    }
 
-        if self.prev_result && !result {
-            let tima = self.get_register(TIMER_COUNTER_ADDRESS).wrapping_add(1);
-            if tima == 0 {
-                self.set_register(TIMER_COUNTER_ADDRESS, self.get_register(TIMER_MODULO_ADDRESS));
-                interrupts.request(Interrupt::Timer);
-            } else {
-                self.set_register(TIMER_COUNTER_ADDRESS, tima);
+    fn increment_tima(&mut self, interrupts: &mut Interrupts, increments: u64) {
+        let mut increments = increments;
+        let mut tima = self.get_register(TIMER_COUNTER_ADDRESS) as u64;
+        while increments > 0 {
+            let until_overflow = 0x100 - tima;
+            if increments < until_overflow {
+                tima += increments;
+                break;
             }
+            increments -= until_overflow;
+            tima = self.get_register(TIMER_MODULO_ADDRESS) as u64;
+            interrupts.request(Interrupt::Timer);
         }
+        self.set_register(TIMER_COUNTER_ADDRESS, tima as u8);
+    }
 
-        self.prev_result = result;
+    // Master clock time at which TIMA will overflow with the current registers.
+    // It is only used to wake up the timer, so erring on the early side is fine
+    pub fn next_overflow(&self, double_speed: bool) -> Option<u64> {
+        let enabled = self.is_timer_enabled();
+        let control = self.get_register(TIMER_CONTROL_ADDRESS);
+        let divider = self.divider as u64;
+        let needed = 0x100 - self.get_register(TIMER_COUNTER_ADDRESS) as u64;
+        let early_increment = self.prev_result && !(enabled && Timer::get_tima_rate(control, divider + 1));
+        let ticks = match enabled {
+            true => {
+                let period = Timer::get_tima_period(control);
+                let first_edge = period - (divider % period);
+                let edges_needed = match early_increment && first_edge != 1 {
+                    true => needed - 1,
+                    false => needed,
+                };
+                match edges_needed {
+                    0 => 1,
+                    _ => first_edge + (edges_needed - 1) * period,
+                }
+            },
+            false => match early_increment && needed == 1 {
+                true => 1,
+                false => return None,
+            },
+        };
+        let factor = Timer::speed_factor(double_speed);
+        Some(self.last_sync + ticks.div_ceil(factor))
     }
 
     fn is_timer_enabled(&self) -> bool {
         get_bit(self.get_register(TIMER_CONTROL_ADDRESS), BitIndex::I2)
     }
 
-    fn get_tima_rate(&self) -> bool {
-        let clock_select = self.control & 0b0000_0011;
+    fn get_tima_bit(control: u8) -> u64 {
+        let clock_select = control & 0b0000_0011;
         match clock_select {
-            0b00 => ((self.divider >> 9) & 1) == 1,
-            0b01 => ((self.divider >> 3) & 1) == 1,
-            0b10 => ((self.divider >> 5) & 1) == 1,
-            0b11 => ((self.divider >> 7) & 1) == 1,
+            0b00 => 9,
+            0b01 => 3,
+            0b10 => 5,
+            0b11 => 7,
             _ => unreachable!(),
         }
     }
+
+    // DIV ticks between two TIMA increments
+    fn get_tima_period(control: u8) -> u64 {
+        1 << (Timer::get_tima_bit(control) + 1)
+    }
+
+    fn get_tima_rate(control: u8, divider: u64) -> bool {
+        ((divider >> Timer::get_tima_bit(control)) & 1) == 1
+    }
+}
+
+impl SaveState for Timer {
+    fn save_state(&self, state: &mut StateWriter) {
+        state.write_u16(self.divider);
+        state.write_bool(self.prev_result);
+        state.write_bool(self.is_enabled);
+        state.write_u8(self.control);
+        state.write_bytes(&self.io_registers);
+        state.write_u64(self.last_sync);
+    }
+
+    fn load_state(&mut self, state: &mut StateReader) -> Result<(), SaveStateError> {
+        self.divider = state.read_u16()?;
+        self.prev_result = state.read_bool()?;
+        self.is_enabled = state.read_bool()?;
+        self.control = state.read_u8()?;
+        state.read_bytes_into(&mut self.io_registers)?;
+        self.last_sync = state.read_u64()?;
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -120,22 +214,22 @@ mod tests {
         timer.set_register(TIMER_CONTROL_ADDRESS, 0b101);
         timer.set_register(TIMER_COUNTER_ADDRESS, 0);
         timer.set_div(0b10111);
-        timer.do_cycles(&mut interrupts, Cycles(1.0));
+        timer.do_cycles(&mut interrupts, TCycles(1));
         assert_eq!(timer.div(), 0b11000);
         assert_eq!(timer.prev_result(), true);
         assert_eq!(timer.get_register(TIMER_COUNTER_ADDRESS), 0);
-        assert_eq!(interrupts.get(Interrupt::Timer), false);
+        assert!(!interrupts.get(Interrupt::Timer));
 
-        timer.do_cycles(&mut interrupts, Cycles(7.0));
+        timer.do_cycles(&mut interrupts, TCycles(7));
         assert_eq!(timer.div(), 0b11111);
         assert_eq!(timer.prev_result(), true);
         assert_eq!(timer.get_register(TIMER_COUNTER_ADDRESS), 0);
-        assert_eq!(interrupts.get(Interrupt::Timer), false);
-        timer.do_cycles(&mut interrupts, Cycles(1.0));
+        assert!(!interrupts.get(Interrupt::Timer));
+        timer.do_cycles(&mut interrupts, TCycles(1));
         assert_eq!(timer.div(), 0b100000);
         assert_eq!(timer.get_register(TIMER_COUNTER_ADDRESS), 1);
         assert_eq!(timer.prev_result(), false);
-        assert_eq!(interrupts.get(Interrupt::Timer), false);
+        assert!(!interrupts.get(Interrupt::Timer));
     }
 
     #[test]
@@ -145,10 +239,10 @@ mod tests {
         timer.set_register(TIMER_CONTROL_ADDRESS, 0b101);
         timer.set_register(TIMER_COUNTER_ADDRESS, 0xFF);
         timer.set_div(0b10111);
-        timer.do_cycles(&mut interrupts, Cycles(9.0));
+        timer.do_cycles(&mut interrupts, TCycles(9));
         assert_eq!(timer.div(), 0b100000);
         assert_eq!(timer.get_register(TIMER_COUNTER_ADDRESS), 0x00);
-        assert_eq!(interrupts.get(Interrupt::Timer), true);
+        assert!(interrupts.get(Interrupt::Timer));
     }
 
     #[test]
@@ -158,12 +252,56 @@ mod tests {
         timer.set_register(TIMER_CONTROL_ADDRESS, 0b101);
         timer.set_register(TIMER_COUNTER_ADDRESS, 0);
         timer.set_div(0b11000);
-        timer.do_cycles(&mut interrupts, Cycles(1.0));
+        timer.do_cycles(&mut interrupts, TCycles(1));
         assert_eq!(timer.div(), 0b11001);
         assert_eq!(timer.get_register(TIMER_COUNTER_ADDRESS), 0);
         timer.set_register(TIMER_CONTROL_ADDRESS, 0b001);
-        timer.do_cycles(&mut interrupts, Cycles(1.0));
+        timer.do_cycles(&mut interrupts, TCycles(1));
         assert_eq!(timer.div(), 0b11010);
         assert_eq!(timer.get_register(TIMER_COUNTER_ADDRESS), 1);
     }
+
+    #[test]
+    fn test_bulk_cycles_match_single_steps() {
+        for control in [0b100, 0b101, 0b110, 0b111] {
+            let mut bulk = Timer::new();
+            let mut single = Timer::new();
+            let mut bulk_interrupts = Interrupts::new();
+            let mut single_interrupts = Interrupts::new();
+            for timer in [&mut bulk, &mut single] {
+                timer.set_register(TIMER_CONTROL_ADDRESS, control);
+                timer.set_register(TIMER_MODULO_ADDRESS, 0xF0);
+                timer.set_register(TIMER_COUNTER_ADDRESS, 0xE0);
+                timer.set_div(0xFF00);
+            }
+            bulk.do_cycles(&mut bulk_interrupts, TCycles(5000));
+            for _ in 0..5000 {
+                single.do_cycles(&mut single_interrupts, TCycles(1));
+            }
+            assert_eq!(bulk.div(), single.div());
+            assert_eq!(bulk.prev_result(), single.prev_result());
+            assert_eq!(bulk.get_register(TIMER_COUNTER_ADDRESS), single.get_register(TIMER_COUNTER_ADDRESS));
+            assert_eq!(bulk_interrupts.get(Interrupt::Timer), single_interrupts.get(Interrupt::Timer));
+        }
+    }
+
+    #[test]
+    fn test_next_overflow() {
+        let mut timer = Timer::new();
+        let mut interrupts = Interrupts::new();
+        timer.set_register(TIMER_CONTROL_ADDRESS, 0b101);
+        timer.set_register(TIMER_COUNTER_ADDRESS, 0xFE);
+        timer.catch_up(&mut interrupts, 0, false);
+        // TIMA increments every 16 ticks, the second increment overflows
+        assert_eq!(timer.next_overflow(false), Some(32));
+        assert_eq!(timer.next_overflow(true), Some(8));
+        timer.catch_up(&mut interrupts, 31, false);
+        assert!(!interrupts.get(Interrupt::Timer));
+        timer.catch_up(&mut interrupts, 32, false);
+        assert!(interrupts.get(Interrupt::Timer));
+
+        timer.set_register(TIMER_CONTROL_ADDRESS, 0b001);
+        timer.catch_up(&mut interrupts, 40, false);
+        assert_eq!(timer.next_overflow(false), None);
+    }
 }